@@ -1,5 +1,13 @@
 use crate::token::Token;
+use crate::token_type::TokenType;
+use crate::natives::NativeFunction;
+use crate::map::LoxMap;
+use crate::module::Module;
+use crate::function::LoxFunction;
+use crate::ast_stmt::Stmt;
+use std::cell::RefCell;
 use std::fmt;
+use std::rc::Rc;
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct Unary {
@@ -33,6 +41,24 @@ impl Binary {
     }
 }
 
+// `and` and `or`, the right operand only runs when the left one doesn't decide
+#[derive(Clone, PartialEq, Debug)]
+pub struct Logical {
+    pub op: Token,
+    pub lhs: Box<Expr>,
+    pub rhs: Box<Expr>,
+}
+
+impl Logical {
+    pub fn new(op: Token, lhs: Box<Expr>, rhs: Box<Expr>) -> Self {
+        Logical {
+            op,
+            lhs,
+            rhs,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct Assign {
     pub name: Token,
@@ -81,14 +107,16 @@ pub struct Ternary {
     pub cond: Box<Expr>,
     pub then_expr: Box<Expr>,
     pub else_expr: Box<Expr>,
+    pub line: usize,
 }
 
 impl Ternary {
-    pub fn new(cond: Box<Expr>, then_expr: Box<Expr>, else_expr: Box<Expr>) -> Self {
+    pub fn new(cond: Box<Expr>, then_expr: Box<Expr>, else_expr: Box<Expr>, line: usize) -> Self {
         Ternary {
             cond,
             then_expr,
             else_expr,
+            line,
         }
     }
 }
@@ -106,20 +134,331 @@ impl Variable {
     }
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct List {
+    pub elements: Vec<Expr>,
+}
+
+impl List {
+   pub fn new(elements: Vec<Expr>) -> Self {
+        List {
+            elements,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Map {
+    pub brace: Token,
+    pub entries: Vec<(Expr, Expr)>,
+}
+
+impl Map {
+   pub fn new(brace: Token, entries: Vec<(Expr, Expr)>) -> Self {
+        Map {
+            brace,
+            entries,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Index {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+}
+
+impl Index {
+   pub fn new(object: Box<Expr>, bracket: Token, index: Box<Expr>) -> Self {
+        Index {
+            object,
+            bracket,
+            index,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Slice {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub start: Option<Box<Expr>>,
+    pub end: Option<Box<Expr>>,
+}
+
+impl Slice {
+   pub fn new(object: Box<Expr>, bracket: Token, start: Option<Box<Expr>>, end: Option<Box<Expr>>) -> Self {
+        Slice {
+            object,
+            bracket,
+            start,
+            end,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SetIndex {
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+    pub value: Box<Expr>,
+}
+
+impl SetIndex {
+   pub fn new(object: Box<Expr>, bracket: Token, index: Box<Expr>, value: Box<Expr>) -> Self {
+        SetIndex {
+            object,
+            bracket,
+            index,
+            value,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Call {
+    pub callee: Box<Expr>,
+    pub paren: Token,
+    pub arguments: Vec<Expr>,
+}
+
+impl Call {
+   pub fn new(callee: Box<Expr>, paren: Token, arguments: Vec<Expr>) -> Self {
+        Call {
+            callee,
+            paren,
+            arguments,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Get {
+    pub object: Box<Expr>,
+    pub name: Token,
+}
+
+impl Get {
+   pub fn new(object: Box<Expr>, name: Token) -> Self {
+        Get {
+            object,
+            name,
+        }
+    }
+}
+
+// `x += v`, `++x` and `x++`, the target (a variable or an element) is evaluated once
+#[derive(Clone, PartialEq, Debug)]
+pub struct Compound {
+    pub target: Box<Expr>,
+    pub operator: Token,
+    pub value: Box<Expr>,
+    pub postfix: bool,
+}
+
+impl Compound {
+   pub fn new(target: Box<Expr>, operator: Token, value: Box<Expr>, postfix: bool) -> Self {
+        Compound {
+            target,
+            operator,
+            value,
+            postfix,
+        }
+    }
+
+    // The binary operator applied to the target and the value
+    pub fn binary_op(&self) -> TokenType {
+        match self.operator.kind {
+            TokenType::PLUSEQUAL | TokenType::PLUSPLUS => TokenType::PLUS,
+            TokenType::MINUSEQUAL | TokenType::MINUSMINUS => TokenType::MINUS,
+            TokenType::STAREQUAL => TokenType::STAR,
+            TokenType::SLASHEQUAL => TokenType::SLASH,
+            ref other => other.clone(),
+        }
+    }
+
+    pub fn is_increment(&self) -> bool {
+        matches!(self.operator.kind, TokenType::PLUSPLUS | TokenType::MINUSMINUS)
+    }
+}
+
+// Interpolated string, the parts are joined by their display form
+#[derive(Clone, PartialEq, Debug)]
+pub struct Format {
+    pub parts: Vec<Expr>,
+    pub line: usize,
+}
+
+impl Format {
+   pub fn new(parts: Vec<Expr>, line: usize) -> Self {
+        Format {
+            parts,
+            line,
+        }
+    }
+}
+
+// `fun (a, b) { ... }`, the arrow form `(a, b) => expr` has a single return as body.
+// The annotations of the parameters are in the same order as them
+#[derive(Clone, PartialEq, Debug)]
+pub struct Lambda {
+    pub keyword: Token,
+    pub params: Vec<Token>,
+    pub annotations: Vec<Option<Token>>,
+    pub returns: Option<Token>,
+    pub body: Rc<Vec<Stmt>>,
+}
+
+impl Lambda {
+   pub fn new(
+        keyword: Token,
+        params: Vec<Token>,
+        annotations: Vec<Option<Token>>,
+        returns: Option<Token>,
+        body: Vec<Stmt>,
+    ) -> Self {
+        Lambda {
+            keyword,
+            params,
+            annotations,
+            returns,
+            body: Rc::new(body),
+        }
+    }
+}
+
+// Names usable as type patterns in a match arm
+pub const TYPE_PATTERNS: [&str; 6] = ["number", "string", "bool", "list", "map", "function"];
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Pattern {
+    LITERAL(Literal),
+    TYPE(Token),
+    BINDING(Token),
+    WILDCARD,
+}
+
+// `pattern ( "|" pattern )* ( "if" guard )? => body`, the token is where the arm starts
+#[derive(Clone, PartialEq, Debug)]
+pub struct MatchArm {
+    pub token: Token,
+    pub patterns: Vec<Pattern>,
+    pub guard: Option<Box<Expr>>,
+    pub body: Box<Expr>,
+}
+
+impl MatchArm {
+   pub fn new(token: Token, patterns: Vec<Pattern>, guard: Option<Box<Expr>>, body: Box<Expr>) -> Self {
+        MatchArm {
+            token,
+            patterns,
+            guard,
+            body,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Match {
+    pub keyword: Token,
+    pub subject: Box<Expr>,
+    pub arms: Vec<MatchArm>,
+}
+
+impl Match {
+   pub fn new(keyword: Token, subject: Box<Expr>, arms: Vec<MatchArm>) -> Self {
+        Match {
+            keyword,
+            subject,
+            arms,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub enum Literal {
     BOOL(bool),
     NUMBER(f64),
+    INTEGER(i64),
     STRING(String),
+    LIST(Rc<RefCell<Vec<Literal>>>),
+    MAP(Rc<RefCell<LoxMap>>),
+    NATIVE(NativeFunction),
+    FUNCTION(Rc<LoxFunction>),
+    MODULE(Rc<Module>),
     NIL,
 }
 
+impl Literal {
+    // Numbers print like `%g` in clox: integers without a fraction and very large or
+    // small magnitudes with an exponent, but keeping all the digits needed to read them back
+    pub fn format_number(val: f64) -> String {
+        if val.is_nan() {
+            return "nan".to_string();
+        }
+
+        if val.is_infinite() {
+            return if val > 0.0 { "inf".to_string() } else { "-inf".to_string() };
+        }
+
+        let scientific = format!("{:e}", val);
+        let (mantissa, exponent) = match scientific.split_once('e') {
+            Some(parts) => parts,
+            None => return val.to_string(),
+        };
+
+        let exponent: i32 = exponent.parse().unwrap_or(0);
+        if (-4..16).contains(&exponent) {
+            return val.to_string();
+        }
+
+        format!("{}e{}{:02}", mantissa, if exponent < 0 { '-' } else { '+' }, exponent.abs())
+    }
+
+    // Whether the value has the type named in a type pattern, integers are numbers
+    pub fn is_type(&self, name: &str) -> bool {
+        matches!(
+            (name, self),
+            ("number", Literal::NUMBER(_) | Literal::INTEGER(_))
+                | ("string", Literal::STRING(_))
+                | ("bool", Literal::BOOL(_))
+                | ("list", Literal::LIST(_))
+                | ("map", Literal::MAP(_))
+                | ("function", Literal::FUNCTION(_) | Literal::NATIVE(_))
+        )
+    }
+
+    // Display form inside containers, where strings are quoted
+    pub fn quoted(&self) -> String {
+        match self {
+            Literal::STRING(val) => format!("\"{}\"", val),
+            other => other.to_string(),
+        }
+    }
+}
+
 impl fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
        match self {
            Literal::BOOL(val) => write!(f, "{}", val),
-           Literal::NUMBER(val) => write!(f, "{}", val), // Check print of this one
+           Literal::NUMBER(val) => write!(f, "{}", Literal::format_number(*val)),
+           Literal::INTEGER(val) => write!(f, "{}", val),
            Literal::STRING(val) => write!(f, "{}", val),
+           Literal::LIST(list) => {
+               let elements: Vec<String> = list.borrow().iter().map(Literal::quoted).collect();
+               write!(f, "[{}]", elements.join(", "))
+           },
+           Literal::MAP(map) => {
+               let entries: Vec<String> = map.borrow().entries().iter()
+                   .map(|(k, v)| format!("{}: {}", k.quoted(), v.quoted()))
+                   .collect();
+               write!(f, "{{{}}}", entries.join(", "))
+           },
+           Literal::NATIVE(native) => write!(f, "<native fn {}>", native.name),
+           Literal::FUNCTION(_) => write!(f, "<fn lambda>"),
+           Literal::MODULE(module) => write!(f, "<module {}>", module.name),
            Literal::NIL => write!(f, "NIL"),
        }
     }
@@ -131,8 +470,20 @@ pub enum Expr {
     TERNARY(Ternary),
     ASSIGN(Assign),
     BINARY(Binary),
+    LOGICAL(Logical),
     GROUPING(Grouping),
     LITERAL(Literal),
     UNARY(Unary),
     VARIABLE(Variable),
+    LIST(List),
+    MAP(Map),
+    INDEX(Index),
+    SLICE(Slice),
+    SETINDEX(SetIndex),
+    COMPOUND(Compound),
+    CALL(Call),
+    GET(Get),
+    FORMAT(Format),
+    LAMBDA(Lambda),
+    MATCH(Match),
 }
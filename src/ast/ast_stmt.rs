@@ -7,17 +7,49 @@ pub enum Stmt {
     EXPRESSION(Expression),
     PRINT(Print),
     VAR(Var),
+    IF(If),
+    WHILE(While),
+    FORIN(ForIn),
+    BREAK(Break),
+    CONTINUE(Continue),
+    IMPORT(Import),
+    THROW(Throw),
+    TRY(Try),
+    RETURN(Return),
+}
+
+impl Stmt {
+    // Source line where the statement starts, used by the instrumentation hooks
+    pub fn line(&self) -> usize {
+        match self {
+            Stmt::BLOCK(b) => b.line,
+            Stmt::EXPRESSION(e) => e.line,
+            Stmt::PRINT(p) => p.line,
+            Stmt::VAR(v) => v.name.line,
+            Stmt::IF(i) => i.line,
+            Stmt::WHILE(w) => w.line,
+            Stmt::FORIN(f) => f.name.line,
+            Stmt::BREAK(b) => b.keyword.line,
+            Stmt::CONTINUE(c) => c.keyword.line,
+            Stmt::IMPORT(i) => i.keyword.line,
+            Stmt::THROW(t) => t.keyword.line,
+            Stmt::TRY(t) => t.keyword.line,
+            Stmt::RETURN(r) => r.keyword.line,
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct Expression {
     pub expr: Box<Expr>,
+    pub line: usize,
 }
 
 impl Expression {
-   pub fn new(expr: Box<Expr>) -> Self {
+   pub fn new(expr: Box<Expr>, line: usize) -> Self {
         Expression {
             expr,
+            line,
         }
     }
 }
@@ -25,12 +57,14 @@ impl Expression {
 #[derive(Clone, PartialEq, Debug)]
 pub struct Print {
     pub expr: Box<Expr>,
+    pub line: usize,
 }
 
 impl Print {
-   pub fn new(expr: Box<Expr>) -> Self {
+   pub fn new(expr: Box<Expr>, line: usize) -> Self {
         Print {
             expr,
+            line,
         }
     }
 }
@@ -38,14 +72,18 @@ impl Print {
 #[derive(Clone, PartialEq, Debug)]
 pub struct Var {
     pub name: Token,
+    pub annotation: Option<Token>,
     pub initializer: Option<Box<Expr>>,
+    pub constant: bool,
 }
 
 impl Var {
-   pub fn new(name: Token, initializer: Option<Box<Expr>>) -> Self {
+   pub fn new(name: Token, annotation: Option<Token>, initializer: Option<Box<Expr>>, constant: bool) -> Self {
         Var {
             name,
+            annotation,
             initializer,
+            constant,
         }
     }
 }
@@ -53,14 +91,171 @@ impl Var {
 #[derive(Clone, PartialEq, Debug)]
 pub struct Block {
     pub statements: Vec<Stmt>,
+    pub line: usize,
 }
 
 impl Block {
-   pub fn new(statements: Vec<Stmt>) -> Self {
+   pub fn new(statements: Vec<Stmt>, line: usize) -> Self {
         Block {
             statements,
+            line,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct If {
+    pub cond: Box<Expr>,
+    pub then_branch: Box<Stmt>,
+    pub else_branch: Option<Box<Stmt>>,
+    pub line: usize,
+}
+
+impl If {
+   pub fn new(cond: Box<Expr>, then_branch: Box<Stmt>, else_branch: Option<Box<Stmt>>, line: usize) -> Self {
+        If {
+            cond,
+            then_branch,
+            else_branch,
+            line,
+        }
+    }
+}
+
+// Loops from `for` keep the increment apart from the body, so `continue` still runs it
+#[derive(Clone, PartialEq, Debug)]
+pub struct While {
+    pub cond: Box<Expr>,
+    pub body: Box<Stmt>,
+    pub increment: Option<Box<Expr>>,
+    pub line: usize,
+}
+
+impl While {
+   pub fn new(cond: Box<Expr>, body: Box<Stmt>, increment: Option<Box<Expr>>, line: usize) -> Self {
+        While {
+            cond,
+            body,
+            increment,
+            line,
         }
     }
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct ForIn {
+    pub name: Token,
+    pub iterable: Box<Expr>,
+    pub body: Box<Stmt>,
+}
+
+impl ForIn {
+   pub fn new(name: Token, iterable: Box<Expr>, body: Box<Stmt>) -> Self {
+        ForIn {
+            name,
+            iterable,
+            body,
+        }
+    }
+}
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct Break {
+    pub keyword: Token,
+}
+
+impl Break {
+   pub fn new(keyword: Token) -> Self {
+        Break {
+            keyword,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Continue {
+    pub keyword: Token,
+}
+
+impl Continue {
+   pub fn new(keyword: Token) -> Self {
+        Continue {
+            keyword,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Import {
+    pub keyword: Token,
+    pub path: Token,
+    pub alias: Option<Token>,
+}
+
+impl Import {
+   pub fn new(keyword: Token, path: Token, alias: Option<Token>) -> Self {
+        Import {
+            keyword,
+            path,
+            alias,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Throw {
+    pub keyword: Token,
+    pub value: Box<Expr>,
+}
+
+impl Throw {
+   pub fn new(keyword: Token, value: Box<Expr>) -> Self {
+        Throw {
+            keyword,
+            value,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Return {
+    pub keyword: Token,
+    pub value: Option<Box<Expr>>,
+}
+
+impl Return {
+   pub fn new(keyword: Token, value: Option<Box<Expr>>) -> Self {
+        Return {
+            keyword,
+            value,
+        }
+    }
+}
+
+// At least one of the catch and the finally clauses is present
+#[derive(Clone, PartialEq, Debug)]
+pub struct Try {
+    pub keyword: Token,
+    pub body: Vec<Stmt>,
+    pub catch_name: Option<Token>,
+    pub catch_body: Option<Vec<Stmt>>,
+    pub finally_body: Option<Vec<Stmt>>,
+}
+
+impl Try {
+   pub fn new(
+        keyword: Token,
+        body: Vec<Stmt>,
+        catch_name: Option<Token>,
+        catch_body: Option<Vec<Stmt>>,
+        finally_body: Option<Vec<Stmt>>,
+    ) -> Self {
+        Try {
+            keyword,
+            body,
+            catch_name,
+            catch_body,
+            finally_body,
+        }
+    }
+}
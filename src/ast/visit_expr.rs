@@ -1,4 +1,5 @@
-use crate::ast_expr::{Expr, Comma, Ternary, Literal, Unary, Binary, Grouping, Variable, Assign};
+use crate::ast_expr::{Expr, Comma, Ternary, Literal, Unary, Binary, Logical, Grouping, Variable, Assign};
+use crate::ast_expr::{List, Map, Index, Slice, SetIndex, Compound, Call, Get, Format, Lambda, Match};
 
 pub trait ExprVisitor<T> {
     fn visit_expr(&mut self, e: &Expr) -> T;
@@ -8,6 +9,18 @@ pub trait ExprVisitor<T> {
     fn visit_literal(&mut self, t: &Literal) -> T;
     fn visit_unary(&mut self, u: &Unary) -> T;
     fn visit_binary(&mut self, b: &Binary) -> T;
+    fn visit_logical(&mut self, l: &Logical) -> T;
     fn visit_grouping(&mut self, g: &Grouping) -> T;
     fn visit_variable(&mut self, v: &Variable) -> T;
+    fn visit_list(&mut self, l: &List) -> T;
+    fn visit_map(&mut self, m: &Map) -> T;
+    fn visit_index(&mut self, i: &Index) -> T;
+    fn visit_slice(&mut self, s: &Slice) -> T;
+    fn visit_set_index(&mut self, s: &SetIndex) -> T;
+    fn visit_compound(&mut self, c: &Compound) -> T;
+    fn visit_call(&mut self, c: &Call) -> T;
+    fn visit_get(&mut self, g: &Get) -> T;
+    fn visit_format(&mut self, f: &Format) -> T;
+    fn visit_lambda(&mut self, l: &Lambda) -> T;
+    fn visit_match(&mut self, m: &Match) -> T;
 }
@@ -1,4 +1,6 @@
 use crate::ast_stmt::{Stmt, Expression, Print, Var, Block};
+use crate::ast_stmt::{If, While, ForIn, Break, Continue, Import};
+use crate::ast_stmt::{Throw, Try, Return};
 
 pub trait StmtVisitor<T> {
     fn visit_stmt(&mut self, s: &Stmt) -> T;
@@ -6,5 +8,13 @@ pub trait StmtVisitor<T> {
     fn visit_print(&mut self, p: &Print) -> T;
     fn visit_var(&mut self, v: &Var) -> T;
     fn visit_block(&mut self, b: &Block) -> T;
+    fn visit_if(&mut self, i: &If) -> T;
+    fn visit_while(&mut self, w: &While) -> T;
+    fn visit_for_in(&mut self, f: &ForIn) -> T;
+    fn visit_break(&mut self, b: &Break) -> T;
+    fn visit_continue(&mut self, c: &Continue) -> T;
+    fn visit_import(&mut self, i: &Import) -> T;
+    fn visit_throw(&mut self, t: &Throw) -> T;
+    fn visit_try(&mut self, t: &Try) -> T;
+    fn visit_return(&mut self, r: &Return) -> T;
 }
-
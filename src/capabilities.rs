@@ -0,0 +1,56 @@
+use std::collections::HashSet;
+use std::fmt;
+
+// Access to the outside of the interpreter, needed by some natives
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Capability {
+    FS,
+    ENV,
+    TIME,
+    RANDOM,
+}
+
+pub const CAPABILITIES: [Capability; 4] = [Capability::FS, Capability::ENV, Capability::TIME, Capability::RANDOM];
+
+impl Capability {
+    pub fn from_name(name: &str) -> Option<Capability> {
+        CAPABILITIES.iter().find(|cap| cap.to_string() == name).cloned()
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Capability::FS => write!(f, "fs"),
+            Capability::ENV => write!(f, "env"),
+            Capability::TIME => write!(f, "time"),
+            Capability::RANDOM => write!(f, "random"),
+        }
+    }
+}
+
+// Capabilities granted to a script, an interpreter starts with none
+#[derive(Clone, Default, Debug)]
+pub struct Capabilities {
+    granted: HashSet<Capability>,
+}
+
+impl Capabilities {
+    pub fn none() -> Self {
+        Capabilities::default()
+    }
+
+    pub fn all() -> Self {
+        Capabilities {
+            granted: CAPABILITIES.iter().cloned().collect(),
+        }
+    }
+
+    pub fn grant(&mut self, capability: Capability) {
+        self.granted.insert(capability);
+    }
+
+    pub fn allows(&self, capability: Capability) -> bool {
+        self.granted.contains(&capability)
+    }
+}
@@ -1,32 +1,57 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 use crate::ast_expr::*;
 
+// The scopes are shared, so a closure holding a clone of the environment sees (and
+// makes) the same changes as the code that created it
+#[derive(Clone)]
 pub struct Environment {
-    values: HashMap<String, Literal>,
+    values: Rc<RefCell<HashMap<String, Literal>>>,
+    constants: Rc<RefCell<HashSet<String>>>,
     enclosing: Option<Box<Self>>,
 }
 
 impl Environment {
     pub fn new() -> Self {
 
-        let values = HashMap::new();
+        let values = Rc::new(RefCell::new(HashMap::new()));
         let enclosing = None;
 
         Environment {
             values,
+            constants: Rc::new(RefCell::new(HashSet::new())),
             enclosing
         }
     }
 
+    // New scope nested in the given one
+    pub fn new_enclosed(enclosing: Environment) -> Self {
+        Environment {
+            values: Rc::new(RefCell::new(HashMap::new())),
+            constants: Rc::new(RefCell::new(HashSet::new())),
+            enclosing: Some(Box::new(enclosing)),
+        }
+    }
+
+    // Drops this scope, returning the one it was nested in
+    pub fn into_enclosing(self) -> Environment {
+        match self.enclosing {
+            Some(env) => *env,
+            None => panic!("Global scope has no enclosing scope"),
+        }
+    }
+
     pub fn add_enclosing(env: & mut Environment) {
 
-        let values = HashMap::new();
+        let values = Rc::new(RefCell::new(HashMap::new()));
         let enclosing = None;
 
         let new_enclosing = Some(
             Box::new(
                 Environment {
                     values,
+                    constants: Rc::new(RefCell::new(HashSet::new())),
                     enclosing
                 }
             )
@@ -36,30 +61,83 @@ impl Environment {
     }
 
     pub fn define(&mut self, name: String, value: Literal) {
-        self.values.insert(name, value);
+        self.constants.borrow_mut().remove(&name);
+        self.values.borrow_mut().insert(name, value);
+    }
+
+    pub fn define_constant(&mut self, name: String, value: Literal) {
+        self.constants.borrow_mut().insert(name.clone());
+        self.values.borrow_mut().insert(name, value);
     }
 
-    pub fn get(&mut self, name: &String) -> &Literal {
-        if self.values.contains_key(name) {
-            return &self.values[name];
+    // Whether the innermost definition of the name is a constant
+    pub fn is_constant(&self, name: &String) -> bool {
+        if self.values.borrow().contains_key(name) {
+            return self.constants.borrow().contains(name);
         }
 
-        match &mut self.enclosing {
+        match &self.enclosing {
+            Some(env) => env.is_constant(name),
+            None => false,
+        }
+    }
+
+    pub fn get(&self, name: &String) -> Option<Literal> {
+        if let Some(value) = self.values.borrow().get(name) {
+            return Some(value.clone());
+        }
+
+        match &self.enclosing {
             Some(env) => return env.get(name),
-            None => panic!("Undefined variable {}", name)
+            None => None,
+        }
+    }
+
+    // Variables defined in this scope only, sorted by name
+    pub fn locals(&self) -> Vec<(String, Literal)> {
+        let mut vars: Vec<(String, Literal)> = self.values
+            .borrow()
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        vars.sort_by(|a, b| a.0.cmp(&b.0));
+        vars
+    }
+
+    // Variables visible from this scope except the global ones, innermost definitions first
+    pub fn enclosed_variables(&self) -> Vec<(String, Literal)> {
+        match &self.enclosing {
+            Some(env) => {
+                let mut vars = self.locals();
+                for (name, value) in env.enclosed_variables() {
+                    if !vars.iter().any(|(n, _)| *n == name) {
+                        vars.push((name, value));
+                    }
+                }
+                vars
+            },
+            None => Vec::new(),
+        }
+    }
+
+    // Variables of the outermost scope, sorted by name
+    pub fn globals(&self) -> Vec<(String, Literal)> {
+        match &self.enclosing {
+            Some(env) => env.globals(),
+            None => self.locals(),
         }
     }
 
-    // Review this function
-    pub fn assign(&mut self, name: String, value: Literal) {
-        if self.values.contains_key(&name) {
-            self.values.insert(name, value);
-            return;
+    // False if the variable is not defined in any scope
+    pub fn assign(&mut self, name: String, value: Literal) -> bool {
+        if let Some(slot) = self.values.borrow_mut().get_mut(&name) {
+            *slot = value;
+            return true;
         }
 
         match &mut self.enclosing {
             Some(env) => env.assign(name, value),
-            None => panic!("Undefined variable {}", name)
+            None => false,
         }
     }
 }
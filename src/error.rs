@@ -29,17 +29,102 @@ impl fmt::Display for ParseError {
     }
 }
 
+// Malformed token, scanning goes on after it
+#[derive(Clone, Debug)]
+pub struct ScanError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl ScanError {
+    pub fn new(message: String, line: usize, column: usize) -> Self {
+        ScanError {
+            message,
+            line,
+            column
+        }
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{} Scan error: {}", self.line, self.column, self.message)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TypeError {
+    pub message: String,
+    pub token: Token,
+}
+
+impl TypeError {
+    pub fn new(message: String, token: Token) -> Self {
+        TypeError {
+            message,
+            token
+        }
+    }
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.token.value {
+            Some(value) => write!(f, "{} at '{}' Type error: {}", self.token.line, value, self.message),
+            None => write!(f, "{} at '{}' Type error: {}", self.token.line, self.token.get_lexeme(), self.message),
+        }
+    }
+}
+
+// Reported like the errors, but the script still runs
+#[derive(Clone, Debug)]
+pub struct Warning {
+    pub message: String,
+    pub token: Token,
+}
+
+impl Warning {
+    pub fn new(message: String, token: Token) -> Self {
+        Warning {
+            message,
+            token
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.token.value {
+            Some(value) => write!(f, "{} at '{}' Warning: {}", self.token.line, value, self.message),
+            None => write!(f, "{} at '{}' Warning: {}", self.token.line, self.token.get_lexeme(), self.message),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub enum Operands {
     UNARY(Literal),
     BINARY(Literal, Literal),
     VAR,
+    CALL,
+    IMPORT,
+    // Value thrown by the script, unwinding up to the innermost try
+    THROW(Literal),
+    // Loop control, unwinding up to the innermost loop
+    BREAK,
+    CONTINUE,
+    // Value returned, unwinding up to the innermost call
+    RETURN(Literal),
+    // Resource limit of the sandbox reached, stopping the run
+    LIMIT,
 }
 
 #[derive(Clone, Debug)]
 pub struct EvaluationError {
     pub message: String,
     pub operands: Operands,
+    pub line: Option<usize>,
 }
 
 impl EvaluationError {
@@ -47,9 +132,28 @@ impl EvaluationError {
         EvaluationError {
             message,
             operands,
+            line: None,
         }
     }
 
+    // Line of the innermost statement the error went through
+    pub fn at_line(mut self, line: usize) -> Self {
+        if self.line.is_none() {
+            self.line = Some(line);
+        }
+        self
+    }
+
+    // Loop control and returns are not errors, so catch clauses let them through
+    pub fn is_control_flow(&self) -> bool {
+        matches!(self.operands, Operands::BREAK | Operands::CONTINUE | Operands::RETURN(_))
+    }
+
+    // Limits stop the run, so catch clauses let them through too
+    pub fn is_limit(&self) -> bool {
+        matches!(self.operands, Operands::LIMIT)
+    }
+
     pub fn new_unary(message: String, lit: Literal) -> Self {
         EvaluationError::new(message, Operands::UNARY(lit))
     }
@@ -61,13 +165,42 @@ impl EvaluationError {
     pub fn new_var(message: String) -> Self {
         EvaluationError::new(message, Operands::VAR)
     }
+
+    pub fn new_call(message: String) -> Self {
+        EvaluationError::new(message, Operands::CALL)
+    }
+
+    pub fn new_import(message: String) -> Self {
+        EvaluationError::new(message, Operands::IMPORT)
+    }
+
+    pub fn new_throw(value: Literal) -> Self {
+        EvaluationError::new("Uncaught exception".to_string(), Operands::THROW(value))
+    }
+
+    pub fn new_break() -> Self {
+        EvaluationError::new("'break' outside of a loop".to_string(), Operands::BREAK)
+    }
+
+    pub fn new_continue() -> Self {
+        EvaluationError::new("'continue' outside of a loop".to_string(), Operands::CONTINUE)
+    }
+
+    pub fn new_return(value: Literal) -> Self {
+        EvaluationError::new("'return' outside of a function".to_string(), Operands::RETURN(value))
+    }
+
+    pub fn new_limit(message: String) -> Self {
+        EvaluationError::new(message, Operands::LIMIT)
+    }
     
     fn literal_to_message(lit: Literal) -> String {
         match &lit {
             Literal::NIL => "NIL".to_string(),
             Literal::BOOL(val) => val.to_string(),
-            Literal::NUMBER(val) => val.to_string(),
-            Literal::STRING(val) => val.to_string()
+            Literal::NUMBER(val) => Literal::format_number(*val),
+            Literal::STRING(val) => val.to_string(),
+            other => other.to_string(),
         }
     }
 }
@@ -92,7 +225,38 @@ impl fmt::Display for EvaluationError {
                 f,
                 "VAR - {}",
                 self.message
-            ) 
+            ),
+            Operands::CALL => write!(
+                f,
+                "CALL - {}",
+                self.message
+            ),
+            Operands::IMPORT => write!(
+                f,
+                "IMPORT - {}",
+                self.message
+            ),
+            Operands::THROW(lit) => write!(
+                f,
+                "THROW - {} {}",
+                self.message,
+                lit.quoted()
+            ),
+            Operands::BREAK | Operands::CONTINUE => write!(
+                f,
+                "LOOP - {}",
+                self.message
+            ),
+            Operands::RETURN(_) => write!(
+                f,
+                "RETURN - {}",
+                self.message
+            ),
+            Operands::LIMIT => write!(
+                f,
+                "LIMIT - {}",
+                self.message
+            ),
         }
     }
 }
@@ -0,0 +1,54 @@
+/* -------------------------------------------------------------------------------------
+*
+*   Functions for the Language Lox
+*
+*   `fun (a, b) { return a + b; }` and `(a, b) => a + b` evaluate to a closure: the
+*   parameters and body of the lambda, plus the scope it was created in.
+*
+*   Some Considerations:
+*       - The closure shares its scopes with the code that created it, so later
+*         changes to captured variables are seen by the function (and the other way);
+*       - Calls run the body in a new scope nested in the closure, not in the caller;
+*       - Falling off the end of the body returns nil.
+*
+* ------------------------------------------------------------------------------------- */
+
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast_stmt::Stmt;
+use crate::environment::Environment;
+use crate::token::Token;
+
+pub struct LoxFunction {
+    pub params: Vec<Token>,
+    pub body: Rc<Vec<Stmt>>,
+    pub closure: Environment,
+}
+
+impl LoxFunction {
+    pub fn new(params: Vec<Token>, body: Rc<Vec<Stmt>>, closure: Environment) -> Self {
+        LoxFunction {
+            params,
+            body,
+            closure,
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        self.params.len()
+    }
+}
+
+// Each evaluation of a lambda is a different function
+impl PartialEq for LoxFunction {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl fmt::Debug for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<fn lambda>")
+    }
+}
@@ -7,28 +7,82 @@ use crate::ast_stmt::Stmt;
 use crate::parser::Parser;
 use crate::scanner::Scanner;
 use crate::ast_printer::AstPrinter;
-use crate::interpreter::Interpreter;
-
-#[path = "utils/utils.rs"] mod utils;
+use crate::interpreter::{Interpreter, InterpreterOptions};
+use crate::type_checker::TypeChecker;
+use crate::optimizer::Optimizer;
+use crate::instrument::Instrument;
+use crate::sandbox::{Limits, CancelToken};
+use crate::capabilities::Capabilities;
+use crate::utils;
 
 pub struct Lox {
     has_error: bool,
     has_runtime_error: bool,
+    optimize: bool,
+    // Prints the AST and the result of each statement
+    echo: bool,
+    options: InterpreterOptions,
+    type_checker: TypeChecker,
     interpreter: Interpreter
 }
 
 impl Lox {
 
-    pub fn new() -> Self {
+    // The natives reaching outside of the interpreter need the capabilities granted here
+    pub fn new(capabilities: Capabilities) -> Self {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_capabilities(capabilities);
+
         Lox {
             has_error: false,
             has_runtime_error: false,
-            interpreter: Interpreter::new()
+            optimize: false,
+            echo: true,
+            options: InterpreterOptions::default(),
+            type_checker: TypeChecker::new(),
+            interpreter,
         }
     }
 
+    pub fn enable_optimizer(&mut self) {
+        self.optimize = true;
+    }
+
+    pub fn disable_echo(&mut self) {
+        self.echo = false;
+    }
+
+    pub fn enable_prefer_const(&mut self) {
+        self.type_checker.enable_prefer_const();
+    }
+
+    pub fn enable_strict(&mut self) {
+        self.options.strict = true;
+        self.type_checker.enable_strict();
+        self.interpreter.set_options(self.options);
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.options.limits = limits;
+        self.interpreter.set_options(self.options);
+    }
+
+    pub fn set_cancel_token(&mut self, token: CancelToken) {
+        self.interpreter.set_cancel_token(token);
+    }
+
+    pub fn enable_deterministic(&mut self, seed: u64) {
+        self.interpreter.set_deterministic(seed);
+    }
+
+    pub fn add_hook(&mut self, hook: Box<dyn Instrument>) {
+        self.interpreter.add_hook(hook);
+    }
+
     pub fn run_file(&mut self, filename: &PathBuf) {
+        self.interpreter.set_script(filename);
         self.run(utils::read_file(filename));
+        self.interpreter.finish();
 
         if self.has_error {
             process::exit(exitcode::DATAERR);
@@ -45,6 +99,7 @@ impl Lox {
             self.has_error = false;
             self.has_runtime_error = false;
             match stdin.read_line(&mut line) {
+                Ok(0) => break,
                 Ok(_) => {
                     let trimmed = line.trim_end();
                     self.run(trimmed.chars().collect::<Vec<_>>());
@@ -53,25 +108,58 @@ impl Lox {
             }
         }
 
+        self.interpreter.finish();
+
     }
 
     fn run(&mut self, source: Vec<char>) {
         let mut scanner = Scanner::new(source);
         let mut parser = Parser::new(scanner.scan_tokens().clone());
 
-        let statements: Vec<Stmt> = parser.parse();
+        let mut statements: Vec<Stmt> = parser.parse();
+
+        for err in &scanner.errors {
+            self.has_error = true;
+            println!("{}", err);
+        }
+
+        for err in &parser.errors {
+            self.has_error = true;
+            println!("{}", err);
+        }
+
+        self.type_checker.check(&statements);
+
+        for err in self.type_checker.errors.drain(..) {
+            self.has_error = true;
+            println!("{}", err);
+        }
+
+        for warning in self.type_checker.warnings.drain(..) {
+            println!("{}", warning);
+        }
+
+        if self.optimize {
+            statements = Optimizer::new(self.options).optimize(&statements);
+        }
+
         let mut ast_printer = AstPrinter::new();
+        self.interpreter.start(&statements);
 
         for stmt in &statements {
 
-            println!("{}", ast_printer.printer(stmt));
+            if self.echo {
+                println!("{}", ast_printer.printer(stmt));
+            }
             
             if self.has_error {
                 return 
             }
 
             match self.interpreter.interpret(stmt) {
-                Ok(lit) => println!("{}", lit.to_string()),
+                Ok(lit) => if self.echo {
+                    println!("{}", lit.to_string())
+                },
                 Err(err) => {
                     self.has_error = true;
                     println!("{}", err);
@@ -1,4 +1,8 @@
-use clap::Parser;
+// Enum variants are in capitals across the crate, and the evaluation errors are
+// returned as they are by every visitor of the interpreter
+#![allow(clippy::upper_case_acronyms, clippy::result_large_err)]
+
+use clap::{Parser, Subcommand};
 
 mod lox;
 mod token_type;
@@ -7,12 +11,28 @@ mod scanner;
 mod parser;
 mod error;
 mod environment;
+mod natives;
+mod map;
+mod module;
+mod function;
+mod capabilities;
+mod random;
 #[path = "ast/ast_expr.rs"] mod ast_expr;
 #[path = "ast/visit_expr.rs"] mod visit_expr;
 #[path = "ast/ast_stmt.rs"] mod ast_stmt;
 #[path = "ast/visit_stmt.rs"] mod visit_stmt;
 #[path = "tools/ast_printer.rs"] mod ast_printer;
 #[path = "tools/interpreter.rs"] mod interpreter;
+#[path = "tools/instrument.rs"] mod instrument;
+#[path = "tools/dap.rs"] mod dap;
+#[path = "tools/profiler.rs"] mod profiler;
+#[path = "tools/coverage.rs"] mod coverage;
+#[path = "tools/type_checker.rs"] mod type_checker;
+#[path = "tools/optimizer.rs"] mod optimizer;
+#[path = "tools/linter.rs"] mod linter;
+#[path = "tools/sandbox.rs"] mod sandbox;
+#[path = "tools/serializer.rs"] mod serializer;
+#[path = "utils/utils.rs"] mod utils;
 
 
 #[derive(Parser, Debug)]
@@ -20,13 +40,218 @@ mod environment;
 struct Args {
     #[arg(short, long, default_value = None)]
     input_filename: Option<std::path::PathBuf>,
+
+    /// Only print what the script prints and the errors, not the AST and result of each statement
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Fold constants and simplify the AST before running
+    #[arg(short = 'O')]
+    optimize: bool,
+
+    /// Only booleans as conditions and no number to string conversion with `+`
+    #[arg(long)]
+    strict: bool,
+
+    /// Warn about variables that are never reassigned, and could be constants
+    #[arg(long)]
+    prefer_const: bool,
+
+    /// Count and time every statement, reporting hot spots at exit
+    #[arg(long)]
+    profile: bool,
+
+    /// Where to write the folded stacks (defaults to the input with `.folded`)
+    #[arg(long, default_value = None)]
+    profile_output: Option<std::path::PathBuf>,
+
+    /// Track executed lines and branches, writing `.info` (lcov) and `.cov` reports
+    #[arg(long)]
+    coverage: bool,
+
+    /// Stop after evaluating this many statements and expressions
+    #[arg(long, default_value = None)]
+    max_steps: Option<u64>,
+
+    /// Maximum depth of nested calls before a "Stack overflow" error, also bounded by the stack
+    #[arg(long, default_value_t = sandbox::DEFAULT_MAX_DEPTH)]
+    max_depth: usize,
+
+    /// Stop after creating this many lists, maps, lambdas and strings
+    #[arg(long, default_value = None)]
+    max_values: Option<u64>,
+
+    /// Stop after creating strings with this many bytes in total
+    #[arg(long, default_value = None)]
+    max_string_bytes: Option<u64>,
+
+    /// Cancel the run after this many milliseconds
+    #[arg(long, default_value = None)]
+    timeout: Option<u64>,
+
+    /// Reproducible runs: seeded `random()` and a virtual `clock()` advancing per step
+    #[arg(long)]
+    deterministic: bool,
+
+    /// Seed of `random()` in the deterministic mode
+    #[arg(long, default_value_t = 0, requires = "deterministic")]
+    seed: u64,
+
+    /// Capabilities granted to the script (fs, env, time, random or none), all by default
+    #[arg(long, value_delimiter = ',', value_name = "CAPABILITY")]
+    capabilities: Option<Vec<String>>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Serve the Debug Adapter Protocol over stdin/stdout
+    Dap,
+
+    /// Report likely mistakes in a script without running it
+    Lint {
+        file: std::path::PathBuf,
+
+        /// Output format, `text` or `json`
+        #[arg(long, default_value = "text")]
+        format: String,
+
+        /// Lints not to report
+        #[arg(long, value_name = "LINT")]
+        allow: Vec<String>,
+
+        /// Lints to report as warnings
+        #[arg(long, value_name = "LINT")]
+        warn: Vec<String>,
+
+        /// Lints to report as errors, failing the run
+        #[arg(long, value_name = "LINT")]
+        deny: Vec<String>,
+    },
+
+    /// Print the AST of a script in a machine-readable form
+    Ast {
+        file: std::path::PathBuf,
+
+        /// Output format, `json` or `sexpr`
+        #[arg(long, default_value = "json")]
+        format: String,
+
+        /// Input format, `lox` source or an AST in `json`
+        #[arg(long, default_value = "lox")]
+        input: String,
+    },
 }
 
+// Calls in Lox recurse in the interpreter, so it runs in a thread with a larger stack
+// than the main one. The calls may use half of it, leaving room for `DEFAULT_MAX_DEPTH`
+// of them even in debug builds
+const STACK_SIZE: usize = 256 * 1024 * 1024;
 
 fn main () {
+    let interpreter = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("failed to start the interpreter thread");
+
+    // The panic message was already printed by the thread
+    if interpreter.join().is_err() {
+        std::process::exit(101);
+    }
+}
+
+fn run () {
 
     let args = Args::parse();
-    let mut lox = lox::Lox::new();
+
+    match args.command {
+        Some(Command::Dap) => {
+            dap::DebugAdapter::stdio().run();
+            return;
+        },
+        Some(Command::Lint { file, format, allow, warn, deny }) => lint(file, format, allow, warn, deny),
+        Some(Command::Ast { file, format, input }) => ast(file, format, input),
+        None => (),
+    }
+
+    let capabilities = match &args.capabilities {
+        Some(names) => {
+            let mut granted = capabilities::Capabilities::none();
+            for name in names.iter().filter(|name| name.as_str() != "none") {
+                match capabilities::Capability::from_name(name) {
+                    Some(capability) => granted.grant(capability),
+                    None => {
+                        eprintln!("[CAPABILITIES] Unknown capability '{}'", name);
+                        std::process::exit(exitcode::USAGE);
+                    },
+                }
+            }
+            granted
+        },
+        None => capabilities::Capabilities::all(),
+    };
+
+    let mut lox = lox::Lox::new(capabilities);
+
+    if args.quiet {
+        lox.disable_echo();
+    }
+
+    if args.optimize {
+        lox.enable_optimizer();
+    }
+
+    if args.strict {
+        lox.enable_strict();
+    }
+
+    if args.prefer_const {
+        lox.enable_prefer_const();
+    }
+
+    lox.set_limits(sandbox::Limits {
+        max_steps: args.max_steps,
+        max_depth: args.max_depth,
+        stack_bytes: STACK_SIZE / 2,
+        max_values: args.max_values,
+        max_string_bytes: args.max_string_bytes,
+    });
+
+    if args.deterministic {
+        lox.enable_deterministic(args.seed);
+    }
+
+    if let Some(timeout) = args.timeout {
+        let token = sandbox::CancelToken::new();
+        lox.set_cancel_token(token.clone());
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(timeout));
+            token.cancel();
+        });
+    }
+
+    if args.profile {
+        let folded = match (&args.profile_output, &args.input_filename) {
+            (Some(path), _) => path.clone(),
+            (None, Some(input)) => input.with_extension("folded"),
+            (None, None) => std::path::PathBuf::from("rslox.folded"),
+        };
+        lox.add_hook(Box::new(profiler::Profiler::new(folded)));
+    }
+
+    if args.coverage {
+        match &args.input_filename {
+            Some(input) => lox.add_hook(Box::new(coverage::Coverage::new(
+                input.clone(),
+                utils::read_file(input).iter().collect(),
+                input.with_extension("info"),
+                input.with_extension("cov"),
+            ))),
+            None => eprintln!("[COVERAGE] Coverage requires an input file"),
+        }
+    }
 
     match args.input_filename {
         Some(filename) => lox.run_file(&filename),
@@ -34,3 +259,88 @@ fn main () {
     }  
     
 }
+
+
+fn lint(file: std::path::PathBuf, format: String, allow: Vec<String>, warn: Vec<String>, deny: Vec<String>) {
+    let mut linter = linter::Linter::new();
+
+    let levels = [
+        (allow, linter::Severity::ALLOW),
+        (warn, linter::Severity::WARNING),
+        (deny, linter::Severity::ERROR),
+    ];
+
+    for (names, severity) in levels {
+        for name in names {
+            if let Err(err) = linter.set_severity(&name, severity) {
+                eprintln!("[LINT] {}", err);
+                std::process::exit(exitcode::USAGE);
+            }
+        }
+    }
+
+    let json = match format.as_str() {
+        "text" => false,
+        "json" => true,
+        other => {
+            eprintln!("[LINT] Unknown format '{}', expected `text` or `json`", other);
+            std::process::exit(exitcode::USAGE);
+        },
+    };
+
+    if linter.run(&file, json) {
+        std::process::exit(exitcode::DATAERR);
+    }
+    std::process::exit(exitcode::OK);
+}
+
+
+fn ast(file: std::path::PathBuf, format: String, input: String) {
+    let statements = match input.as_str() {
+        "lox" => {
+            let mut scanner = scanner::Scanner::new(utils::read_file(&file));
+            let mut parser = parser::Parser::new(scanner.scan_tokens().clone());
+            let statements = parser.parse();
+
+            if !scanner.errors.is_empty() || !parser.errors.is_empty() {
+                for err in &scanner.errors {
+                    eprintln!("{}", err);
+                }
+                for err in &parser.errors {
+                    eprintln!("{}", err);
+                }
+                std::process::exit(exitcode::DATAERR);
+            }
+            statements
+        },
+        "json" => {
+            let source: String = utils::read_file(&file).iter().collect();
+            let parsed = serde_json::from_str(&source)
+                .map_err(|err| err.to_string())
+                .and_then(|value| serializer::AstDeserializer::new().deserialize(&value));
+
+            match parsed {
+                Ok(statements) => statements,
+                Err(err) => {
+                    eprintln!("[AST] {}", err);
+                    std::process::exit(exitcode::DATAERR);
+                },
+            }
+        },
+        other => {
+            eprintln!("[AST] Unknown input '{}', expected `lox` or `json`", other);
+            std::process::exit(exitcode::USAGE);
+        },
+    };
+
+    let tree = serializer::AstSerializer::new().serialize(&statements);
+    match format.as_str() {
+        "json" => println!("{}", tree),
+        "sexpr" => println!("{}", serializer::to_sexpr(&tree)),
+        other => {
+            eprintln!("[AST] Unknown format '{}', expected `json` or `sexpr`", other);
+            std::process::exit(exitcode::USAGE);
+        },
+    }
+    std::process::exit(exitcode::OK);
+}
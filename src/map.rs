@@ -0,0 +1,106 @@
+/* -------------------------------------------------------------------------------------
+*
+*   Map value for the Language Lox
+*
+*   Associative container keyed by strings or numbers. The entries are kept in
+*   insertion order (updating a key keeps its position), with an index from the key
+*   to the position of the entry for the lookups.
+*
+* ------------------------------------------------------------------------------------- */
+
+use std::collections::HashMap;
+
+use crate::ast_expr::Literal;
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+enum MapKey {
+    STRING(String),
+    INTEGER(i64),
+    NUMBER(u64),
+}
+
+impl MapKey {
+    fn from_literal(key: &Literal) -> Option<MapKey> {
+        match key {
+            Literal::STRING(val) => Some(MapKey::STRING(val.clone())),
+            Literal::INTEGER(val) => Some(MapKey::INTEGER(*val)),
+            // Integral floats are the same key as the integer, and -0.0 the same as 0.0
+            Literal::NUMBER(val) if val.fract() == 0.0 && val.abs() < 9.2e18 => Some(MapKey::INTEGER(*val as i64)),
+            Literal::NUMBER(val) if !val.is_nan() => Some(MapKey::NUMBER(val.to_bits())),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct LoxMap {
+    entries: Vec<(Literal, Literal)>,
+    index: HashMap<MapKey, usize>,
+}
+
+impl LoxMap {
+    pub fn new() -> Self {
+        LoxMap::default()
+    }
+
+    // Only strings and numbers (except NaN) can be keys
+    pub fn is_valid_key(key: &Literal) -> bool {
+        MapKey::from_literal(key).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn get(&self, key: &Literal) -> Option<&Literal> {
+        let pos = self.index.get(&MapKey::from_literal(key)?)?;
+        Some(&self.entries[*pos].1)
+    }
+
+    pub fn has(&self, key: &Literal) -> bool {
+        self.get(key).is_some()
+    }
+
+    // Inserts or updates the entry, false if the key is not valid
+    pub fn set(&mut self, key: Literal, value: Literal) -> bool {
+        let map_key = match MapKey::from_literal(&key) {
+            Some(k) => k,
+            None => return false,
+        };
+
+        match self.index.get(&map_key) {
+            Some(pos) => self.entries[*pos].1 = value,
+            None => {
+                self.index.insert(map_key, self.entries.len());
+                self.entries.push((key, value));
+            },
+        }
+
+        true
+    }
+
+    pub fn remove(&mut self, key: &Literal) -> Option<Literal> {
+        let pos = self.index.remove(&MapKey::from_literal(key)?)?;
+        let (_, value) = self.entries.remove(pos);
+
+        for idx in self.index.values_mut() {
+            if *idx > pos {
+                *idx -= 1;
+            }
+        }
+
+        Some(value)
+    }
+
+    pub fn keys(&self) -> Vec<Literal> {
+        self.entries.iter().map(|(k, _)| k.clone()).collect()
+    }
+
+    pub fn values(&self) -> Vec<Literal> {
+        self.entries.iter().map(|(_, v)| v.clone()).collect()
+    }
+
+    pub fn entries(&self) -> &Vec<(Literal, Literal)> {
+        &self.entries
+    }
+}
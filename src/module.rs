@@ -0,0 +1,116 @@
+/* -------------------------------------------------------------------------------------
+*
+*   Modules for the Language Lox
+*
+*   `import "path.lox" as name;` runs another script and binds its top-level
+*   declarations as a namespace, read with `name.member`.
+*
+*   Some Considerations:
+*       - Paths are relative to the file doing the import (the working directory for
+*         the prompt);
+*       - Each module runs once, later imports of the same file share the namespace;
+*       - A module importing a file that is still loading is a cycle, reported with
+*         the chain of imports.
+*
+* ------------------------------------------------------------------------------------- */
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::ast_expr::Literal;
+
+pub struct Module {
+    pub name: String,
+    pub path: PathBuf,
+    members: Vec<(String, Literal)>,
+}
+
+impl Module {
+    pub fn new(name: String, path: PathBuf, members: Vec<(String, Literal)>) -> Self {
+        Module {
+            name,
+            path,
+            members,
+        }
+    }
+
+    pub fn get(&self, member: &str) -> Option<&Literal> {
+        self.members.iter().find(|(name, _)| name == member).map(|(_, value)| value)
+    }
+}
+
+// Modules are the same if they were loaded from the same file
+impl PartialEq for Module {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl fmt::Debug for Module {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<module {}>", self.name)
+    }
+}
+
+pub struct ModuleLoader {
+    cache: HashMap<PathBuf, Rc<Module>>,
+    loading: Vec<PathBuf>,
+}
+
+impl ModuleLoader {
+    pub fn new() -> Self {
+        ModuleLoader {
+            cache: HashMap::new(),
+            loading: Vec::new(),
+        }
+    }
+
+    // Marks the main script as loading, so importing it back is a cycle
+    pub fn set_script(&mut self, path: &Path) {
+        self.loading = vec![path.canonicalize().unwrap_or(path.to_path_buf())];
+    }
+
+    // Path of an import, relative to the directory of the file being run
+    pub fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        let base = match self.loading.last().and_then(|file| file.parent()) {
+            Some(dir) => dir.to_path_buf(),
+            None => env::current_dir().unwrap_or_default(),
+        };
+
+        let full = base.join(path);
+        match full.canonicalize() {
+            Ok(resolved) => Ok(resolved),
+            Err(err) => Err(format!("Cannot find module '{}' ({})", full.display(), err)),
+        }
+    }
+
+    pub fn cached(&self, path: &Path) -> Option<Rc<Module>> {
+        self.cache.get(path).cloned()
+    }
+
+    pub fn begin(&mut self, path: &Path) -> Result<(), String> {
+        if let Some(start) = self.loading.iter().position(|file| file == path) {
+            let chain: Vec<String> = self.loading[start..]
+                .iter()
+                .chain([path.to_path_buf()].iter())
+                .map(|file| file.display().to_string())
+                .collect();
+
+            return Err(format!("Import cycle: {}", chain.join(" -> ")));
+        }
+
+        self.loading.push(path.to_path_buf());
+        Ok(())
+    }
+
+    pub fn end(&mut self, module: Option<Rc<Module>>) {
+        if let Some(path) = self.loading.pop() {
+            if let Some(module) = module {
+                self.cache.insert(path, module);
+            }
+        }
+    }
+}
@@ -0,0 +1,307 @@
+/* -------------------------------------------------------------------------------------
+*
+*   Native functions for the Language Lox
+*
+*   Functions implemented in Rust and defined as globals when the interpreter is
+*   created. Each native has a fixed arity, checked by the interpreter on the call.
+*
+*   Lists:
+*       - len(list|string): number of elements (or characters);
+*       - push(list, value): appends the value, returning the new length;
+*       - pop(list): removes and returns the last element;
+*       - map(list, fn): new list with fn applied to each element;
+*       - filter(list, fn): new list with the elements for which fn is truthy.
+*
+*   Maps:
+*       - len(map): number of entries;
+*       - keys(map), values(map): lists in insertion order;
+*       - has(map, key): whether the key is in the map;
+*       - remove(map, key): removes the entry, returning its value (or nil).
+*
+*   System, each one needs a capability granted to the interpreter:
+*       - clock(): seconds since the Unix epoch, or virtual ones in deterministic mode (time);
+*       - random(): number in [0, 1) (random);
+*       - env(name): value of an environment variable, or nil (env);
+*       - readFile(path), writeFile(path, text): whole file as a string (fs).
+*
+* ------------------------------------------------------------------------------------- */
+
+use std::cell::RefCell;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::ast_expr::Literal;
+use crate::environment::Environment;
+use crate::error::EvaluationError;
+use crate::interpreter::Interpreter;
+use crate::capabilities::Capability;
+
+pub type NativeFn = fn(&mut Interpreter, Vec<Literal>) -> Result<Literal, EvaluationError>;
+
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: usize,
+    pub func: NativeFn,
+    pub capability: Option<Capability>,
+}
+
+impl NativeFunction {
+    pub fn new(name: &str, arity: usize, func: NativeFn) -> Self {
+        NativeFunction {
+            name: name.to_string(),
+            arity,
+            func,
+            capability: None,
+        }
+    }
+
+    // Calls are denied unless the interpreter was granted the capability
+    pub fn requires(mut self, capability: Capability) -> Self {
+        self.capability = Some(capability);
+        self
+    }
+}
+
+// Natives are the same function if they were defined with the same name
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
+
+pub fn define_natives(env: &mut Environment) {
+    let natives = [
+        NativeFunction::new("len", 1, len),
+        NativeFunction::new("push", 2, push),
+        NativeFunction::new("pop", 1, pop),
+        NativeFunction::new("map", 2, map),
+        NativeFunction::new("filter", 2, filter),
+        NativeFunction::new("keys", 1, keys),
+        NativeFunction::new("values", 1, values),
+        NativeFunction::new("has", 2, has),
+        NativeFunction::new("remove", 2, remove),
+        NativeFunction::new("clock", 0, clock).requires(Capability::TIME),
+        NativeFunction::new("random", 0, random).requires(Capability::RANDOM),
+        NativeFunction::new("env", 1, env_var).requires(Capability::ENV),
+        NativeFunction::new("readFile", 1, read_file).requires(Capability::FS),
+        NativeFunction::new("writeFile", 2, write_file).requires(Capability::FS),
+    ];
+
+    for native in natives {
+        env.define(native.name.clone(), Literal::NATIVE(native));
+    }
+}
+
+fn len(_: &mut Interpreter, args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    match &args[0] {
+        Literal::LIST(list) => Ok(Literal::INTEGER(list.borrow().len() as i64)),
+        Literal::STRING(val) => Ok(Literal::INTEGER(val.chars().count() as i64)),
+        Literal::MAP(map) => Ok(Literal::INTEGER(map.borrow().len() as i64)),
+        other => Err(
+            EvaluationError::new_unary("len() expects a list, map or string".to_string(), other.clone())
+        ),
+    }
+}
+
+fn push(interpreter: &mut Interpreter, args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    match &args[0] {
+        Literal::LIST(list) => {
+            interpreter.grow(1)?;
+            list.borrow_mut().push(args[1].clone());
+            Ok(Literal::INTEGER(list.borrow().len() as i64))
+        },
+        other => Err(
+            EvaluationError::new_unary("push() expects a list".to_string(), other.clone())
+        ),
+    }
+}
+
+fn pop(_: &mut Interpreter, args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    match &args[0] {
+        Literal::LIST(list) => match list.borrow_mut().pop() {
+            Some(value) => Ok(value),
+            None => Err(
+                EvaluationError::new_unary("pop() on an empty list".to_string(), args[0].clone())
+            ),
+        },
+        other => Err(
+            EvaluationError::new_unary("pop() expects a list".to_string(), other.clone())
+        ),
+    }
+}
+
+// Lists made by the natives count against the limits of the sandbox, with their elements
+fn new_list(interpreter: &mut Interpreter, elements: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    interpreter.grow(elements.len())?;
+
+    let list = Literal::LIST(Rc::new(RefCell::new(elements)));
+    interpreter.count(&list).map(|_| list)
+}
+
+fn keys(interpreter: &mut Interpreter, args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    match &args[0] {
+        Literal::MAP(map) => new_list(interpreter, map.borrow().keys()),
+        other => Err(
+            EvaluationError::new_unary("keys() expects a map".to_string(), other.clone())
+        ),
+    }
+}
+
+fn values(interpreter: &mut Interpreter, args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    match &args[0] {
+        Literal::MAP(map) => new_list(interpreter, map.borrow().values()),
+        other => Err(
+            EvaluationError::new_unary("values() expects a map".to_string(), other.clone())
+        ),
+    }
+}
+
+fn has(_: &mut Interpreter, args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    match &args[0] {
+        Literal::MAP(map) => Ok(Literal::BOOL(map.borrow().has(&args[1]))),
+        other => Err(
+            EvaluationError::new_unary("has() expects a map".to_string(), other.clone())
+        ),
+    }
+}
+
+fn remove(_: &mut Interpreter, args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    match &args[0] {
+        Literal::MAP(map) => Ok(map.borrow_mut().remove(&args[1]).unwrap_or(Literal::NIL)),
+        other => Err(
+            EvaluationError::new_unary("remove() expects a map".to_string(), other.clone())
+        ),
+    }
+}
+
+// Calls a function of one argument passed to a native
+fn call_back(interpreter: &mut Interpreter, native: &str, callback: &Literal, value: Literal) -> Result<Literal, EvaluationError> {
+    let arity = match callback {
+        Literal::NATIVE(native) => native.arity,
+        Literal::FUNCTION(function) => function.arity(),
+        _ => 0,
+    };
+
+    if arity != 1 {
+        return Err(
+            EvaluationError::new_unary(
+                format!("{}() expects a function of one argument", native),
+                callback.clone(),
+            )
+        );
+    }
+
+    interpreter.call(callback.clone(), vec![value])
+}
+
+fn map(interpreter: &mut Interpreter, args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    // The elements are copied first, the callback may change the list
+    let elements = match &args[0] {
+        Literal::LIST(list) => list.borrow().clone(),
+        other => return Err(
+            EvaluationError::new_unary("map() expects a list".to_string(), other.clone())
+        ),
+    };
+
+    let mut mapped = Vec::new();
+    for element in elements {
+        match call_back(interpreter, "map", &args[1], element) {
+            Ok(value) => mapped.push(value),
+            Err(e) => return Err(e),
+        }
+    }
+
+    new_list(interpreter, mapped)
+}
+
+fn filter(interpreter: &mut Interpreter, args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    let elements = match &args[0] {
+        Literal::LIST(list) => list.borrow().clone(),
+        other => return Err(
+            EvaluationError::new_unary("filter() expects a list".to_string(), other.clone())
+        ),
+    };
+
+    let mut kept = Vec::new();
+    for element in elements {
+        match call_back(interpreter, "filter", &args[1], element.clone()) {
+            Ok(value) => match interpreter.options().condition(&value) {
+                Ok(true) => kept.push(element),
+                Ok(false) => (),
+                Err(e) => return Err(e),
+            },
+            Err(e) => return Err(e),
+        }
+    }
+
+    new_list(interpreter, kept)
+}
+
+fn clock(interpreter: &mut Interpreter, _args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    if let Some(time) = interpreter.virtual_clock() {
+        return Ok(Literal::NUMBER(time));
+    }
+
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => Ok(Literal::NUMBER(elapsed.as_secs_f64())),
+        Err(_) => Err(EvaluationError::new_call("clock() is before the Unix epoch".to_string())),
+    }
+}
+
+fn random(interpreter: &mut Interpreter, _args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    Ok(Literal::NUMBER(interpreter.random()))
+}
+
+fn env_var(interpreter: &mut Interpreter, args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    let value = match &args[0] {
+        Literal::STRING(name) => env::var(name).map_or(Literal::NIL, Literal::STRING),
+        other => return Err(
+            EvaluationError::new_unary("env() expects a string".to_string(), other.clone())
+        ),
+    };
+
+    match value {
+        Literal::NIL => Ok(value),
+        _ => interpreter.count(&value).map(|_| value),
+    }
+}
+
+fn read_file(interpreter: &mut Interpreter, args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    let contents = match &args[0] {
+        Literal::STRING(path) => match fs::read_to_string(path) {
+            Ok(contents) => Literal::STRING(contents),
+            Err(err) => return Err(
+                EvaluationError::new_unary(format!("readFile() failed: {}", err), args[0].clone())
+            ),
+        },
+        other => return Err(
+            EvaluationError::new_unary("readFile() expects a string".to_string(), other.clone())
+        ),
+    };
+
+    interpreter.count(&contents).map(|_| contents)
+}
+
+fn write_file(_: &mut Interpreter, args: Vec<Literal>) -> Result<Literal, EvaluationError> {
+    match (&args[0], &args[1]) {
+        (Literal::STRING(path), Literal::STRING(text)) => match fs::write(path, text) {
+            Ok(_) => Ok(Literal::NIL),
+            Err(err) => Err(
+                EvaluationError::new_unary(format!("writeFile() failed: {}", err), args[0].clone())
+            ),
+        },
+        (path, text) => Err(
+            EvaluationError::new_binary("writeFile() expects a path and a string".to_string(), path.clone(), text.clone())
+        ),
+    }
+}
@@ -7,15 +7,24 @@ use crate::error::ParseError;
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    allow_comma: bool,
+    loop_depth: usize,
+    function_depth: usize,
+    pub errors: Vec<ParseError>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
         let current: usize = 0;
+        let errors = Vec::new();
     
         Parser {
             tokens,
             current,
+            allow_comma: true,
+            loop_depth: 0,
+            function_depth: 0,
+            errors,
         }
     }
 
@@ -25,7 +34,7 @@ impl Parser {
         while !self.is_at_end() {
             match self.declaration() {
                 Ok(stmt) => statements.push(stmt),
-                Err(err) => println!("{}", err)
+                Err(err) => self.errors.push(err),
             }
         }
 
@@ -40,6 +49,11 @@ impl Parser {
         self.tokens[self.current - 1].clone()
     }
 
+    fn peek_at(&self, offset: usize) -> &Token {
+        let idx = (self.current + offset).min(self.tokens.len() - 1);
+        &self.tokens[idx]
+    }
+
     fn is_at_end(&self) -> bool {
         self.peek().kind == TokenType::EOF
     }
@@ -72,33 +86,87 @@ impl Parser {
     }
 
     fn declaration(&mut self) -> Result<Stmt, ParseError> {
+        let result = if self.match_types(vec![
+            TokenType::VAR,
+            TokenType::CONST,
+        ]) {
+            self.var_declaration()
+        } else if self.match_types(vec![
+            TokenType::IMPORT
+        ]) {
+            self.import_declaration()
+        } else {
+            self.statement()
+        };
+
+        if result.is_err() {
+            self.synchronize();
+        }
+
+        result
+    }
+
+    fn import_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+
+        let path = match self.consume(TokenType::STRING, "Expect module path after 'import'") {
+            Ok(t) => t,
+            Err(err) => return Err(err),
+        };
+
+        let mut alias: Option<Token> = None;
         if self.match_types(vec![
-            TokenType::VAR
+            TokenType::AS
         ]) {
-            match self.var_declaration() {
-                Ok(stmt) => return Ok(stmt),
-                Err(err) =>  {
-                    self.synchronize();
-                    return Err(err);
-                }
+            alias = match self.consume(TokenType::IDENTIFIER, "Expect module name after 'as'") {
+                Ok(t) => Some(t),
+                Err(err) => return Err(err),
             };
         }
 
-        self.statement()
+        match self.consume(TokenType::SEMICOLON, "Expect ';' after import") {
+            Ok(_) => Ok(Stmt::IMPORT(Import::new(keyword, path, alias))),
+            Err(err) => Err(err),
+        }
+    }
+
+    // `: type` after a variable, a parameter or the parameters of a lambda
+    fn annotation(&mut self) -> Result<Option<Token>, ParseError> {
+        if !self.match_types(vec![
+            TokenType::COLON
+        ]) {
+            return Ok(None);
+        }
+
+        match self.consume(TokenType::IDENTIFIER, "Expect type name after ':'") {
+            Ok(t) => Ok(Some(t)),
+            Err(err) => Err(err),
+        }
     }
 
     fn var_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let constant = self.previous().kind == TokenType::CONST;
         let token: Token = match self.consume(TokenType::IDENTIFIER, "Expect variable name") {
             Ok(t) => t,
             Err(err) => return Err(err),
         };
 
+        let annotation = match self.annotation() {
+            Ok(t) => t,
+            Err(err) => return Err(err),
+        };
+
         let mut initiliazer: Option<Expr> = None;
 
         if self.match_types(vec![
             TokenType::EQUAL
         ]) {
-            initiliazer = Some(self.expression());
+            initiliazer = match self.expression() {
+                Ok(expr) => Some(expr),
+                Err(err) => return Err(err),
+            };
+        } else if constant {
+            return Err(ParseError::new("Expect '=' after constant name.".to_string(), self.peek().clone()));
         }
 
         match self.consume(TokenType::SEMICOLON, "Expect ';' after value.") {
@@ -109,12 +177,12 @@ impl Parser {
         match initiliazer {
             Some(expr) => Ok(
                 Stmt::VAR(
-                    Var::new(token, Some(Box::new(expr)))
+                    Var::new(token, annotation, Some(Box::new(expr)), constant)
                 )
             ),
             None => Ok(
                 Stmt::VAR(
-                    Var::new(token, None)
+                    Var::new(token, annotation, None, constant)
                 )
             )
         }
@@ -128,11 +196,413 @@ impl Parser {
             return self.print_statement();
         }
 
+        if self.match_types(vec![
+            TokenType::LEFTBRACE
+        ]) {
+            let line = self.previous().line;
+            return match self.block() {
+                Ok(statements) => Ok(Stmt::BLOCK(Block::new(statements, line))),
+                Err(err) => Err(err),
+            };
+        }
+
+        if self.match_types(vec![
+            TokenType::IF
+        ]) {
+            return self.if_statement();
+        }
+
+        if self.match_types(vec![
+            TokenType::WHILE
+        ]) {
+            return self.while_statement();
+        }
+
+        if self.match_types(vec![
+            TokenType::FOR
+        ]) {
+            return self.for_statement();
+        }
+
+        if self.match_types(vec![
+            TokenType::BREAK,
+            TokenType::CONTINUE,
+        ]) {
+            return self.loop_control_statement();
+        }
+
+        if self.match_types(vec![
+            TokenType::THROW
+        ]) {
+            return self.throw_statement();
+        }
+
+        if self.match_types(vec![
+            TokenType::TRY
+        ]) {
+            return self.try_statement();
+        }
+
+        if self.match_types(vec![
+            TokenType::RETURN
+        ]) {
+            return self.return_statement();
+        }
+
         self.expression_statement()
     }
 
+    fn block(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let mut statements = Vec::new();
+
+        while !self.check(TokenType::RIGHTBRACE) && !self.is_at_end() {
+            match self.declaration() {
+                Ok(stmt) => statements.push(stmt),
+                Err(err) => self.errors.push(err),
+            }
+        }
+
+        match self.consume(TokenType::RIGHTBRACE, "Expect '}' after block.") {
+            Ok(_) => Ok(statements),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt, ParseError> {
+        let line = self.previous().line;
+
+        match self.consume(TokenType::LEFTPAREN, "Expect '(' after 'if'.") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        let cond: Expr = match self.expression() {
+            Ok(cond) => cond,
+            Err(err) => return Err(err),
+        };
+
+        match self.consume(TokenType::RIGHTPAREN, "Expect ')' after if condition.") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        let then_branch = match self.statement() {
+            Ok(stmt) => stmt,
+            Err(err) => return Err(err),
+        };
+
+        let mut else_branch: Option<Box<Stmt>> = None;
+
+        if self.match_types(vec![
+            TokenType::ELSE
+        ]) {
+            else_branch = match self.statement() {
+                Ok(stmt) => Some(Box::new(stmt)),
+                Err(err) => return Err(err),
+            };
+        }
+
+        Ok(
+            Stmt::IF(
+                If::new(
+                    Box::new(cond),
+                    Box::new(then_branch),
+                    else_branch,
+                    line,
+                )
+            )
+        )
+    }
+
+    fn loop_body(&mut self) -> Result<Stmt, ParseError> {
+        self.loop_depth += 1;
+        let body = self.statement();
+        self.loop_depth -= 1;
+        body
+    }
+
+    fn while_statement(&mut self) -> Result<Stmt, ParseError> {
+        let line = self.previous().line;
+
+        match self.consume(TokenType::LEFTPAREN, "Expect '(' after 'while'.") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        let cond: Expr = match self.expression() {
+            Ok(cond) => cond,
+            Err(err) => return Err(err),
+        };
+
+        match self.consume(TokenType::RIGHTPAREN, "Expect ')' after condition.") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        let body = match self.loop_body() {
+            Ok(stmt) => stmt,
+            Err(err) => return Err(err),
+        };
+
+        Ok(
+            Stmt::WHILE(
+                While::new(
+                    Box::new(cond),
+                    Box::new(body),
+                    None,
+                    line,
+                )
+            )
+        )
+    }
+
+    // The C-style for is desugared into a while loop inside a block with the initializer
+    fn for_statement(&mut self) -> Result<Stmt, ParseError> {
+        let line = self.previous().line;
+
+        match self.consume(TokenType::LEFTPAREN, "Expect '(' after 'for'.") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        if self.check(TokenType::VAR)
+            && self.peek_at(1).kind == TokenType::IDENTIFIER
+            && self.peek_at(2).kind == TokenType::IN {
+            return self.for_in_statement();
+        }
+
+        let initializer: Option<Stmt> = if self.match_types(vec![
+            TokenType::SEMICOLON
+        ]) {
+            None
+        } else if self.match_types(vec![
+            TokenType::VAR
+        ]) {
+            match self.var_declaration() {
+                Ok(stmt) => Some(stmt),
+                Err(err) => return Err(err),
+            }
+        } else {
+            match self.expression_statement() {
+                Ok(stmt) => Some(stmt),
+                Err(err) => return Err(err),
+            }
+        };
+
+        let mut cond: Expr = Expr::LITERAL(Literal::BOOL(true));
+        if !self.check(TokenType::SEMICOLON) {
+            cond = match self.expression() {
+                Ok(expr) => expr,
+                Err(err) => return Err(err),
+            };
+        }
+
+        match self.consume(TokenType::SEMICOLON, "Expect ';' after loop condition.") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        let mut increment: Option<Box<Expr>> = None;
+        if !self.check(TokenType::RIGHTPAREN) {
+            increment = match self.expression() {
+                Ok(expr) => Some(Box::new(expr)),
+                Err(err) => return Err(err),
+            };
+        }
+
+        match self.consume(TokenType::RIGHTPAREN, "Expect ')' after for clauses.") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        let body = match self.loop_body() {
+            Ok(stmt) => stmt,
+            Err(err) => return Err(err),
+        };
+
+        let loop_stmt = Stmt::WHILE(
+            While::new(
+                Box::new(cond),
+                Box::new(body),
+                increment,
+                line,
+            )
+        );
+
+        match initializer {
+            Some(init) => Ok(Stmt::BLOCK(Block::new(vec![init, loop_stmt], line))),
+            None => Ok(loop_stmt),
+        }
+    }
+
+    fn for_in_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.advance();
+        let name = self.advance();
+        self.advance();
+
+        let iterable: Expr = match self.expression() {
+            Ok(iterable) => iterable,
+            Err(err) => return Err(err),
+        };
+
+        match self.consume(TokenType::RIGHTPAREN, "Expect ')' after for iterable.") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        let body = match self.loop_body() {
+            Ok(stmt) => stmt,
+            Err(err) => return Err(err),
+        };
+
+        Ok(
+            Stmt::FORIN(
+                ForIn::new(
+                    name,
+                    Box::new(iterable),
+                    Box::new(body),
+                )
+            )
+        )
+    }
+
+    fn throw_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+        let value: Expr = match self.expression() {
+            Ok(value) => value,
+            Err(err) => return Err(err),
+        };
+
+        match self.consume(TokenType::SEMICOLON, "Expect ';' after thrown value.") {
+            Ok(_) => Ok(Stmt::THROW(Throw::new(keyword, Box::new(value)))),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn try_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+
+        match self.consume(TokenType::LEFTBRACE, "Expect '{' after 'try'.") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        let body = match self.block() {
+            Ok(statements) => statements,
+            Err(err) => return Err(err),
+        };
+
+        let mut catch_name: Option<Token> = None;
+        let mut catch_body: Option<Vec<Stmt>> = None;
+
+        if self.match_types(vec![
+            TokenType::CATCH
+        ]) {
+            match self.consume(TokenType::LEFTPAREN, "Expect '(' after 'catch'.") {
+                Ok(_) => (),
+                Err(err) => return Err(err),
+            };
+
+            catch_name = match self.consume(TokenType::IDENTIFIER, "Expect exception name.") {
+                Ok(t) => Some(t),
+                Err(err) => return Err(err),
+            };
+
+            match self.consume(TokenType::RIGHTPAREN, "Expect ')' after exception name.") {
+                Ok(_) => (),
+                Err(err) => return Err(err),
+            };
+
+            match self.consume(TokenType::LEFTBRACE, "Expect '{' before catch body.") {
+                Ok(_) => (),
+                Err(err) => return Err(err),
+            };
+
+            catch_body = match self.block() {
+                Ok(statements) => Some(statements),
+                Err(err) => return Err(err),
+            };
+        }
+
+        let mut finally_body: Option<Vec<Stmt>> = None;
+
+        if self.match_types(vec![
+            TokenType::FINALLY
+        ]) {
+            match self.consume(TokenType::LEFTBRACE, "Expect '{' after 'finally'.") {
+                Ok(_) => (),
+                Err(err) => return Err(err),
+            };
+
+            finally_body = match self.block() {
+                Ok(statements) => Some(statements),
+                Err(err) => return Err(err),
+            };
+        }
+
+        if catch_body.is_none() && finally_body.is_none() {
+            return Err(
+                ParseError::new("Expect 'catch' or 'finally' after try block.".to_string(), self.peek().clone())
+            );
+        }
+
+        Ok(Stmt::TRY(Try::new(keyword, body, catch_name, catch_body, finally_body)))
+    }
+
+    // Outside of a loop the statement is reported, but the parsing goes on
+    fn loop_control_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+
+        match self.consume(TokenType::SEMICOLON, "Expect ';' after loop control.") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        if self.loop_depth == 0 {
+            self.errors.push(
+                ParseError::new("Can't be used outside of a loop.".to_string(), keyword.clone())
+            );
+        }
+
+        match keyword.kind {
+            TokenType::BREAK => Ok(Stmt::BREAK(Break::new(keyword))),
+            _ => Ok(Stmt::CONTINUE(Continue::new(keyword))),
+        }
+    }
+
+    // Outside of a function the statement is reported, but the parsing goes on
+    fn return_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.previous();
+
+        let value = if self.check(TokenType::SEMICOLON) {
+            None
+        } else {
+            match self.expression() {
+                Ok(expr) => Some(Box::new(expr)),
+                Err(err) => return Err(err),
+            }
+        };
+
+        match self.consume(TokenType::SEMICOLON, "Expect ';' after return value.") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        if self.function_depth == 0 {
+            self.errors.push(
+                ParseError::new("Can't return from top-level code.".to_string(), keyword.clone())
+            );
+        }
+
+        Ok(Stmt::RETURN(Return::new(keyword, value)))
+    }
+
     fn print_statement(&mut self) -> Result<Stmt, ParseError>  {
-        let expr: Expr = self.expression();
+        let line = self.previous().line;
+        let expr: Expr = match self.expression() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
 
         match self.consume(TokenType::SEMICOLON, "Expect ';' after value.") {
             Ok(_) => (),
@@ -142,14 +612,19 @@ impl Parser {
         Ok(
             Stmt::PRINT(
                 Print::new(
-                    Box::new(expr)
+                    Box::new(expr),
+                    line,
                 )
             )
         )
     }
 
     fn expression_statement(&mut self) -> Result<Stmt, ParseError> {
-        let expr: Expr = self.expression();
+        let line = self.peek().line;
+        let expr: Expr = match self.expression() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
 
         match self.consume(TokenType::SEMICOLON, "Expect ';' after value.") {
             Ok(_) => (),
@@ -159,92 +634,345 @@ impl Parser {
         Ok(
             Stmt::EXPRESSION(
                 Expression::new(
-                    Box::new(expr)
+                    Box::new(expr),
+                    line,
                 )
             )
         )
     }
 
-    fn expression(&mut self) -> Expr {
+    fn expression(&mut self) -> Result<Expr, ParseError> {
         self.assignment()
     }
 
-    fn assignment(&mut self) -> Expr {
-        let expr: Expr = self.ternary();
+    fn assignment(&mut self) -> Result<Expr, ParseError> {
+        let expr: Expr = match self.ternary() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
 
         if self.match_types(vec![
             TokenType::EQUAL,
         ]) {
             let equals = self.previous();
-            let value  = self.assignment();
+            let value = match self.assignment() {
+                Ok(value) => value,
+                Err(err) => return Err(err),
+            };
 
             match expr {
                 Expr::VARIABLE(var) => {
-                    return Expr::ASSIGN(
+                    return Ok(Expr::ASSIGN(
                         Assign::new(
                             var.name,
                             Box::new(value)
                         )
+                    ));
+                },
+                Expr::INDEX(idx) => {
+                    return Ok(Expr::SETINDEX(
+                        SetIndex::new(
+                            idx.object,
+                            idx.bracket,
+                            idx.index,
+                            Box::new(value)
+                        )
+                    ));
+                },
+                // The target is reported, but the parsing goes on
+                _ => {
+                    self.errors.push(
+                        ParseError::new("Invalid assignment target.".to_string(), equals)
                     );
+                    return Ok(expr);
                 },
-                _ => panic!("[PARSER] {} - Invalid assignment target", equals)
             }
         }
 
-        expr
+        if self.match_types(vec![
+            TokenType::PLUSEQUAL,
+            TokenType::MINUSEQUAL,
+            TokenType::STAREQUAL,
+            TokenType::SLASHEQUAL,
+        ]) {
+            let operator = self.previous();
+            let value = match self.assignment() {
+                Ok(value) => value,
+                Err(err) => return Err(err),
+            };
+            return self.compound(expr, operator, value, false);
+        }
+
+        Ok(expr)
+    }
+
+    // Compound assignments and increments only take variables and elements as targets
+    // Any other target is reported, but the parsing goes on
+    fn compound(&mut self, target: Expr, operator: Token, value: Expr, postfix: bool) -> Result<Expr, ParseError> {
+        match target {
+            Expr::VARIABLE(_) | Expr::INDEX(_) => Ok(Expr::COMPOUND(
+                Compound::new(
+                    Box::new(target),
+                    operator,
+                    Box::new(value),
+                    postfix
+                )
+            )),
+            _ => {
+                self.errors.push(
+                    ParseError::new("Invalid assignment target.".to_string(), operator)
+                );
+                Ok(target)
+            },
+        }
+    }
+
+    fn ternary(&mut self) -> Result<Expr, ParseError> {
+        let mut cond: Expr = match self.comma() {
+            Ok(cond) => cond,
+            Err(err) => return Err(err),
+        };
+
+        if self.match_types(vec![
+            TokenType::QUESTION,
+        ]) {
+            let line = self.previous().line;
+            let then_expr: Expr = match self.expression() {
+                Ok(then_expr) => then_expr,
+                Err(err) => return Err(err),
+            };
+            match self.consume(TokenType::COLON, "Expect ':' after THEN of conditional expression") {
+                Ok(_) => (),
+                Err(err) => return Err(err),
+            };
+            let else_expr: Expr = match self.ternary() {
+                Ok(else_expr) => else_expr,
+                Err(err) => return Err(err),
+            };
+            cond = Expr::TERNARY(
+                Ternary::new(
+                    Box::new(cond),
+                    Box::new(then_expr),
+                    Box::new(else_expr),
+                    line,
+                )
+            )
+        }
+
+        Ok(cond)
+    }
+
+    // Expression that stops before the comma operator, used in lists and arguments
+    fn element(&mut self) -> Result<Expr, ParseError> {
+        let allow_comma = self.allow_comma;
+        self.allow_comma = false;
+        let expr = self.expression();
+        self.allow_comma = allow_comma;
+        expr
+    }
+
+    fn comma(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = match self.logic_or() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
+
+        if !self.allow_comma {
+            return Ok(expr);
+        }
+
+        while self.match_types(vec![
+            TokenType::COMMA,
+        ]) {
+            let rhs: Expr = match self.logic_or() {
+                Ok(rhs) => rhs,
+                Err(err) => return Err(err),
+            };
+            expr = Expr::COMMA(
+                Comma::new(
+                    Box::new(expr),
+                    Box::new(rhs),
+                )
+            )
+        }
+
+        Ok(expr)
+    }
+
+    fn logic_or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = match self.logic_and() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
+
+        while self.match_types(vec![
+            TokenType::OR,
+        ]) {
+            let op: Token = self.previous();
+            let rhs: Expr = match self.logic_and() {
+                Ok(rhs) => rhs,
+                Err(err) => return Err(err),
+            };
+            expr = Expr::LOGICAL(
+                Logical::new(
+                    op,
+                    Box::new(expr),
+                    Box::new(rhs),
+                )
+            )
+        }
+
+        Ok(expr)
+    }
+
+    fn logic_and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = match self.equality() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
+
+        while self.match_types(vec![
+            TokenType::AND,
+        ]) {
+            let op: Token = self.previous();
+            let rhs: Expr = match self.equality() {
+                Ok(rhs) => rhs,
+                Err(err) => return Err(err),
+            };
+            expr = Expr::LOGICAL(
+                Logical::new(
+                    op,
+                    Box::new(expr),
+                    Box::new(rhs),
+                )
+            )
+        }
+
+        Ok(expr)
+    }
+
+    fn equality(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = match self.comparison() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
+
+        while self.match_types(vec![
+            TokenType::BANGEQUAL,
+            TokenType::EQUALEQUAL,
+        ]) {
+            let op: Token = self.previous();
+            let rhs: Expr = match self.comparison() {
+                Ok(rhs) => rhs,
+                Err(err) => return Err(err),
+            };
+            expr = Expr::BINARY(
+                Binary::new(
+                    op,
+                    Box::new(expr),
+                    Box::new(rhs),
+                )
+            )
+        }
+
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = match self.bit_or() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
+
+        while self.match_types(vec![
+            TokenType::GREATER,
+            TokenType::GREATEREQUAL,
+            TokenType::LESS,
+            TokenType::LESSEQUAL,
+        ]) {
+            let op: Token = self.previous();
+            let rhs: Expr = match self.bit_or() {
+                Ok(rhs) => rhs,
+                Err(err) => return Err(err),
+            };
+            expr = Expr::BINARY(
+                Binary::new(
+                    op,
+                    Box::new(expr),
+                    Box::new(rhs),
+                )
+            ) 
+        }
+
+        Ok(expr)
     }
 
-    fn ternary(&mut self) -> Expr {
-        let mut cond: Expr = self.comma();
+    fn bit_or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = match self.bit_xor() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
 
-        if self.match_types(vec![
-            TokenType::QUESTION,
+        while self.match_types(vec![
+            TokenType::PIPE,
         ]) {
-            let then_expr: Expr = self.expression();
-            match self.consume(TokenType::COLON, "Expect ':' after THEN of conditional expression") {
-                Ok(_) => (),
-                Err(err) => panic!("[PARSER] {}", err)
+            let op: Token = self.previous();
+            let rhs: Expr = match self.bit_xor() {
+                Ok(rhs) => rhs,
+                Err(err) => return Err(err),
             };
-            let else_expr: Expr = self.ternary();
-            cond = Expr::TERNARY(
-                Ternary::new(
-                    Box::new(cond),
-                    Box::new(then_expr),
-                    Box::new(else_expr),
+            expr = Expr::BINARY(
+                Binary::new(
+                    op,
+                    Box::new(expr),
+                    Box::new(rhs),
                 )
             )
         }
 
-        cond
+        Ok(expr)
     }
 
-    fn comma(&mut self) -> Expr {
-        let mut expr: Expr = self.equality();
+    fn bit_xor(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = match self.bit_and() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
 
         while self.match_types(vec![
-            TokenType::COMMA,
+            TokenType::CARET,
         ]) {
-            let rhs: Expr = self.equality();
-            expr = Expr::COMMA(
-                Comma::new(
+            let op: Token = self.previous();
+            let rhs: Expr = match self.bit_and() {
+                Ok(rhs) => rhs,
+                Err(err) => return Err(err),
+            };
+            expr = Expr::BINARY(
+                Binary::new(
+                    op,
                     Box::new(expr),
                     Box::new(rhs),
                 )
             )
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn equality(&mut self) -> Expr {
-        let mut expr: Expr = self.comparison();
+    fn bit_and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = match self.shift() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
 
         while self.match_types(vec![
-            TokenType::BANGEQUAL,
-            TokenType::EQUALEQUAL,
+            TokenType::AMPERSAND,
         ]) {
             let op: Token = self.previous();
-            let rhs: Expr = self.comparison();
+            let rhs: Expr = match self.shift() {
+                Ok(rhs) => rhs,
+                Err(err) => return Err(err),
+            };
             expr = Expr::BINARY(
                 Binary::new(
                     op,
@@ -254,41 +982,51 @@ impl Parser {
             )
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn comparison(&mut self) -> Expr {
-        let mut expr: Expr = self.term();
+    fn shift(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = match self.term() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
 
         while self.match_types(vec![
-            TokenType::GREATER,
-            TokenType::GREATEREQUAL,
-            TokenType::LESS,
-            TokenType::LESSEQUAL,
+            TokenType::LESSLESS,
+            TokenType::GREATERGREATER,
         ]) {
             let op: Token = self.previous();
-            let rhs: Expr = self.term();
+            let rhs: Expr = match self.term() {
+                Ok(rhs) => rhs,
+                Err(err) => return Err(err),
+            };
             expr = Expr::BINARY(
                 Binary::new(
                     op,
                     Box::new(expr),
                     Box::new(rhs),
                 )
-            ) 
+            )
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn term(&mut self) -> Expr {
-        let mut expr: Expr = self.factor();
+    fn term(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = match self.factor() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
 
         while self.match_types(vec![
             TokenType::MINUS,
             TokenType::PLUS,
         ]) {
             let op: Token = self.previous();
-            let rhs: Expr = self.factor();
+            let rhs: Expr = match self.factor() {
+                Ok(rhs) => rhs,
+                Err(err) => return Err(err),
+            };
             expr = Expr::BINARY(
                 Binary::new(
                     op,
@@ -298,18 +1036,26 @@ impl Parser {
             )
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn factor(&mut self) -> Expr {
-        let mut expr: Expr = self.unary();
+    fn factor(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = match self.unary() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
 
         while self.match_types(vec![
             TokenType::SLASH,
+            TokenType::SLASHSLASH,
+            TokenType::PERCENT,
             TokenType::STAR,
         ]) {
             let op: Token = self.previous();
-            let rhs: Expr = self.unary();
+            let rhs: Expr = match self.unary() {
+                Ok(rhs) => rhs,
+                Err(err) => return Err(err),
+            };
             expr = Expr::BINARY(
                 Binary::new(
                     op,
@@ -319,32 +1065,480 @@ impl Parser {
             )
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn unary(&mut self) -> Expr {
+    fn unary(&mut self) -> Result<Expr, ParseError> {
         while self.match_types(vec![
             TokenType::BANG,
             TokenType::MINUS,
         ]) {
             let op: Token = self.previous();
-            let rhs: Expr = self.unary();
-            return Expr::UNARY(
+            let rhs: Expr = match self.unary() {
+                Ok(rhs) => rhs,
+                Err(err) => return Err(err),
+            };
+            return Ok(Expr::UNARY(
                 Unary::new(
                     op,
                     Box::new(rhs),
                 )
+            ))
+        }
+
+        if self.match_types(vec![
+            TokenType::PLUSPLUS,
+            TokenType::MINUSMINUS,
+        ]) {
+            let operator: Token = self.previous();
+            let target: Expr = match self.unary() {
+                Ok(target) => target,
+                Err(err) => return Err(err),
+            };
+            return self.compound(target, operator, Expr::LITERAL(Literal::INTEGER(1)), false);
+        }
+
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr, ParseError> {
+        let expr: Expr = match self.call() {
+            Ok(expr) => expr,
+            Err(err) => return Err(err),
+        };
+
+        if self.match_types(vec![
+            TokenType::PLUSPLUS,
+            TokenType::MINUSMINUS,
+        ]) {
+            let operator: Token = self.previous();
+            return self.compound(expr, operator, Expr::LITERAL(Literal::INTEGER(1)), true);
+        }
+
+        Ok(expr)
+    }
+
+    fn call(&mut self) -> Result<Expr, ParseError> {
+        let mut expr: Expr = match self.primary() {
+            Ok(parsed) => parsed,
+            Err(err) => return Err(err),
+        };
+
+        loop {
+            if self.match_types(vec![
+                TokenType::LEFTPAREN,
+            ]) {
+                expr = match self.finish_call(expr) {
+                    Ok(call) => call,
+                    Err(err) => return Err(err),
+                };
+            } else if self.match_types(vec![
+                TokenType::LEFTBRACKET,
+            ]) {
+                expr = match self.finish_index(expr) {
+                    Ok(index) => index,
+                    Err(err) => return Err(err),
+                };
+            } else if self.match_types(vec![
+                TokenType::DOT,
+            ]) {
+                let name = match self.consume(TokenType::IDENTIFIER, "Expect member name after '.'") {
+                    Ok(t) => t,
+                    Err(err) => return Err(err),
+                };
+                expr = Expr::GET(Get::new(Box::new(expr), name));
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, ParseError> {
+        let mut arguments: Vec<Expr> = Vec::new();
+
+        if !self.check(TokenType::RIGHTPAREN) {
+            loop {
+                match self.element() {
+                    Ok(argument) => arguments.push(argument),
+                    Err(err) => return Err(err),
+                };
+
+                if !self.match_types(vec![
+                    TokenType::COMMA,
+                ]) {
+                    break;
+                }
+            }
+        }
+
+        let paren = match self.consume(TokenType::RIGHTPAREN, "Expect ')' after arguments") {
+            Ok(t) => t,
+            Err(err) => return Err(err),
+        };
+
+        Ok(Expr::CALL(
+            Call::new(
+                Box::new(callee),
+                paren,
+                arguments,
             )
+        ))
+    }
+
+    fn finish_index(&mut self, object: Expr) -> Result<Expr, ParseError> {
+        let bracket = self.previous();
+
+        let mut start: Option<Box<Expr>> = None;
+        if !self.check(TokenType::COLON) {
+            start = match self.element() {
+                Ok(expr) => Some(Box::new(expr)),
+                Err(err) => return Err(err),
+            };
+        }
+
+        let is_slice = self.match_types(vec![
+            TokenType::COLON,
+        ]);
+
+        let mut end: Option<Box<Expr>> = None;
+        if is_slice && !self.check(TokenType::RIGHTBRACKET) {
+            end = match self.element() {
+                Ok(expr) => Some(Box::new(expr)),
+                Err(err) => return Err(err),
+            };
+        }
+
+        match self.consume(TokenType::RIGHTBRACKET, "Expect ']' after index") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        if is_slice {
+            return Ok(Expr::SLICE(
+                Slice::new(
+                    Box::new(object),
+                    bracket,
+                    start,
+                    end,
+                )
+            ));
+        }
+
+        match start {
+            Some(index) => Ok(Expr::INDEX(
+                Index::new(
+                    Box::new(object),
+                    bracket,
+                    index,
+                )
+            )),
+            None => Err(ParseError::new("Expect index expression".to_string(), bracket)),
+        }
+    }
+
+    // "a ${x} b ${y} c" is scanned as INTERPOLATION("a ") x INTERPOLATION(" b ") y STRING(" c")
+    fn interpolation(&mut self) -> Result<Expr, ParseError> {
+        let line = self.previous().line;
+        let mut parts: Vec<Expr> = Vec::new();
+
+        loop {
+            let text = self.previous().value.unwrap_or_default();
+            if !text.is_empty() {
+                parts.push(Expr::LITERAL(Literal::STRING(text)));
+            }
+
+            if self.previous().kind == TokenType::STRING {
+                break;
+            }
+
+            let allow_comma = self.allow_comma;
+            self.allow_comma = true;
+            let part = self.expression();
+            self.allow_comma = allow_comma;
+
+            match part {
+                Ok(expr) => parts.push(expr),
+                Err(err) => return Err(err),
+            };
+
+            if !self.match_types(vec![
+                TokenType::INTERPOLATION,
+                TokenType::STRING,
+            ]) {
+                return Err(
+                    ParseError::new("Expect '}' after interpolated expression".to_string(), self.peek().clone())
+                );
+            }
+        }
+
+        Ok(Expr::FORMAT(Format::new(parts, line)))
+    }
+
+    // `(` starts an arrow lambda if the parenthesis only holds (annotated) names and is
+    // followed by `=>`, or by a return type and `=>`
+    fn is_arrow_lambda(&self) -> bool {
+        let mut offset = 1;
+
+        if self.peek_at(offset).kind != TokenType::RIGHTPAREN {
+            loop {
+                if self.peek_at(offset).kind != TokenType::IDENTIFIER {
+                    return false;
+                }
+                offset += 1;
+
+                if self.is_annotation_at(offset) {
+                    offset += 2;
+                }
+
+                match self.peek_at(offset).kind {
+                    TokenType::COMMA => offset += 1,
+                    TokenType::RIGHTPAREN => break,
+                    _ => return false,
+                }
+            }
+        }
+
+        offset += 1;
+        if self.is_annotation_at(offset) {
+            offset += 2;
+        }
+
+        self.peek_at(offset).kind == TokenType::ARROW
+    }
+
+    fn is_annotation_at(&self, offset: usize) -> bool {
+        self.peek_at(offset).kind == TokenType::COLON && self.peek_at(offset + 1).kind == TokenType::IDENTIFIER
+    }
+
+    // Names of the parameters and their annotations, up to the closing parenthesis
+    fn parameters(&mut self) -> Result<(Vec<Token>, Vec<Option<Token>>), ParseError> {
+        let mut params: Vec<Token> = Vec::new();
+        let mut annotations: Vec<Option<Token>> = Vec::new();
+
+        if !self.check(TokenType::RIGHTPAREN) {
+            loop {
+                match self.consume(TokenType::IDENTIFIER, "Expect parameter name") {
+                    Ok(t) => params.push(t),
+                    Err(err) => return Err(err),
+                };
+
+                match self.annotation() {
+                    Ok(t) => annotations.push(t),
+                    Err(err) => return Err(err),
+                };
+
+                if !self.match_types(vec![
+                    TokenType::COMMA,
+                ]) {
+                    break;
+                }
+            }
+        }
+
+        match self.consume(TokenType::RIGHTPAREN, "Expect ')' after parameters") {
+            Ok(_) => Ok((params, annotations)),
+            Err(err) => Err(err),
+        }
+    }
+
+    // The body is parsed outside of any loop, `break` can't leave the function
+    fn lambda_body(&mut self, arrow: bool) -> Result<Vec<Stmt>, ParseError> {
+        let loop_depth = self.loop_depth;
+        self.loop_depth = 0;
+        self.function_depth += 1;
+
+        // The depths are restored before giving back an error too
+        let body = if arrow {
+            let keyword = self.previous();
+            self.element().map(|value| vec![Stmt::RETURN(Return::new(keyword, Some(Box::new(value))))])
+        } else {
+            match self.consume(TokenType::LEFTBRACE, "Expect '{' before lambda body") {
+                Ok(_) => self.block(),
+                Err(err) => Err(err),
+            }
+        };
+
+        self.function_depth -= 1;
+        self.loop_depth = loop_depth;
+        body
+    }
+
+    fn match_expression(&mut self) -> Result<Expr, ParseError> {
+        let keyword = self.previous();
+
+        match self.consume(TokenType::LEFTPAREN, "Expect '(' after 'match'") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+        let subject = match self.expression() {
+            Ok(subject) => subject,
+            Err(err) => return Err(err),
+        };
+        match self.consume(TokenType::RIGHTPAREN, "Expect ')' after match value") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+        match self.consume(TokenType::LEFTBRACE, "Expect '{' before match arms") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        let mut arms: Vec<MatchArm> = Vec::new();
+        while !self.check(TokenType::RIGHTBRACE) && !self.is_at_end() {
+            match self.match_arm() {
+                Ok(arm) => arms.push(arm),
+                Err(err) => return Err(err),
+            };
+
+            if !self.match_types(vec![
+                TokenType::COMMA,
+            ]) {
+                break;
+            }
+        }
+
+        match self.consume(TokenType::RIGHTBRACE, "Expect '}' after match arms") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+
+        Ok(Expr::MATCH(Match::new(keyword, Box::new(subject), arms)))
+    }
+
+    // A binding among alternatives is reported, but the parsing goes on
+    fn match_arm(&mut self) -> Result<MatchArm, ParseError> {
+        let token = self.peek().clone();
+        let mut patterns: Vec<Pattern> = Vec::new();
+
+        loop {
+            match self.pattern() {
+                Ok(pattern) => patterns.push(pattern),
+                Err(err) => return Err(err),
+            };
+
+            if !self.match_types(vec![
+                TokenType::PIPE,
+            ]) {
+                break;
+            }
+        }
+
+        if patterns.len() > 1 && patterns.iter().any(|p| matches!(p, Pattern::BINDING(_))) {
+            self.errors.push(
+                ParseError::new("A binding can't be one of several alternatives.".to_string(), token.clone())
+            );
+        }
+
+        let guard = if self.match_types(vec![
+            TokenType::IF,
+        ]) {
+            match self.element() {
+                Ok(guard) => Some(Box::new(guard)),
+                Err(err) => return Err(err),
+            }
+        } else {
+            None
+        };
+
+        match self.consume(TokenType::ARROW, "Expect '=>' after match pattern") {
+            Ok(_) => (),
+            Err(err) => return Err(err),
+        };
+        let body = match self.element() {
+            Ok(body) => body,
+            Err(err) => return Err(err),
+        };
+
+        Ok(MatchArm::new(token, patterns, guard, Box::new(body)))
+    }
+
+    // Literals (numbers may be negative), type names, `_` and names bound to the value
+    fn pattern(&mut self) -> Result<Pattern, ParseError> {
+        if self.match_types(vec![
+            TokenType::UNDERSCORE,
+        ]) {
+            return Ok(Pattern::WILDCARD);
+        }
+
+        if self.match_types(vec![
+            TokenType::IDENTIFIER,
+        ]) {
+            let name = self.previous();
+            return match name.value.as_deref() {
+                Some(ty) if TYPE_PATTERNS.contains(&ty) => Ok(Pattern::TYPE(name)),
+                _ => Ok(Pattern::BINDING(name)),
+            };
         }
 
+        let token = self.peek().clone();
+        let negative = self.match_types(vec![
+            TokenType::MINUS,
+        ]);
+
         match self.primary() {
-            Ok(parsed) => return parsed,
-            Err(err) => panic!("[PARSER] {}", err)
+            Ok(Expr::LITERAL(Literal::NUMBER(val))) if negative => Ok(Pattern::LITERAL(Literal::NUMBER(-val))),
+            Ok(Expr::LITERAL(Literal::INTEGER(val))) if negative => Ok(Pattern::LITERAL(Literal::INTEGER(-val))),
+            Ok(Expr::LITERAL(lit)) if !negative => Ok(Pattern::LITERAL(lit)),
+            _ => Err(
+                ParseError::new("Expect a literal, type name, '_' or name as pattern".to_string(), token)
+            ),
         }
     }
 
     fn primary(&mut self) -> Result<Expr, ParseError> {
 
+        if self.match_types(vec![
+            TokenType::MATCH,
+        ]) {
+            return self.match_expression();
+        }
+
+        if self.match_types(vec![
+            TokenType::FUN,
+        ]) {
+            let keyword = self.previous();
+            match self.consume(TokenType::LEFTPAREN, "Expect '(' after 'fun'") {
+                Ok(_) => (),
+                Err(err) => return Err(err),
+            };
+            let (params, annotations) = match self.parameters() {
+                Ok(parameters) => parameters,
+                Err(err) => return Err(err),
+            };
+            let returns = match self.annotation() {
+                Ok(t) => t,
+                Err(err) => return Err(err),
+            };
+            let body = match self.lambda_body(false) {
+                Ok(body) => body,
+                Err(err) => return Err(err),
+            };
+            return Ok(Expr::LAMBDA(Lambda::new(keyword, params, annotations, returns, body)));
+        }
+
+        if self.check(TokenType::LEFTPAREN) && self.is_arrow_lambda() {
+            let keyword = self.advance();
+            let (params, annotations) = match self.parameters() {
+                Ok(parameters) => parameters,
+                Err(err) => return Err(err),
+            };
+            let returns = match self.annotation() {
+                Ok(t) => t,
+                Err(err) => return Err(err),
+            };
+            match self.consume(TokenType::ARROW, "Expect '=>' after parameters") {
+                Ok(_) => (),
+                Err(err) => return Err(err),
+            };
+            let body = match self.lambda_body(true) {
+                Ok(body) => body,
+                Err(err) => return Err(err),
+            };
+            return Ok(Expr::LAMBDA(Lambda::new(keyword, params, annotations, returns, body)));
+        }
+
         if self.match_types(vec![
             TokenType::NIL,
         ]) {
@@ -354,17 +1548,25 @@ impl Parser {
         if self.match_types(vec![
             TokenType::STRING,
             TokenType::NUMBER,
+            TokenType::INTEGER,
             TokenType::FALSE,
             TokenType::TRUE,
         ]) {
             return match self.previous().get_value() {
                 Some(ValueTypes::NUMBER(value)) => Ok(Expr::LITERAL(Literal::NUMBER(value))),
+                Some(ValueTypes::INTEGER(value)) => Ok(Expr::LITERAL(Literal::INTEGER(value))),
                 Some(ValueTypes::STRING(value)) => Ok(Expr::LITERAL(Literal::STRING(value))),
                 Some(ValueTypes::BOOL(value)) => Ok(Expr::LITERAL(Literal::BOOL(value))),
                 _ => Err(ParseError::new("Expect number, string or bool".to_string(), self.previous())),
             }
         }
 
+        if self.match_types(vec![
+            TokenType::INTERPOLATION,
+        ]) {
+            return self.interpolation();
+        }
+
         if self.match_types(vec![
             TokenType::IDENTIFIER
         ]) {
@@ -374,10 +1576,18 @@ impl Parser {
         if self.match_types(vec![
             TokenType::LEFTPAREN,
         ]) {
-            let expr = self.expression();
+            let allow_comma = self.allow_comma;
+            self.allow_comma = true;
+            let grouped = self.expression();
+            self.allow_comma = allow_comma;
+
+            let expr = match grouped {
+                Ok(expr) => expr,
+                Err(err) => return Err(err),
+            };
             match self.consume(TokenType::RIGHTPAREN, "Expect ')' after expression") {
                 Ok(_) => (),
-                Err(err) => panic!("[PARSER] {}", err)
+                Err(err) => return Err(err),
             };
             return Ok(Expr::GROUPING(
                 Grouping::new(
@@ -386,16 +1596,77 @@ impl Parser {
             ));
         }
 
+        if self.match_types(vec![
+            TokenType::LEFTBRACKET,
+        ]) {
+            let mut elements: Vec<Expr> = Vec::new();
+
+            while !self.check(TokenType::RIGHTBRACKET) {
+                match self.element() {
+                    Ok(element) => elements.push(element),
+                    Err(err) => return Err(err),
+                };
+
+                if !self.match_types(vec![
+                    TokenType::COMMA,
+                ]) {
+                    break;
+                }
+            }
+
+            match self.consume(TokenType::RIGHTBRACKET, "Expect ']' after list elements") {
+                Ok(_) => (),
+                Err(err) => return Err(err),
+            };
+            return Ok(Expr::LIST(List::new(elements)));
+        }
+
+        // Statements starting with '{' are blocks, so here it is always a map
+        if self.match_types(vec![
+            TokenType::LEFTBRACE,
+        ]) {
+            let brace = self.previous();
+            let mut entries: Vec<(Expr, Expr)> = Vec::new();
+
+            while !self.check(TokenType::RIGHTBRACE) {
+                let key = match self.element() {
+                    Ok(key) => key,
+                    Err(err) => return Err(err),
+                };
+                match self.consume(TokenType::COLON, "Expect ':' after map key") {
+                    Ok(_) => (),
+                    Err(err) => return Err(err),
+                };
+                let value = match self.element() {
+                    Ok(value) => value,
+                    Err(err) => return Err(err),
+                };
+                entries.push((key, value));
+
+                if !self.match_types(vec![
+                    TokenType::COMMA,
+                ]) {
+                    break;
+                }
+            }
+
+            match self.consume(TokenType::RIGHTBRACE, "Expect '}' after map entries") {
+                Ok(_) => (),
+                Err(err) => return Err(err),
+            };
+            return Ok(Expr::MAP(Map::new(brace, entries)));
+        }
+
         // ERROR Productions
         // They are considering operations without left-hand operands, consuming
-        // the right-hand operand
+        // the right-hand operand (its own errors are dropped, the missing operand is reported)
 
         if self.match_types(vec![
             TokenType::BANGEQUAL,
             TokenType::EQUALEQUAL,
         ]) {
             let err = Err(ParseError::new("Missing left-hand Operand".to_string(), self.previous()));
-            self.equality();
+            let _ = self.equality();
             return err;
         }
 
@@ -406,7 +1677,19 @@ impl Parser {
             TokenType::LESSEQUAL,
         ]) {
             let err = Err(ParseError::new("Missing left-hand Operand".to_string(), self.previous()));
-            self.comparison();
+            let _ = self.comparison();
+            return err;
+        }
+
+        if self.match_types(vec![
+            TokenType::PIPE,
+            TokenType::CARET,
+            TokenType::AMPERSAND,
+            TokenType::LESSLESS,
+            TokenType::GREATERGREATER,
+        ]) {
+            let err = Err(ParseError::new("Missing left-hand Operand".to_string(), self.previous()));
+            let _ = self.bit_or();
             return err;
         }
 
@@ -414,16 +1697,18 @@ impl Parser {
             TokenType::PLUS,
         ]) {
             let err = Err(ParseError::new("Missing left-hand Operand".to_string(), self.previous()));
-            self.term();
+            let _ = self.term();
             return err;
         }
 
         if self.match_types(vec![
             TokenType::SLASH,
+            TokenType::SLASHSLASH,
+            TokenType::PERCENT,
             TokenType::STAR,
         ]) {
             let err = Err(ParseError::new("Missing left-hand Operand".to_string(), self.previous()));
-            self.factor();
+            let _ = self.factor();
             return err;
         }
 
@@ -452,6 +1737,11 @@ impl Parser {
                 | TokenType::IF
                 | TokenType::WHILE
                 | TokenType::PRINT
+                | TokenType::BREAK
+                | TokenType::CONTINUE
+                | TokenType::IMPORT
+                | TokenType::THROW
+                | TokenType::TRY
                 | TokenType::RETURN => return,
                 _ => self.advance(),
             };
@@ -1,4 +1,4 @@
-use crate::{token::Token, token_type::TokenType};
+use crate::{error::ScanError, token::Token, token_type::TokenType};
 
 const RADIX: u32 = 10;
 
@@ -8,6 +8,16 @@ pub struct Scanner {
     start: usize,
     current: usize,
     line: usize,
+    // Index of the first character of the current line, to report columns
+    line_start: usize,
+    pub errors: Vec<ScanError>,
+    // Open braces inside each `${` being scanned, innermost last
+    interpolations: Vec<usize>,
+    // For each open '(', whether it starts the header of if/while/for/catch/match or
+    // the parameters of a lambda, the others are calls and groupings
+    parens: Vec<bool>,
+    // Whether the last ')' closed one of those headers
+    closed_header: bool,
 }
 
 impl Scanner {
@@ -25,6 +35,11 @@ impl Scanner {
             start,
             current,
             line,
+            line_start: 0,
+            errors: Vec::new(),
+            interpolations: Vec::new(),
+            parens: Vec::new(),
+            closed_header: false,
         }
     }
 
@@ -35,6 +50,11 @@ impl Scanner {
             self.scan();
         }
 
+        // A `${` still open at the end, reported at the end of the source
+        if !self.interpolations.is_empty() {
+            self.error_at(self.current, "Unterminated string interpolation".to_string());
+        }
+
         self.tokens.push(Token::new(TokenType::EOF, self.line, None));
 
         &self.tokens
@@ -76,21 +96,49 @@ impl Scanner {
         self.tokens.push(Token::new(kind, self.line, value))
     }
 
+    // Errors are reported at the start of the token being scanned
+    fn error(&mut self, message: String) {
+        self.error_at(self.start, message);
+    }
+
+    // The position must be on the current line, for the column to make sense
+    fn error_at(&mut self, position: usize, message: String) {
+        let column = position - self.line_start + 1;
+        self.errors.push(ScanError::new(message, self.line, column));
+    }
+
+    fn newline(&mut self) {
+        self.line += 1;
+        self.line_start = self.current;
+    }
+
     fn check_keyword(&self, text_token: &Vec<char>) -> TokenType {
         match text_token[..] {
             ['A', 'N', 'D'] => TokenType::AND,
+            ['A', 'S'] => TokenType::AS,
+            ['B', 'R', 'E', 'A', 'K'] => TokenType::BREAK,
+            ['C', 'A', 'T', 'C', 'H'] => TokenType::CATCH,
             ['C', 'L', 'A', 'S', 'S'] => TokenType::CLASS,
+            ['C', 'O', 'N', 'S', 'T'] => TokenType::CONST,
+            ['C', 'O', 'N', 'T', 'I', 'N', 'U', 'E'] => TokenType::CONTINUE,
             ['E', 'L', 'S', 'E'] => TokenType::ELSE,
+            ['F', 'A', 'L', 'S', 'E'] => TokenType::FALSE,
+            ['F', 'I', 'N', 'A', 'L', 'L', 'Y'] => TokenType::FINALLY,
             ['F', 'O', 'R'] => TokenType::FOR,
             ['F', 'U', 'N'] => TokenType::FUN,
             ['I', 'F'] => TokenType::IF,
+            ['I', 'M', 'P', 'O', 'R', 'T'] => TokenType::IMPORT,
+            ['I', 'N'] => TokenType::IN,
+            ['M', 'A', 'T', 'C', 'H'] => TokenType::MATCH,
             ['N', 'I', 'L'] => TokenType::NIL,
             ['O', 'R'] => TokenType::OR,
             ['P', 'R', 'I', 'N', 'T'] => TokenType::PRINT,
             ['R', 'E', 'T', 'U', 'R', 'N'] => TokenType::RETURN,
             ['S', 'U', 'P', 'E', 'R'] => TokenType::SUPER,
             ['T', 'H', 'I', 'S'] => TokenType::THIS,
+            ['T', 'H', 'R', 'O', 'W'] => TokenType::THROW,
             ['T', 'R', 'U', 'E'] => TokenType::TRUE,
+            ['T', 'R', 'Y'] => TokenType::TRY,
             ['V', 'A', 'R'] => TokenType::VAR,
             ['W', 'H', 'I', 'L', 'E'] => TokenType::WHILE,
             _ => {
@@ -101,18 +149,86 @@ impl Scanner {
 
     fn scan(&mut self) {
        match self.advance() {
-            '(' => self.add_token(TokenType::LEFTPAREN, None),
-            ')' => self.add_token(TokenType::RIGHTPAREN, None),
-            '{' => self.add_token(TokenType::LEFTBRACE, None),
-            '}' => self.add_token(TokenType::RIGHTBRACE, None),
+            '(' => {
+                let header = matches!(
+                    self.tokens.last().map(|t| &t.kind),
+                    Some(
+                        TokenType::IF
+                            | TokenType::WHILE
+                            | TokenType::FOR
+                            | TokenType::CATCH
+                            | TokenType::MATCH
+                            | TokenType::FUN
+                    )
+                );
+                self.parens.push(header);
+                self.add_token(TokenType::LEFTPAREN, None)
+            },
+            ')' => {
+                self.closed_header = self.parens.pop().unwrap_or(false);
+                self.add_token(TokenType::RIGHTPAREN, None)
+            },
+            '{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                self.add_token(TokenType::LEFTBRACE, None)
+            },
+            '}' => match self.interpolations.last_mut() {
+                // Closes the `${`, the string goes on
+                Some(0) => {
+                    self.interpolations.pop();
+                    self.string();
+                },
+                Some(depth) => {
+                    *depth -= 1;
+                    self.add_token(TokenType::RIGHTBRACE, None)
+                },
+                None => self.add_token(TokenType::RIGHTBRACE, None),
+            },
+            '[' => self.add_token(TokenType::LEFTBRACKET, None),
+            ']' => self.add_token(TokenType::RIGHTBRACKET, None),
             ',' => self.add_token(TokenType::COMMA, None),
-            '.' => self.add_token(TokenType::DOT, None),
-            '-' => self.add_token(TokenType::MINUS, None),
-            '+' => self.add_token(TokenType::PLUS, None),
+            '.' => {
+                if self.peek().is_digit(RADIX) {
+                    self.number()
+                } else {
+                    self.add_token(TokenType::DOT, None)
+                }
+            },
+            '-' => {
+                if self.match_char('-') {
+                    self.add_token(TokenType::MINUSMINUS, None)
+                } else if self.match_char('=') {
+                    self.add_token(TokenType::MINUSEQUAL, None)
+                } else {
+                    self.add_token(TokenType::MINUS, None)
+                }
+            },
+            '+' => {
+                if self.match_char('+') {
+                    self.add_token(TokenType::PLUSPLUS, None)
+                } else if self.match_char('=') {
+                    self.add_token(TokenType::PLUSEQUAL, None)
+                } else {
+                    self.add_token(TokenType::PLUS, None)
+                }
+            },
             ';' => self.add_token(TokenType::SEMICOLON, None),
-            '*' => self.add_token(TokenType::STAR, None),
+            '*' => {
+                if self.match_char('=') {
+                    self.add_token(TokenType::STAREQUAL, None)
+                } else {
+                    self.add_token(TokenType::STAR, None)
+                }
+            },
+            '%' => self.add_token(TokenType::PERCENT, None),
+            '&' => self.add_token(TokenType::AMPERSAND, None),
+            '|' => self.add_token(TokenType::PIPE, None),
+            '^' => self.add_token(TokenType::CARET, None),
             '?' => self.add_token(TokenType::QUESTION, None),
             ':' => self.add_token(TokenType::COLON, None),
+            '_' => self.add_token(TokenType::UNDERSCORE, None),
             '!' => {
                 if self.match_char('=') {
                     self.add_token(TokenType::BANGEQUAL, None)
@@ -123,6 +239,8 @@ impl Scanner {
             '=' => {
                 if self.match_char('=') {
                     self.add_token(TokenType::EQUALEQUAL, None)
+                } else if self.match_char('>') {
+                    self.add_token(TokenType::ARROW, None)
                 } else { 
                     self.add_token(TokenType::EQUAL, None)
                 }
@@ -130,6 +248,8 @@ impl Scanner {
             '<' => {
                 if self.match_char('=') {
                     self.add_token(TokenType::LESSEQUAL, None)
+                } else if self.match_char('<') {
+                    self.add_token(TokenType::LESSLESS, None)
                 } else { 
                     self.add_token(TokenType::LESS, None)
                 }
@@ -137,38 +257,84 @@ impl Scanner {
             '>' => {
                 if self.match_char('=') {
                     self.add_token(TokenType::GREATEREQUAL, None)
+                } else if self.match_char('>') {
+                    self.add_token(TokenType::GREATERGREATER, None)
                 } else { 
                     self.add_token(TokenType::GREATER, None)
                 }
             },
             '/' => {
                 if self.match_char('/') {
-                    while self.peek() != '\n' && !self.is_at_end() {
-                        self.advance();
+                    if self.after_operand() && !self.words_follow() {
+                        self.add_token(TokenType::SLASHSLASH, None);
+                    } else {
+                        while self.peek() != '\n' && !self.is_at_end() {
+                            self.advance();
+                        }
                     }
                 } else if self.match_char('*') {
                     self.block_comment();
+                } else if self.match_char('=') {
+                    self.add_token(TokenType::SLASHEQUAL, None);
                 } else {
                     self.add_token(TokenType::SLASH, None);
                 }
             },
             ' ' | '\r' | '\t' => (),
-            '\n' => self.line += 1,
+            '\n' => self.newline(),
             '"' => self.string(),
             '0'..='9' => self.number(),
-            'a'..='z' | 'A'..='Z' => self.identifier(),
-            _ => {
-                panic!("[SCANNER] Error: Unexpected character");
-            }
+            c if c.is_alphabetic() => self.identifier(),
+            other => self.error(format!("Unexpected character '{}'", other)),
         } 
     }
 
+    // `//` right after a value is the integer division, anywhere else it starts a comment.
+    // The ')' closing an if/while/for/catch/match header or the parameters of a lambda is
+    // not a value, so a comment can follow.
+    fn after_operand(&self) -> bool {
+        match self.tokens.last().map(|t| &t.kind) {
+            Some(TokenType::RIGHTPAREN) => !self.closed_header,
+            Some(
+                TokenType::NUMBER
+                | TokenType::INTEGER
+                | TokenType::STRING
+                | TokenType::IDENTIFIER
+                | TokenType::RIGHTBRACKET
+                | TokenType::TRUE
+                | TokenType::FALSE
+                | TokenType::NIL
+            ) => true,
+            _ => false,
+        }
+    }
+
+    // Whether the rest of the line, up to a `;` or another `//`, has two operands in a row,
+    // which no expression has, so the `//` after a value starts a comment like `x // the x`
+    fn words_follow(&self) -> bool {
+        let rest: String = self.source[self.current..].iter().take_while(|c| **c != '\n').collect();
+        let code = rest.split(';').next().unwrap_or_default();
+        let code = code.split("//").next().unwrap_or_default();
+
+        // The keywords that sit between operands
+        let keyword = |word: &str| matches!(word, "and" | "or" | "in" | "fun");
+        let ends_operand = |word: &str| {
+            !keyword(word) && word.ends_with(|c: char| c.is_alphanumeric() || matches!(c, '_' | '"' | ')' | ']'))
+        };
+        let starts_operand = |word: &str| {
+            !keyword(word) && word.starts_with(|c: char| c.is_alphanumeric() || matches!(c, '_' | '"'))
+        };
+
+        let words: Vec<&str> = code.split_whitespace().collect();
+        words.windows(2).any(|pair| ends_operand(pair[0]) && starts_operand(pair[1]))
+    }
+
     fn block_comment(&mut self) {
         while !self.is_at_end() {
 
             if self.peek() == '\n' {
-                self.line += 1;
                 self.advance();
+                self.newline();
             }
 
             if self.peek() == '/' && self.peek_next() == '*' {
@@ -187,40 +353,209 @@ impl Scanner {
         }
     }
 
+    // Scans up to the closing quote, or up to a `${`, where the text so far becomes an
+    // INTERPOLATION token and the expression is scanned as usual until its `}`
     fn string(&mut self) {
+        let mut str_token = String::new();
 
         while self.peek() != '"' && !self.is_at_end() {
             if self.peek() == '\n' {
-                self.line += 1;
+                self.advance();
+                self.newline();
+                str_token.push('\n');
+                continue;
             }
-            self.advance();
+
+            // A bad escape is reported and left out, the rest of the string is still scanned
+            if self.peek() == '\\' {
+                let backslash = self.current;
+                self.advance();
+                if self.is_at_end() {
+                    break;
+                }
+                match self.escape() {
+                    Ok(c) => str_token.push(c),
+                    Err(message) => self.error_at(backslash, message),
+                };
+                continue;
+            }
+
+            if self.peek() == '$' && self.peek_next() == '{' {
+                self.advance();
+                self.advance();
+                self.interpolations.push(0);
+                self.add_token(TokenType::INTERPOLATION, Some(str_token));
+                return;
+            }
+
+            str_token.push(self.advance());
         }
 
+        // The string may span several lines, it is reported where the source ends
         if self.is_at_end() {
-            panic!("[SCANNER] Error: Unterminated string");
+            self.error_at(self.current, "Unterminated string".to_string());
+            return;
         }
 
         self.advance();
 
-        let str_token = self.source[self.start + 1..self.current - 1].iter().collect::<String>();
         self.add_token(TokenType::STRING, Some(str_token));
     }
 
+    // Character written by the escape sequence after a backslash
+    fn escape(&mut self) -> Result<char, String> {
+        match self.advance() {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '"' => Ok('"'),
+            '\\' => Ok('\\'),
+            '$' => Ok('$'),
+            'u' => self.unicode_escape(),
+            other => Err(format!("Unknown escape sequence '\\{}'", other)),
+        }
+    }
+
+    // \u{XXXX}, with 1 to 6 hex digits naming a Unicode scalar value
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        if !self.match_char('{') {
+            return Err("Expect '{' after '\\u'".to_string());
+        }
+
+        let mut digits = String::new();
+        while self.peek().is_ascii_hexdigit() && digits.len() < 6 {
+            digits.push(self.advance());
+        }
+
+        if digits.is_empty() || !self.match_char('}') {
+            return Err("Invalid unicode escape".to_string());
+        }
+
+        match u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32) {
+            Some(c) => Ok(c),
+            None => Err(format!("Invalid unicode code point '{}'", digits)),
+        }
+    }
+
+    // Digits in the radix, where '_' can separate two of them
+    fn digits(&mut self, radix: u32) -> Result<String, String> {
+        let mut digits = String::new();
+
+        loop {
+            let c = self.peek();
+
+            if c.is_digit(radix) {
+                digits.push(self.advance());
+            } else if c == '_' && !digits.is_empty() && self.peek_next().is_digit(radix) {
+                self.advance();
+            } else if c == '_' {
+                return Err("'_' must be between digits".to_string());
+            } else {
+                break;
+            }
+        }
+
+        Ok(digits)
+    }
+
+    // A malformed number is reported whole, and scanning goes on after it.
+    // It still gives a token, so the parser doesn't report errors of its own.
     fn number(&mut self) {
+        match self.number_literal() {
+            Ok((kind, value)) => self.add_token(kind, Some(value)),
+            Err(message) => {
+                while self.peek().is_alphanumeric() || self.peek() == '_' {
+                    self.advance();
+                }
 
-        while self.peek().is_digit(RADIX) {
-            self.advance();
+                let literal = self.source[self.start..self.current].iter().collect::<String>();
+                self.error(format!("Malformed number '{}': {}", literal, message));
+                self.add_token(TokenType::INTEGER, Some("0".to_string()));
+            }
         }
+    }
 
-        if self.peek() == '.' && self.peek_next().is_digit(RADIX) {
-            self.advance();
-            while self.peek().is_digit(RADIX) {
+    // Numbers are `0x` hex, `0b` binary or decimal with optional fraction and exponent.
+    // Without fraction nor exponent they are INTEGER tokens, otherwise NUMBER (floats),
+    // as are the decimal integers out of the 64 bits range.
+    // The token keeps the value in decimal, so the parser reads every kind the same way.
+    fn number_literal(&mut self) -> Result<(TokenType, String), String> {
+        let first = self.source[self.start];
+        let mut str_token = String::new();
+        let mut is_float = first == '.';
+
+        if first == '0' && matches!(self.peek(), 'x' | 'X' | 'b' | 'B') {
+            let prefix = self.advance();
+            let radix = if prefix == 'x' || prefix == 'X' { 16 } else { 2 };
+
+            let digits = self.digits(radix)?;
+            if digits.is_empty() {
+                return Err(format!("expect digits after '0{}'", prefix));
+            }
+
+            self.number_end()?;
+
+            return match i64::from_str_radix(&digits, radix) {
+                Ok(value) => Ok((TokenType::INTEGER, value.to_string())),
+                Err(_) => Err("integer out of range".to_string()),
+            };
+        }
+
+        if first == '.' {
+            str_token.push_str("0.");
+            str_token.push_str(&self.digits(RADIX)?);
+        } else {
+            // Rescans the first digit, so a '_' right after it is a separator
+            self.current = self.start;
+            str_token.push_str(&self.digits(RADIX)?);
+
+            if self.peek() == '.' && self.peek_next().is_digit(RADIX) {
+                is_float = true;
                 self.advance();
+                str_token.push('.');
+                str_token.push_str(&self.digits(RADIX)?);
             }
         }
 
-        let str_token = self.source[self.start..self.current].iter().collect::<String>();
-        self.add_token(TokenType::NUMBER, Some(str_token));
+        if matches!(self.peek(), 'e' | 'E') {
+            is_float = true;
+            self.advance();
+            str_token.push('e');
+
+            if matches!(self.peek(), '+' | '-') {
+                str_token.push(self.advance());
+            }
+
+            let digits = self.digits(RADIX)?;
+            if digits.is_empty() {
+                return Err("expect digits in exponent".to_string());
+            }
+            str_token.push_str(&digits);
+        }
+
+        self.number_end()?;
+
+        if !is_float {
+            if let Ok(value) = str_token.parse::<i64>() {
+                return Ok((TokenType::INTEGER, value.to_string()));
+            }
+        }
+
+        match str_token.parse::<f64>() {
+            Ok(value) => Ok((TokenType::NUMBER, value.to_string())),
+            Err(_) => Err("invalid number".to_string()),
+        }
+    }
+
+    // A number can't run into letters, as in `12abc` or `0x1g`
+    fn number_end(&self) -> Result<(), String> {
+        let c = self.peek();
+
+        if c.is_alphanumeric() || c == '_' {
+            return Err(format!("unexpected '{}'", c));
+        }
+
+        Ok(())
     }
 
     fn identifier(&mut self) {
@@ -12,6 +12,7 @@ pub struct Token {
 pub enum ValueTypes {
     STRING(String),
     NUMBER(f64),
+    INTEGER(i64),
     BOOL(bool),
 }
 
@@ -41,6 +42,9 @@ impl Token {
             TokenType::NUMBER => Some(
                 ValueTypes::NUMBER(self.value.clone().unwrap().parse::<f64>().unwrap())
             ),
+            TokenType::INTEGER => Some(
+                ValueTypes::INTEGER(self.value.clone().unwrap().parse::<i64>().unwrap())
+            ),
             _ => None,
         }
     }
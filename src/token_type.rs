@@ -6,45 +6,103 @@ pub enum TokenType {
     RIGHTPAREN,
     LEFTBRACE,
     RIGHTBRACE,
+    LEFTBRACKET,
+    RIGHTBRACKET,
     COMMA,
     DOT,
     MINUS,
+    MINUSEQUAL,
+    MINUSMINUS,
     PLUS,
+    PLUSEQUAL,
+    PLUSPLUS,
     SEMICOLON,
     SLASH,
+    SLASHEQUAL,
+    SLASHSLASH,
+    PERCENT,
     STAR,
+    STAREQUAL,
     BANG,
     QUESTION,
     COLON,
+    UNDERSCORE,
+    AMPERSAND,
+    PIPE,
+    CARET,
     BANGEQUAL,
     EQUAL,
     EQUALEQUAL,
+    ARROW,
     GREATER,
     GREATEREQUAL,
+    GREATERGREATER,
     LESS,
     LESSEQUAL,
+    LESSLESS,
     IDENTIFIER,
     STRING,
+    INTERPOLATION,
     NUMBER,
+    INTEGER,
     AND,
+    AS,
+    BREAK,
+    CATCH,
     CLASS,
+    CONST,
+    CONTINUE,
     ELSE,
     FALSE,
+    FINALLY,
     FUN,
     FOR,
     IF,
+    IMPORT,
+    IN,
+    MATCH,
     NIL,
     OR,
     PRINT,
     RETURN,
     SUPER,
     THIS,
+    THROW,
     TRUE,
+    TRY,
     VAR,
     WHILE,
     EOF,
 }
 
+// Every token type, to read them back from their names
+pub const TOKEN_TYPES: [TokenType; 71] = [
+    TokenType::LEFTPAREN, TokenType::RIGHTPAREN, TokenType::LEFTBRACE,
+    TokenType::RIGHTBRACE, TokenType::LEFTBRACKET, TokenType::RIGHTBRACKET,
+    TokenType::COMMA, TokenType::DOT, TokenType::MINUS, TokenType::MINUSEQUAL,
+    TokenType::MINUSMINUS, TokenType::PLUS, TokenType::PLUSEQUAL, TokenType::PLUSPLUS,
+    TokenType::SEMICOLON, TokenType::SLASH, TokenType::SLASHEQUAL, TokenType::SLASHSLASH,
+    TokenType::PERCENT, TokenType::STAR, TokenType::STAREQUAL, TokenType::BANG,
+    TokenType::QUESTION, TokenType::COLON, TokenType::UNDERSCORE, TokenType::AMPERSAND,
+    TokenType::PIPE, TokenType::CARET, TokenType::BANGEQUAL, TokenType::EQUAL,
+    TokenType::EQUALEQUAL, TokenType::ARROW, TokenType::GREATER, TokenType::GREATEREQUAL,
+    TokenType::GREATERGREATER, TokenType::LESS, TokenType::LESSEQUAL, TokenType::LESSLESS,
+    TokenType::IDENTIFIER, TokenType::STRING, TokenType::INTERPOLATION, TokenType::NUMBER,
+    TokenType::INTEGER, TokenType::AND, TokenType::AS, TokenType::BREAK, TokenType::CATCH,
+    TokenType::CLASS, TokenType::CONST, TokenType::CONTINUE, TokenType::ELSE,
+    TokenType::FALSE, TokenType::FINALLY, TokenType::FUN, TokenType::FOR, TokenType::IF,
+    TokenType::IMPORT, TokenType::IN, TokenType::MATCH, TokenType::NIL, TokenType::OR,
+    TokenType::PRINT, TokenType::RETURN, TokenType::SUPER, TokenType::THIS,
+    TokenType::THROW, TokenType::TRUE, TokenType::TRY, TokenType::VAR, TokenType::WHILE,
+    TokenType::EOF,
+];
+
+impl TokenType {
+    pub fn from_name(name: &str) -> Option<TokenType> {
+        TOKEN_TYPES.iter().find(|kind| kind.to_string() == name).cloned()
+    }
+}
+
 impl Display for TokenType {
     fn fmt(&self, f: &mut Formatter) -> Result {
         write!(f, "{:?}", self)
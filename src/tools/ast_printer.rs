@@ -1,7 +1,9 @@
 use crate::visit_expr::ExprVisitor;
-use crate::ast_expr::{Unary, Binary, Grouping, Expr, Literal, Comma, Ternary, Variable, Assign};
+use crate::ast_expr::{Unary, Binary, Logical, Grouping, Expr, Literal, Comma, Ternary, Variable, Assign};
+use crate::ast_expr::{List, Map, Index, Slice, SetIndex, Compound, Call, Get, Format, Lambda, Match, Pattern};
 use crate::visit_stmt::StmtVisitor;
-use crate::ast_stmt::{Stmt, Expression, Print, Var, Block};
+use crate::ast_stmt::{Stmt, Expression, Print, Var, Block, If, While, ForIn, Break, Continue, Import};
+use crate::ast_stmt::{Throw, Try, Return};
 
 pub struct AstPrinter;
 
@@ -20,12 +22,24 @@ impl ExprVisitor<String> for AstPrinter {
         match e {
             Expr::UNARY(u) => self.visit_unary(&u),
             Expr::BINARY(b) => self.visit_binary(&b),
+            Expr::LOGICAL(l) => self.visit_logical(&l),
             Expr::ASSIGN(a) => self.visit_assign(&a),
             Expr::GROUPING(g) => self.visit_grouping(&g),
             Expr::LITERAL(l) => self.visit_literal(&l),
             Expr::COMMA(c) => self.visit_comma(&c),
             Expr::TERNARY(t) => self.visit_ternary(&t),
             Expr::VARIABLE(v) => self.visit_variable(&v),
+            Expr::LIST(l) => self.visit_list(&l),
+            Expr::MAP(m) => self.visit_map(&m),
+            Expr::INDEX(i) => self.visit_index(&i),
+            Expr::SLICE(s) => self.visit_slice(&s),
+            Expr::SETINDEX(s) => self.visit_set_index(&s),
+            Expr::COMPOUND(c) => self.visit_compound(&c),
+            Expr::CALL(c) => self.visit_call(&c),
+            Expr::GET(g) => self.visit_get(&g),
+            Expr::FORMAT(f) => self.visit_format(&f),
+            Expr::LAMBDA(l) => self.visit_lambda(&l),
+            Expr::MATCH(m) => self.visit_match(&m),
         } 
     }
 
@@ -59,6 +73,15 @@ impl ExprVisitor<String> for AstPrinter {
         )
     }
     
+    fn visit_logical(&mut self, l: &Logical) -> String {
+        return format!(
+            "({} {} {})",
+            l.op.get_lexeme(),
+            self.visit_expr(&l.lhs),
+            self.visit_expr(&l.rhs)
+        )
+    }
+
     fn visit_grouping(&mut self, g: &Grouping) -> String {
         return format!("(GROUP {})", self.visit_expr(&g.expr));
     }
@@ -69,9 +92,106 @@ impl ExprVisitor<String> for AstPrinter {
             Literal::NUMBER(value) => value.to_string(),
             Literal::STRING(value) => value.clone(),
             Literal::NIL => "nil".to_string(),
+            other => other.to_string(),
         };
     }
 
+    fn visit_list(&mut self, l: &List) -> String {
+        let elements: Vec<String> = l.elements.iter().map(|e| self.visit_expr(e)).collect();
+        format!("(LIST {})", elements.join(" "))
+    }
+
+    fn visit_map(&mut self, m: &Map) -> String {
+        let entries: Vec<String> = m.entries.iter()
+            .map(|(k, v)| format!("({} {})", self.visit_expr(k), self.visit_expr(v)))
+            .collect();
+        format!("(MAP {})", entries.join(" "))
+    }
+
+    fn visit_index(&mut self, i: &Index) -> String {
+        format!("(INDEX {} {})", self.visit_expr(&i.object), self.visit_expr(&i.index))
+    }
+
+    fn visit_slice(&mut self, s: &Slice) -> String {
+        let start = match &s.start {
+            Some(expr) => self.visit_expr(&expr),
+            None => "_".to_string(),
+        };
+
+        let end = match &s.end {
+            Some(expr) => self.visit_expr(&expr),
+            None => "_".to_string(),
+        };
+
+        format!("(SLICE {} {} {})", self.visit_expr(&s.object), start, end)
+    }
+
+    fn visit_set_index(&mut self, s: &SetIndex) -> String {
+        format!(
+            "(SETINDEX {} {} = {})",
+            self.visit_expr(&s.object),
+            self.visit_expr(&s.index),
+            self.visit_expr(&s.value)
+        )
+    }
+
+    fn visit_compound(&mut self, c: &Compound) -> String {
+        format!(
+            "({} {} {} {})",
+            if c.postfix { "POSTFIX" } else { "COMPOUND" },
+            c.operator.kind,
+            self.visit_expr(&c.target),
+            self.visit_expr(&c.value)
+        )
+    }
+
+    fn visit_call(&mut self, c: &Call) -> String {
+        let arguments: Vec<String> = c.arguments.iter().map(|e| self.visit_expr(e)).collect();
+        format!("(CALL {} {})", self.visit_expr(&c.callee), arguments.join(" "))
+    }
+
+    fn visit_get(&mut self, g: &Get) -> String {
+        format!("(GET {} {})", self.visit_expr(&g.object), g.name.value.clone().unwrap_or_default())
+    }
+
+    fn visit_format(&mut self, f: &Format) -> String {
+        let parts: Vec<String> = f.parts.iter().map(|e| self.visit_expr(e)).collect();
+        format!("(FORMAT {})", parts.join(" "))
+    }
+
+    fn visit_match(&mut self, m: &Match) -> String {
+        let arms: Vec<String> = m.arms.iter().map(|arm| {
+            let patterns: Vec<String> = arm.patterns.iter().map(|p| match p {
+                Pattern::LITERAL(lit) => lit.quoted(),
+                Pattern::TYPE(name) | Pattern::BINDING(name) => name.value.clone().unwrap_or_default(),
+                Pattern::WILDCARD => "_".to_string(),
+            }).collect();
+
+            match &arm.guard {
+                Some(guard) => format!("({} IF {} => {})", patterns.join(" | "), self.visit_expr(&guard), self.visit_expr(&arm.body)),
+                None => format!("({} => {})", patterns.join(" | "), self.visit_expr(&arm.body)),
+            }
+        }).collect();
+
+        format!("(MATCH {} {})", self.visit_expr(&m.subject), arms.join(" "))
+    }
+
+    fn visit_lambda(&mut self, l: &Lambda) -> String {
+        let params: Vec<String> = l.params.iter().zip(&l.annotations).map(|(p, annotation)| {
+            let name = p.value.clone().unwrap_or_default();
+            match annotation {
+                Some(t) => format!("{}: {}", name, t.value.clone().unwrap_or_default()),
+                None => name,
+            }
+        }).collect();
+        let body: Vec<String> = l.body.iter().map(|s| self.visit_stmt(s)).collect();
+
+        match &l.returns {
+            Some(t) => format!("(LAMBDA ({}) : {} {})", params.join(" "), t.value.clone().unwrap_or_default(), body.join(" ")),
+            None => format!("(LAMBDA ({}) {})", params.join(" "), body.join(" ")),
+        }
+    }
+
     fn visit_variable(&mut self, v: &Variable) -> String {
         
         let name = match v.name.value {
@@ -100,6 +220,15 @@ impl StmtVisitor<String> for AstPrinter {
             Stmt::PRINT(e) => self.visit_print(&e),
             Stmt::EXPRESSION(p) => self.visit_expression(&p),
             Stmt::VAR(v) => self.visit_var(&v),
+            Stmt::IF(i) => self.visit_if(&i),
+            Stmt::WHILE(w) => self.visit_while(&w),
+            Stmt::FORIN(f) => self.visit_for_in(&f),
+            Stmt::BREAK(b) => self.visit_break(&b),
+            Stmt::CONTINUE(c) => self.visit_continue(&c),
+            Stmt::IMPORT(i) => self.visit_import(&i),
+            Stmt::THROW(t) => self.visit_throw(&t),
+            Stmt::TRY(t) => self.visit_try(&t),
+            Stmt::RETURN(r) => self.visit_return(&r),
         }
     }
 
@@ -124,14 +253,99 @@ impl StmtVisitor<String> for AstPrinter {
             None => panic!("No name in token defined for variable"),
         };
 
+        let name = match &v.annotation {
+            Some(t) => format!("{}: {}", name, t.value.clone().unwrap_or_default()),
+            None => name,
+        };
+
+        let keyword = if v.constant { "CONST" } else { "VAR" };
+
         match &v.initializer {
-            Some(expr) => format!("(VAR {} {})", name, self.visit_expr(&expr)),
-            None => format!("(VAR {} NIL)", name)
+            Some(expr) => format!("({} {} {})", keyword, name, self.visit_expr(&expr)),
+            None => format!("({} {} NIL)", keyword, name)
         }
 
     }
 
     fn visit_block(&mut self, b: &Block) -> String {
-        unimplemented!()
+        let statements: Vec<String> = b.statements.iter().map(|s| self.visit_stmt(s)).collect();
+
+        format!("(BLOCK {})", statements.join(" "))
+    }
+
+    fn visit_if(&mut self, i: &If) -> String {
+        let cond = self.visit_expr(&i.cond);
+        let then_branch = self.visit_stmt(&i.then_branch);
+
+        match &i.else_branch {
+            Some(stmt) => format!("(IF {} {} ELSE {})", cond, then_branch, self.visit_stmt(&stmt)),
+            None => format!("(IF {} {})", cond, then_branch),
+        }
+    }
+
+    fn visit_while(&mut self, w: &While) -> String {
+        let cond = self.visit_expr(&w.cond);
+        let body = self.visit_stmt(&w.body);
+
+        match &w.increment {
+            Some(expr) => format!("(WHILE {} {} STEP {})", cond, body, self.visit_expr(&expr)),
+            None => format!("(WHILE {} {})", cond, body),
+        }
+    }
+
+    fn visit_for_in(&mut self, f: &ForIn) -> String {
+        let name = match f.name.value {
+            Some(ref n) => n.clone(),
+            None => panic!("No name in token defined for variable"),
+        };
+
+        format!("(FOR {} IN {} {})", name, self.visit_expr(&f.iterable), self.visit_stmt(&f.body))
+    }
+
+    fn visit_import(&mut self, i: &Import) -> String {
+        let path = i.path.value.clone().unwrap_or_default();
+
+        match &i.alias {
+            Some(alias) => format!("(IMPORT \"{}\" AS {})", path, alias.value.clone().unwrap_or_default()),
+            None => format!("(IMPORT \"{}\")", path),
+        }
+    }
+
+    fn visit_return(&mut self, r: &Return) -> String {
+        match &r.value {
+            Some(expr) => format!("(RETURN {})", self.visit_expr(&expr)),
+            None => "(RETURN)".to_string(),
+        }
+    }
+
+    fn visit_throw(&mut self, t: &Throw) -> String {
+        format!("(THROW {})", self.visit_expr(&t.value))
+    }
+
+    fn visit_try(&mut self, t: &Try) -> String {
+        let mut printed = format!("(TRY {}", self.visit_block(&Block::new(t.body.clone(), t.keyword.line)));
+
+        if let (Some(name), Some(body)) = (&t.catch_name, &t.catch_body) {
+            printed.push_str(&format!(
+                " CATCH {} {}",
+                name.value.clone().unwrap_or_default(),
+                self.visit_block(&Block::new(body.clone(), name.line)),
+            ));
+        }
+
+        if let Some(body) = &t.finally_body {
+            printed.push_str(&format!(" FINALLY {}", self.visit_block(&Block::new(body.clone(), t.keyword.line))));
+        }
+
+        printed.push(')');
+        printed
+    }
+
+    fn visit_break(&mut self, _b: &Break) -> String {
+        "(BREAK)".to_string()
+    }
+
+    fn visit_continue(&mut self, _c: &Continue) -> String {
+        "(CONTINUE)".to_string()
     }
 }
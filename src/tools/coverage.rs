@@ -0,0 +1,435 @@
+/* -------------------------------------------------------------------------------------
+*
+*   Line and branch coverage for Lox scripts
+*
+*   Before running, the program is walked to find the executable lines (where the
+*   statements begin) and the branch points: the ternaries and the ifs, with their then
+*   and else sides, and the `and`/`or`, whose sides are the right operand evaluated and
+*   the left one deciding alone. During the run the instrumentation hooks count the hits
+*   of each line and of each side of a branch.
+*
+*   At the end of the run it writes:
+*       - An lcov `.info` file (DA/BRDA records), readable by genhtml and similar tools;
+*       - An annotated copy of the source with the hits of each line, `#####` marking
+*         the executable lines that never ran, followed by the branch counts.
+*
+*   Branch points are identified by the address of their node, which is stable while
+*   the statements of the program are alive.
+*
+* ------------------------------------------------------------------------------------- */
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::visit_expr::ExprVisitor;
+use crate::visit_stmt::StmtVisitor;
+use crate::ast_expr::{Unary, Binary, Logical, Grouping, Expr, Literal, Comma, Ternary, Variable, Assign};
+use crate::ast_expr::{List, Map, Index, Slice, SetIndex, Compound, Call, Get, Format, Lambda, Match};
+use crate::ast_stmt::{Stmt, Expression, Print, Var, Block, If, While, ForIn, Break, Continue, Import};
+use crate::ast_stmt::{Throw, Try, Return};
+use crate::environment::Environment;
+use crate::instrument::Instrument;
+
+struct Branch {
+    line: usize,
+    evaluated: bool,
+    taken: [u64; 2],
+    // Names of the two sides in the annotated report
+    sides: [&'static str; 2],
+}
+
+const CONDITION_SIDES: [&str; 2] = ["then", "else"];
+const LOGICAL_SIDES: [&str; 2] = ["right", "short-circuit"];
+
+// Writes one of the reports
+type Writer = fn(&Coverage, &mut dyn Write) -> io::Result<()>;
+
+pub struct Coverage {
+    source_path: PathBuf,
+    source: String,
+    lcov_path: PathBuf,
+    report_path: PathBuf,
+    lines: BTreeMap<usize, u64>,
+    branches: Vec<Branch>,
+    index: HashMap<*const (), usize>,
+}
+
+impl Coverage {
+    pub fn new(source_path: PathBuf, source: String, lcov_path: PathBuf, report_path: PathBuf) -> Self {
+        Coverage {
+            source_path,
+            source,
+            lcov_path,
+            report_path,
+            lines: BTreeMap::new(),
+            branches: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    fn add_branch(&mut self, node: *const (), line: usize, sides: [&'static str; 2]) {
+        self.index.insert(node, self.branches.len());
+        self.branches.push(Branch {
+            line,
+            evaluated: false,
+            taken: [0, 0],
+            sides,
+        });
+    }
+
+    fn hit_branch(&mut self, node: *const (), then_taken: bool) {
+        if let Some(idx) = self.index.get(&node) {
+            let branch = &mut self.branches[*idx];
+            branch.evaluated = true;
+            branch.taken[if then_taken { 0 } else { 1 }] += 1;
+        }
+    }
+
+    fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|hits| **hits > 0).count()
+    }
+
+    fn branches_hit(&self) -> usize {
+        self.branches.iter().map(|b| b.taken.iter().filter(|t| **t > 0).count()).sum()
+    }
+
+    fn write_lcov(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "TN:")?;
+        writeln!(out, "SF:{}", self.source_path.display())?;
+
+        for (line, hits) in &self.lines {
+            writeln!(out, "DA:{},{}", line, hits)?;
+        }
+
+        for (block, branch) in self.branches.iter().enumerate() {
+            for (side, taken) in branch.taken.iter().enumerate() {
+                if branch.evaluated {
+                    writeln!(out, "BRDA:{},{},{},{}", branch.line, block, side, taken)?;
+                } else {
+                    writeln!(out, "BRDA:{},{},{},-", branch.line, block, side)?;
+                }
+            }
+        }
+
+        writeln!(out, "BRF:{}", self.branches.len() * 2)?;
+        writeln!(out, "BRH:{}", self.branches_hit())?;
+        writeln!(out, "LF:{}", self.lines.len())?;
+        writeln!(out, "LH:{}", self.lines_hit())?;
+        writeln!(out, "end_of_record")
+    }
+
+    fn write_report(&self, out: &mut dyn Write) -> io::Result<()> {
+        let percent = |hit: usize, found: usize| {
+            if found == 0 { 100.0 } else { hit as f64 * 100.0 / found as f64 }
+        };
+
+        writeln!(out, "Coverage for {}", self.source_path.display())?;
+        writeln!(
+            out,
+            "Lines: {}/{} ({:.1}%)  Branches: {}/{} ({:.1}%)",
+            self.lines_hit(),
+            self.lines.len(),
+            percent(self.lines_hit(), self.lines.len()),
+            self.branches_hit(),
+            self.branches.len() * 2,
+            percent(self.branches_hit(), self.branches.len() * 2),
+        )?;
+        writeln!(out)?;
+
+        for (idx, text) in self.source.lines().enumerate() {
+            let line = idx + 1;
+            let hits = match self.lines.get(&line) {
+                Some(0) => "#####".to_string(),
+                Some(hits) => hits.to_string(),
+                None => "-".to_string(),
+            };
+
+            writeln!(out, "{:>6} | {:>6} | {}", line, hits, text)?;
+
+            for (block, branch) in self.branches.iter().enumerate().filter(|(_, b)| b.line == line) {
+                writeln!(
+                    out,
+                    "{:>6} | {:>6} |   branch {}: {} {}, {} {}",
+                    "", "", block, branch.sides[0], branch.taken[0], branch.sides[1], branch.taken[1]
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Instrument for Coverage {
+    fn before_program(&mut self, statements: &[Stmt]) {
+        for stmt in statements {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn before_stmt(&mut self, stmt: &Stmt, _env: &Environment) {
+        *self.lines.entry(stmt.line()).or_insert(0) += 1;
+    }
+
+    fn on_ternary(&mut self, t: &Ternary, then_taken: bool) {
+        self.hit_branch(t as *const Ternary as *const (), then_taken);
+    }
+
+    fn on_if(&mut self, i: &If, then_taken: bool) {
+        self.hit_branch(i as *const If as *const (), then_taken);
+    }
+
+    fn on_logical(&mut self, l: &Logical, rhs_taken: bool) {
+        self.hit_branch(l as *const Logical as *const (), rhs_taken);
+    }
+
+    fn finish(&mut self) {
+        let outputs: [(&PathBuf, Writer); 2] = [
+            (&self.lcov_path, Coverage::write_lcov),
+            (&self.report_path, Coverage::write_report),
+        ];
+
+        for (path, write) in outputs {
+            let result = File::create(path).and_then(|mut file| write(self, &mut file));
+
+            match result {
+                Ok(_) => eprintln!("[COVERAGE] Written to {}", path.display()),
+                Err(err) => eprintln!("[COVERAGE] {err} | {}", path.display()),
+            }
+        }
+    }
+}
+
+// Walks the program registering the executable lines and the branch points
+
+impl ExprVisitor<()> for Coverage {
+    fn visit_expr(&mut self, e: &Expr) {
+        match e {
+            Expr::UNARY(u) => self.visit_unary(u),
+            Expr::BINARY(b) => self.visit_binary(b),
+            Expr::LOGICAL(l) => self.visit_logical(l),
+            Expr::ASSIGN(a) => self.visit_assign(a),
+            Expr::GROUPING(g) => self.visit_grouping(g),
+            Expr::LITERAL(l) => self.visit_literal(l),
+            Expr::COMMA(c) => self.visit_comma(c),
+            Expr::TERNARY(t) => self.visit_ternary(t),
+            Expr::VARIABLE(v) => self.visit_variable(v),
+            Expr::LIST(l) => self.visit_list(l),
+            Expr::MAP(m) => self.visit_map(m),
+            Expr::INDEX(i) => self.visit_index(i),
+            Expr::SLICE(s) => self.visit_slice(s),
+            Expr::SETINDEX(s) => self.visit_set_index(s),
+            Expr::COMPOUND(c) => self.visit_compound(c),
+            Expr::CALL(c) => self.visit_call(c),
+            Expr::GET(g) => self.visit_get(g),
+            Expr::FORMAT(f) => self.visit_format(f),
+            Expr::LAMBDA(l) => self.visit_lambda(l),
+            Expr::MATCH(m) => self.visit_match(m),
+        }
+    }
+
+    fn visit_comma(&mut self, c: &Comma) {
+        self.visit_expr(&c.lhs);
+        self.visit_expr(&c.rhs);
+    }
+
+    fn visit_ternary(&mut self, t: &Ternary) {
+        self.add_branch(t as *const Ternary as *const (), t.line, CONDITION_SIDES);
+
+        self.visit_expr(&t.cond);
+        self.visit_expr(&t.then_expr);
+        self.visit_expr(&t.else_expr);
+    }
+
+    fn visit_assign(&mut self, a: &Assign) {
+        self.visit_expr(&a.value);
+    }
+
+    fn visit_literal(&mut self, _l: &Literal) {}
+
+    fn visit_unary(&mut self, u: &Unary) {
+        self.visit_expr(&u.rhs);
+    }
+
+    fn visit_binary(&mut self, b: &Binary) {
+        self.visit_expr(&b.lhs);
+        self.visit_expr(&b.rhs);
+    }
+
+    fn visit_logical(&mut self, l: &Logical) {
+        self.add_branch(l as *const Logical as *const (), l.op.line, LOGICAL_SIDES);
+
+        self.visit_expr(&l.lhs);
+        self.visit_expr(&l.rhs);
+    }
+
+    fn visit_grouping(&mut self, g: &Grouping) {
+        self.visit_expr(&g.expr);
+    }
+
+    fn visit_variable(&mut self, _v: &Variable) {}
+
+    fn visit_list(&mut self, l: &List) {
+        for expr in &l.elements {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_map(&mut self, m: &Map) {
+        for (key, value) in &m.entries {
+            self.visit_expr(key);
+            self.visit_expr(value);
+        }
+    }
+
+    fn visit_index(&mut self, i: &Index) {
+        self.visit_expr(&i.object);
+        self.visit_expr(&i.index);
+    }
+
+    fn visit_slice(&mut self, s: &Slice) {
+        self.visit_expr(&s.object);
+        if let Some(expr) = &s.start {
+            self.visit_expr(expr);
+        }
+        if let Some(expr) = &s.end {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_set_index(&mut self, s: &SetIndex) {
+        self.visit_expr(&s.object);
+        self.visit_expr(&s.index);
+        self.visit_expr(&s.value);
+    }
+
+    fn visit_compound(&mut self, c: &Compound) {
+        self.visit_expr(&c.target);
+        self.visit_expr(&c.value);
+    }
+
+    fn visit_call(&mut self, c: &Call) {
+        self.visit_expr(&c.callee);
+        for expr in &c.arguments {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_get(&mut self, g: &Get) {
+        self.visit_expr(&g.object);
+    }
+
+    fn visit_format(&mut self, f: &Format) {
+        for expr in &f.parts {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_match(&mut self, m: &Match) {
+        self.visit_expr(&m.subject);
+        for arm in &m.arms {
+            if let Some(guard) = &arm.guard {
+                self.visit_expr(guard);
+            }
+            self.visit_expr(&arm.body);
+        }
+    }
+
+    fn visit_lambda(&mut self, l: &Lambda) {
+        for stmt in l.body.iter() {
+            self.visit_stmt(stmt);
+        }
+    }
+}
+
+impl StmtVisitor<()> for Coverage {
+    fn visit_stmt(&mut self, s: &Stmt) {
+        self.lines.entry(s.line()).or_insert(0);
+
+        match s {
+            Stmt::BLOCK(b) => self.visit_block(b),
+            Stmt::EXPRESSION(e) => self.visit_expression(e),
+            Stmt::PRINT(p) => self.visit_print(p),
+            Stmt::VAR(v) => self.visit_var(v),
+            Stmt::IF(i) => self.visit_if(i),
+            Stmt::WHILE(w) => self.visit_while(w),
+            Stmt::FORIN(f) => self.visit_for_in(f),
+            Stmt::BREAK(b) => self.visit_break(b),
+            Stmt::CONTINUE(c) => self.visit_continue(c),
+            Stmt::IMPORT(i) => self.visit_import(i),
+            Stmt::THROW(t) => self.visit_throw(t),
+            Stmt::TRY(t) => self.visit_try(t),
+            Stmt::RETURN(r) => self.visit_return(r),
+        }
+    }
+
+    fn visit_expression(&mut self, e: &Expression) {
+        self.visit_expr(&e.expr);
+    }
+
+    fn visit_print(&mut self, p: &Print) {
+        self.visit_expr(&p.expr);
+    }
+
+    fn visit_var(&mut self, v: &Var) {
+        if let Some(expr) = &v.initializer {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_block(&mut self, b: &Block) {
+        for stmt in &b.statements {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn visit_if(&mut self, i: &If) {
+        self.add_branch(i as *const If as *const (), i.line, CONDITION_SIDES);
+
+        self.visit_expr(&i.cond);
+        self.visit_stmt(&i.then_branch);
+        if let Some(stmt) = &i.else_branch {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn visit_while(&mut self, w: &While) {
+        self.visit_expr(&w.cond);
+        self.visit_stmt(&w.body);
+        if let Some(expr) = &w.increment {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_for_in(&mut self, f: &ForIn) {
+        self.visit_expr(&f.iterable);
+        self.visit_stmt(&f.body);
+    }
+
+    fn visit_break(&mut self, _b: &Break) {}
+
+    fn visit_continue(&mut self, _c: &Continue) {}
+
+    fn visit_import(&mut self, _i: &Import) {}
+
+    fn visit_return(&mut self, r: &Return) {
+        if let Some(expr) = &r.value {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_throw(&mut self, t: &Throw) {
+        self.visit_expr(&t.value);
+    }
+
+    fn visit_try(&mut self, t: &Try) {
+        let bodies = [Some(&t.body), t.catch_body.as_ref(), t.finally_body.as_ref()];
+
+        for body in bodies.into_iter().flatten() {
+            for stmt in body {
+                self.visit_stmt(stmt);
+            }
+        }
+    }
+}
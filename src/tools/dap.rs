@@ -0,0 +1,560 @@
+/* -------------------------------------------------------------------------------------
+*
+*   Debug Adapter Protocol server for Lox
+*
+*   Speaks DAP over stdin/stdout so editors can launch and debug Lox scripts. Messages
+*   are framed by a `Content-Length` header followed by a JSON body.
+*
+*   The adapter runs in a single thread: the session waits for `configurationDone`,
+*   then runs the program with a hook registered in the Interpreter. When the hook
+*   decides to stop (entry, breakpoint or step) it keeps answering requests until the
+*   client resumes the execution.
+*
+*   Some Considerations:
+*       - There is only one thread ("main"). Its stack has a frame for the program and
+*         one for each function or native being called, named after the callee;
+*       - Only the innermost frame has its local variables, the callers' scopes are put
+*         aside while a function runs, so the other frames only show the globals;
+*       - Program output is forwarded to the client as `output` events;
+*       - `stepIn` stops at the next statement, `next` at the next one that is not in a
+*         function it calls, and `stepOut` at the next one in a caller.
+*
+* ------------------------------------------------------------------------------------- */
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::io::{self, BufRead, Read, Write};
+use std::path::PathBuf;
+use std::process;
+use std::rc::Rc;
+
+use serde_json::{json, Value};
+
+use crate::ast_expr::Literal;
+use crate::ast_stmt::Stmt;
+use crate::environment::Environment;
+use crate::instrument::Instrument;
+use crate::interpreter::Interpreter;
+use crate::capabilities::Capabilities;
+use crate::parser::Parser;
+use crate::scanner::Scanner;
+use crate::type_checker::TypeChecker;
+use crate::utils;
+
+const THREAD_ID: i64 = 1;
+const LOCALS_REF: i64 = 1;
+const GLOBALS_REF: i64 = 2;
+
+pub struct Connection {
+    reader: Box<dyn BufRead>,
+    writer: Box<dyn Write>,
+    seq: i64,
+}
+
+impl Connection {
+    pub fn new(reader: Box<dyn BufRead>, writer: Box<dyn Write>) -> Self {
+        Connection {
+            reader,
+            writer,
+            seq: 0,
+        }
+    }
+
+    // Reads the next framed message, None when the client closed the stream
+    fn read_message(&mut self) -> Option<Value> {
+        let mut length: Option<usize> = None;
+
+        loop {
+            let mut header = String::new();
+            match self.reader.read_line(&mut header) {
+                Ok(0) | Err(_) => return None,
+                Ok(_) => (),
+            }
+
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+
+        let mut body = vec![0; length?];
+        if self.reader.read_exact(&mut body).is_err() {
+            return None;
+        }
+
+        serde_json::from_slice(&body).ok()
+    }
+
+    fn send(&mut self, mut message: Value) {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+
+        let body = message.to_string();
+        let _ = write!(self.writer, "Content-Length: {}\r\n\r\n{}", body.len(), body);
+        let _ = self.writer.flush();
+    }
+
+    fn respond(&mut self, request: &Value, body: Value) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": true,
+            "command": request["command"],
+            "body": body,
+        }));
+    }
+
+    fn respond_error(&mut self, request: &Value, message: &str) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": false,
+            "command": request["command"],
+            "message": message,
+        }));
+    }
+
+    fn event(&mut self, event: &str, body: Value) {
+        self.send(json!({
+            "type": "event",
+            "event": event,
+            "body": body,
+        }));
+    }
+}
+
+// A function being called, at the line it is running
+struct Frame {
+    name: String,
+    line: usize,
+}
+
+struct Session {
+    conn: Connection,
+    program: Option<PathBuf>,
+    breakpoints: HashSet<usize>,
+    stop_on_entry: bool,
+    // When stepping, the deepest stack where the next statement stops
+    step_depth: Option<usize>,
+    // The program first, the innermost call last
+    frames: Vec<Frame>,
+}
+
+impl Session {
+    fn new(conn: Connection) -> Self {
+        Session {
+            conn,
+            program: None,
+            breakpoints: HashSet::new(),
+            stop_on_entry: false,
+            step_depth: None,
+            frames: vec![Frame { name: "main".to_string(), line: 0 }],
+        }
+    }
+
+    // Frames are numbered from the program up, the innermost one comes first
+    fn stack_trace(&self) -> Value {
+        let source = self.source();
+        let frames: Vec<Value> = self.frames.iter().enumerate().rev().map(|(idx, frame)| json!({
+            "id": idx + 1,
+            "name": frame.name,
+            "line": frame.line,
+            "column": 1,
+            "source": source,
+        })).collect();
+
+        json!({
+            "stackFrames": frames,
+            "totalFrames": self.frames.len(),
+        })
+    }
+
+    fn scopes(&self, request: &Value) -> Value {
+        let globals = json!({
+            "name": "Globals",
+            "variablesReference": GLOBALS_REF,
+            "expensive": false,
+        });
+
+        match request["arguments"]["frameId"].as_u64() {
+            Some(id) if id as usize == self.frames.len() => json!({
+                "scopes": [{
+                    "name": "Locals",
+                    "variablesReference": LOCALS_REF,
+                    "expensive": false,
+                }, globals],
+            }),
+            _ => json!({ "scopes": [globals] }),
+        }
+    }
+
+    fn output(&mut self, category: &str, text: &str) {
+        self.conn.event("output", json!({
+            "category": category,
+            "output": text,
+        }));
+    }
+
+    fn source(&self) -> Value {
+        match &self.program {
+            Some(path) => json!({
+                "name": path.file_name().map(|n| n.to_string_lossy().to_string()),
+                "path": path.display().to_string(),
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn set_breakpoints(&mut self, request: &Value) {
+        let lines: Vec<usize> = match request["arguments"]["breakpoints"].as_array() {
+            Some(bps) => bps.iter().filter_map(|bp| bp["line"].as_u64()).map(|l| l as usize).collect(),
+            None => Vec::new(),
+        };
+
+        self.breakpoints = lines.iter().cloned().collect();
+
+        let source = self.source();
+        let verified: Vec<Value> = lines.iter().map(|line| json!({
+            "verified": true,
+            "line": line,
+            "source": source,
+        })).collect();
+
+        self.conn.respond(request, json!({ "breakpoints": verified }));
+    }
+
+    fn disconnect(&mut self, request: &Value) -> ! {
+        self.conn.respond(request, json!({}));
+        process::exit(0);
+    }
+
+    // Answers requests until the client resumes the execution
+    fn paused(&mut self, reason: &str, env: &Environment) {
+        self.conn.event("stopped", json!({
+            "reason": reason,
+            "threadId": THREAD_ID,
+            "allThreadsStopped": true,
+        }));
+
+        loop {
+            let request = match self.conn.read_message() {
+                Some(req) => req,
+                None => process::exit(0),
+            };
+
+            match request["command"].as_str().unwrap_or("") {
+                "threads" => self.conn.respond(&request, threads()),
+                "stackTrace" => {
+                    let frames = self.stack_trace();
+                    self.conn.respond(&request, frames);
+                },
+                "scopes" => {
+                    let scopes = self.scopes(&request);
+                    self.conn.respond(&request, scopes);
+                },
+                "variables" => {
+                    let vars = match request["arguments"]["variablesReference"].as_i64() {
+                        Some(LOCALS_REF) => env.enclosed_variables(),
+                        Some(GLOBALS_REF) => env.globals(),
+                        _ => Vec::new(),
+                    };
+                    let vars: Vec<Value> = vars.iter()
+                        .filter(|(_, value)| !matches!(value, Literal::NATIVE(_)))
+                        .map(|(name, value)| json!({
+                            "name": name,
+                            "value": display_value(value),
+                            "type": type_name(value),
+                            "variablesReference": 0,
+                        })).collect();
+                    self.conn.respond(&request, json!({ "variables": vars }));
+                },
+                "setBreakpoints" => self.set_breakpoints(&request),
+                "continue" => {
+                    self.step_depth = None;
+                    self.conn.respond(&request, json!({ "allThreadsContinued": true }));
+                    return;
+                },
+                "stepIn" | "next" | "stepOut" => {
+                    let depth = self.frames.len();
+                    self.step_depth = match request["command"].as_str() {
+                        Some("stepIn") => Some(usize::MAX),
+                        Some("next") => Some(depth),
+                        _ => Some(depth - 1),
+                    };
+                    self.conn.respond(&request, json!({}));
+                    return;
+                },
+                "disconnect" => self.disconnect(&request),
+                _ => self.conn.respond_error(&request, "Unsupported request while paused"),
+            }
+        }
+    }
+}
+
+fn threads() -> Value {
+    json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })
+}
+
+fn display_value(value: &Literal) -> String {
+    match value {
+        Literal::STRING(val) => format!("\"{}\"", val),
+        Literal::NIL => "nil".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn type_name(value: &Literal) -> &'static str {
+    match value {
+        Literal::BOOL(_) => "bool",
+        Literal::NUMBER(_) => "number",
+        Literal::INTEGER(_) => "int",
+        Literal::STRING(_) => "string",
+        Literal::LIST(_) => "list",
+        Literal::MAP(_) => "map",
+        Literal::NATIVE(_) => "native",
+        Literal::FUNCTION(_) => "function",
+        Literal::MODULE(_) => "module",
+        Literal::NIL => "nil",
+    }
+}
+
+// Interpreter hook that decides when the execution must pause
+struct DapHook {
+    session: Rc<RefCell<Session>>,
+    last_line: usize,
+    // The last statement started is still running, nothing else ran since
+    entering: bool,
+}
+
+impl Instrument for DapHook {
+    // A breakpoint stops each time a statement of its line starts, except the first one
+    // nested in the statement that just started there (the block of `while (c) { ... }`),
+    // so a line isn't stopped at twice in a row, but a one-line loop stops every time
+    fn before_stmt(&mut self, stmt: &Stmt, env: &Environment) {
+        let mut session = self.session.borrow_mut();
+        let line = stmt.line();
+        if let Some(frame) = session.frames.last_mut() {
+            frame.line = line;
+        }
+
+        let continues = self.entering && line == self.last_line;
+        let depth = session.frames.len();
+
+        let reason = if session.stop_on_entry {
+            session.stop_on_entry = false;
+            Some("entry")
+        } else if session.step_depth.is_some_and(|max| depth <= max) {
+            Some("step")
+        } else if !continues && session.breakpoints.contains(&line) {
+            Some("breakpoint")
+        } else {
+            None
+        };
+
+        self.last_line = line;
+        self.entering = true;
+
+        if let Some(reason) = reason {
+            session.paused(reason, env);
+        }
+    }
+
+    fn after_stmt(&mut self, _stmt: &Stmt, _env: &Environment) {
+        self.entering = false;
+    }
+
+    // The body of a function is a new frame, even when it is on the line of the call
+    fn before_call(&mut self, line: usize, callee: &str) {
+        self.entering = false;
+        self.session.borrow_mut().frames.push(Frame { name: callee.to_string(), line });
+    }
+
+    fn after_call(&mut self, _line: usize, _callee: &str) {
+        self.session.borrow_mut().frames.pop();
+    }
+}
+
+// Forwards the program output to the client, one `output` event per line
+struct DapOutput {
+    session: Rc<RefCell<Session>>,
+    buffer: Vec<u8>,
+}
+
+impl Write for DapOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            self.session.borrow_mut().output("stdout", &String::from_utf8_lossy(&line));
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            let rest: Vec<u8> = self.buffer.drain(..).collect();
+            self.session.borrow_mut().output("stdout", &String::from_utf8_lossy(&rest));
+        }
+        Ok(())
+    }
+}
+
+pub struct DebugAdapter {
+    session: Rc<RefCell<Session>>,
+}
+
+impl DebugAdapter {
+    pub fn new(conn: Connection) -> Self {
+        DebugAdapter {
+            session: Rc::new(RefCell::new(Session::new(conn))),
+        }
+    }
+
+    pub fn stdio() -> Self {
+        DebugAdapter::new(
+            Connection::new(
+                Box::new(io::BufReader::new(io::stdin())),
+                Box::new(io::stdout()),
+            )
+        )
+    }
+
+    pub fn run(&mut self) {
+        if !self.configure() {
+            return;
+        }
+
+        let exit_code = self.launch();
+
+        {
+            let mut session = self.session.borrow_mut();
+            session.conn.event("exited", json!({ "exitCode": exit_code }));
+            session.conn.event("terminated", json!({}));
+        }
+
+        // Keep answering until the client disconnects
+        loop {
+            let mut session = self.session.borrow_mut();
+            let request = match session.conn.read_message() {
+                Some(req) => req,
+                None => return,
+            };
+
+            match request["command"].as_str().unwrap_or("") {
+                "threads" => session.conn.respond(&request, threads()),
+                "disconnect" => {
+                    session.conn.respond(&request, json!({}));
+                    return;
+                },
+                _ => session.conn.respond_error(&request, "Program has terminated"),
+            }
+        }
+    }
+
+    // Handles the requests before the execution starts, false if the client left
+    fn configure(&mut self) -> bool {
+        let mut session = self.session.borrow_mut();
+
+        loop {
+            let request = match session.conn.read_message() {
+                Some(req) => req,
+                None => return false,
+            };
+
+            match request["command"].as_str().unwrap_or("") {
+                "initialize" => {
+                    session.conn.respond(&request, json!({
+                        "supportsConfigurationDoneRequest": true,
+                    }));
+                    session.conn.event("initialized", json!({}));
+                },
+                "launch" => {
+                    let args = &request["arguments"];
+                    match args["program"].as_str() {
+                        Some(program) => {
+                            session.program = Some(PathBuf::from(program));
+                            session.stop_on_entry = args["stopOnEntry"].as_bool().unwrap_or(false);
+                            session.conn.respond(&request, json!({}));
+                        },
+                        None => session.conn.respond_error(&request, "Missing 'program' to launch"),
+                    }
+                },
+                "setBreakpoints" => session.set_breakpoints(&request),
+                "setExceptionBreakpoints" => session.conn.respond(&request, json!({ "breakpoints": [] })),
+                "threads" => session.conn.respond(&request, threads()),
+                "configurationDone" => {
+                    session.conn.respond(&request, json!({}));
+                    if session.program.is_some() {
+                        return true;
+                    }
+                },
+                "disconnect" => {
+                    session.conn.respond(&request, json!({}));
+                    return false;
+                },
+                _ => session.conn.respond_error(&request, "Unsupported request"),
+            }
+        }
+    }
+
+    // Runs the launched program, returning its exit code
+    fn launch(&mut self) -> i32 {
+        let program = self.session.borrow().program.clone().unwrap();
+
+        let mut scanner = Scanner::new(utils::read_file(&program));
+        let mut parser = Parser::new(scanner.scan_tokens().clone());
+        let statements: Vec<Stmt> = parser.parse();
+
+        let mut type_checker = TypeChecker::new();
+        type_checker.check(&statements);
+
+        if !scanner.errors.is_empty() || !parser.errors.is_empty() || !type_checker.errors.is_empty() {
+            let mut session = self.session.borrow_mut();
+            for err in &scanner.errors {
+                session.output("stderr", &format!("{}\n", err));
+            }
+            for err in &parser.errors {
+                session.output("stderr", &format!("{}\n", err));
+            }
+            for err in &type_checker.errors {
+                session.output("stderr", &format!("{}\n", err));
+            }
+            return exitcode::DATAERR;
+        }
+
+        let output = DapOutput {
+            session: self.session.clone(),
+            buffer: Vec::new(),
+        };
+
+        // Scripts are launched by the user from the editor, like in the prompt
+        let mut interpreter = Interpreter::with_output(Box::new(output));
+        interpreter.set_capabilities(Capabilities::all());
+        interpreter.set_script(&program);
+        interpreter.add_hook(Box::new(DapHook {
+            session: self.session.clone(),
+            last_line: 0,
+            entering: false,
+        }));
+
+        interpreter.start(&statements);
+
+        let mut exit_code = exitcode::OK;
+        for stmt in &statements {
+            if let Err(err) = interpreter.interpret(stmt) {
+                self.session.borrow_mut().output("stderr", &format!("{}\n", err));
+                exit_code = exitcode::SOFTWARE;
+                break;
+            }
+        }
+
+        interpreter.finish();
+        exit_code
+    }
+}
@@ -0,0 +1,48 @@
+/* -------------------------------------------------------------------------------------
+*
+*   Instrumentation hooks for the Interpreter
+*
+*   Tools that need to observe or drive the execution (debuggers, profilers, coverage)
+*   implement this trait and are registered in the Interpreter. The interpreter calls
+*   the hooks around every statement it executes and every function it calls, in
+*   registration order.
+*
+*   All the methods have empty default implementations, so each tool only implements
+*   the events it cares about.
+*
+* ------------------------------------------------------------------------------------- */
+
+use crate::ast_expr::{Ternary, Logical};
+use crate::ast_stmt::{Stmt, If};
+use crate::environment::Environment;
+
+pub trait Instrument {
+    // Called with the whole program before any statement executes.
+    fn before_program(&mut self, _statements: &[Stmt]) {}
+
+    // Called before the statement executes. Blocking here pauses the interpreter.
+    fn before_stmt(&mut self, _stmt: &Stmt, _env: &Environment) {}
+
+    // Called after the statement executes, successfully or not.
+    fn after_stmt(&mut self, _stmt: &Stmt, _env: &Environment) {}
+
+    // Called before a function or a native runs, with the line of the call and the name
+    // the callee was called by (`lambda` when it has none).
+    fn before_call(&mut self, _line: usize, _callee: &str) {}
+
+    // Called after the function or the native returns, successfully or not.
+    fn after_call(&mut self, _line: usize, _callee: &str) {}
+
+    // Called after the condition of a ternary is evaluated, with the branch taken.
+    fn on_ternary(&mut self, _t: &Ternary, _then_taken: bool) {}
+
+    // Called after the condition of an if statement is evaluated, with the branch taken.
+    fn on_if(&mut self, _i: &If, _then_taken: bool) {}
+
+    // Called after the left operand of `and`/`or` is evaluated, with whether the right one
+    // runs too or the left one decides.
+    fn on_logical(&mut self, _l: &Logical, _rhs_taken: bool) {}
+
+    // Called once when the program finishes running.
+    fn finish(&mut self) {}
+}
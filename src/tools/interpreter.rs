@@ -12,79 +12,1132 @@
 *   Some Considerations:
 *       - Bools: Only False and NIL should evaluate to false, all other combinations are true;
 *       - Sum on String: Concatenates the values, convert number to string;
+*       - Strict mode (`InterpreterOptions`): conditions and the operand of `!` must be
+*         booleans, and `+` only joins two strings, without converting numbers;
+*       - Limits (`InterpreterOptions`): each statement and expression is a step of the
+*         sandbox, calls nest up to the maximum depth and the new values are counted;
+*       - Capabilities: an interpreter is created without any, so the natives reaching
+*         outside of it (files, environment, clock, random) and `import` fail until granted;
+*       - Deterministic mode: `random()` follows a seed and `clock()` is virtual, advancing
+*         with the steps of the sandbox, so the runs of a script are reproducible;
+*       - Imports: a module runs in its own global scope, with only the natives, and
+*         the hooks are paused meanwhile, as its lines belong to another file;
+*       - Loop control: `break` and `continue` unwind as errors up to the innermost loop,
+*         where they are caught. Blocks always pop their scope on the way out;
+*       - Exceptions: `throw` unwinds as an error too. A `catch` receives the thrown value,
+*         or a map with the `message` and `line` of a runtime error. Loop control goes
+*         through `catch`, and `finally` runs on every way out of the `try`.
 *   
 *
 * ------------------------------------------------------------------------------------- */
 
 
+use std::cell::RefCell;
+use std::mem;
+use std::io::{self, Write};
+use std::path::Path;
+use std::rc::Rc;
+
 use crate::environment::Environment;
+use crate::instrument::Instrument;
+use crate::sandbox::{Sandbox, Limits, CancelToken};
+use crate::capabilities::{Capabilities, Capability};
+use crate::random::Random;
 use crate::visit_expr::ExprVisitor;
 use crate::visit_stmt::StmtVisitor;
-use crate::ast_expr::{Unary, Binary, Grouping, Expr, Literal, Comma, Ternary, Variable, Assign};
-use crate::ast_stmt::{Expression, Stmt, Print, Var, Block};
+use crate::ast_expr::{Unary, Binary, Logical, Grouping, Expr, Literal, Comma, Ternary, Variable, Assign};
+use crate::ast_expr::{List, Map, Index, Slice, SetIndex, Compound, Call, Get, Format, Lambda, Match, MatchArm, Pattern};
+use crate::natives;
+use crate::map::LoxMap;
+use crate::module::{Module, ModuleLoader};
+use crate::function::LoxFunction;
+use crate::scanner::Scanner;
+use crate::parser::Parser;
+use crate::type_checker::TypeChecker;
+use crate::utils;
+use crate::ast_stmt::{Expression, Stmt, Print, Var, Block, If, While, ForIn, Break, Continue, Import};
+use crate::ast_stmt::{Throw, Try, Return};
+use crate::token::Token;
 use crate::token_type::TokenType;
-use crate::error::EvaluationError;
+use crate::error::{EvaluationError, Operands};
+
+// Rules of a run, the default is plain Lox without limits other than the call depth
+#[derive(Clone, Copy, Default, Debug)]
+pub struct InterpreterOptions {
+    pub strict: bool,
+    pub limits: Limits,
+}
+
+impl InterpreterOptions {
+    // Truth value of a condition, with the strict mode only booleans are accepted
+    pub fn condition(&self, lit: &Literal) -> Result<bool, EvaluationError> {
+        match lit {
+            Literal::BOOL(val) => Ok(*val),
+            other if self.strict => Err(
+                EvaluationError::new_unary("Condition must be a boolean".to_string(), other.clone())
+            ),
+            other => Ok(Interpreter::is_truthy(other)),
+        }
+    }
+
+    pub fn unary_op(&self, op: &TokenType, r: Literal) -> Result<Literal, EvaluationError> {
+        match (op, r) {
+            (TokenType::BANG, r) if self.strict && !matches!(r, Literal::BOOL(_)) => Err(
+                EvaluationError::new_unary("Operand of '!' must be a boolean".to_string(), r)
+            ),
+            (op, r) => Interpreter::unary_op(op, r),
+        }
+    }
+
+    pub fn binary_op(&self, op: &TokenType, l: Literal, r: Literal) -> Result<Literal, EvaluationError> {
+        match (op, l, r) {
+            (TokenType::PLUS, l @ Literal::STRING(_), r) | (TokenType::PLUS, l, r @ Literal::STRING(_))
+                if self.strict && !(matches!(l, Literal::STRING(_)) && matches!(r, Literal::STRING(_))) => Err(
+                    EvaluationError::new_binary("Operands of '+' must be two numbers or two strings".to_string(), l, r)
+                ),
+            (op, l, r) => Interpreter::binary_op(op, l, r),
+        }
+    }
+}
+
+// Seconds of virtual clock for each step in the deterministic mode
+const VIRTUAL_TICK: f64 = 1e-6;
+
+pub struct Interpreter {
+    environment: Environment,
+    options: InterpreterOptions,
+    sandbox: Sandbox,
+    capabilities: Capabilities,
+    random: Random,
+    deterministic: bool,
+    hooks: Vec<Box<dyn Instrument>>,
+    // Line and callee of the call expression being evaluated, for the call hooks
+    call_line: usize,
+    call_name: Option<String>,
+    output: Box<dyn Write>,
+    modules: ModuleLoader,
+}
+
+impl Interpreter {
+
+    pub fn new() -> Self {
+        Interpreter::with_output(Box::new(io::stdout()))
+    }
+
+    pub fn with_output(output: Box<dyn Write>) -> Self {
+
+        let mut environment = Environment::new();
+        let hooks = Vec::new();
+
+        natives::define_natives(&mut environment);
+
+        Interpreter {
+            environment,
+            options: InterpreterOptions::default(),
+            sandbox: Sandbox::new(Limits::default()),
+            capabilities: Capabilities::none(),
+            random: Random::from_time(),
+            deterministic: false,
+            hooks,
+            call_line: 0,
+            call_name: None,
+            output,
+            modules: ModuleLoader::new(),
+        }
+    }
+
+    pub fn set_options(&mut self, options: InterpreterOptions) {
+        self.options = options;
+        self.sandbox.set_limits(options.limits);
+    }
+
+    // Natives needing a capability not granted fail when called
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+
+    // Makes the runs reproducible, with the same seed `random()` gives the same numbers
+    pub fn set_deterministic(&mut self, seed: u64) {
+        self.random = Random::new(seed);
+        self.deterministic = true;
+    }
+
+    pub fn random(&mut self) -> f64 {
+        self.random.next_f64()
+    }
+
+    // Time of `clock()` in the deterministic mode, None when it is the system clock
+    pub fn virtual_clock(&self) -> Option<f64> {
+        if self.deterministic {
+            Some(self.sandbox.steps() as f64 * VIRTUAL_TICK)
+        } else {
+            None
+        }
+    }
+
+    // The run stops at the next step after the token is cancelled
+    pub fn set_cancel_token(&mut self, token: CancelToken) {
+        self.sandbox.set_cancel_token(token);
+    }
+
+    // Counts a value created by a native against the limits
+    pub fn count(&mut self, value: &Literal) -> Result<(), EvaluationError> {
+        self.sandbox.count(value)
+    }
+
+    pub fn grow(&mut self, elements: usize) -> Result<(), EvaluationError> {
+        self.sandbox.grow(elements as u64)
+    }
+
+    pub fn options(&self) -> InterpreterOptions {
+        self.options
+    }
+
+    // File being run, imports are resolved relative to it
+    pub fn set_script(&mut self, path: &Path) {
+        self.modules.set_script(path);
+    }
+
+    pub fn add_hook(&mut self, hook: Box<dyn Instrument>) {
+        self.hooks.push(hook);
+    }
+
+    pub fn start(&mut self, statements: &[Stmt]) {
+        for hook in self.hooks.iter_mut() {
+            hook.before_program(statements);
+        }
+    }
+
+    pub fn interpret(&mut self, s: &Stmt) -> Result<Literal, EvaluationError> {
+        return self.visit_stmt(s);
+    }
+
+    pub fn finish(&mut self) {
+        let _ = self.output.flush();
+        for hook in self.hooks.iter_mut() {
+            hook.finish();
+        }
+    }
+
+    // Lox truthiness: only false and nil are false
+    pub fn is_truthy(lit: &Literal) -> bool {
+        !matches!(lit, Literal::BOOL(false) | Literal::NIL)
+    }
+
+    pub fn unary_op(op: &TokenType, r: Literal) -> Result<Literal, EvaluationError> {
+        match (op.clone(), r) {
+            (TokenType::MINUS, Literal::NUMBER(val)) => Ok(Literal::NUMBER(-val)),
+            (TokenType::MINUS, Literal::INTEGER(val)) => match val.checked_neg() {
+                Some(neg) => Ok(Literal::INTEGER(neg)),
+                None => Err(
+                    EvaluationError::new_unary("Integer overflow".to_string(), Literal::INTEGER(val))
+                ),
+            },
+            (TokenType::BANG, Literal::BOOL(val)) => Ok(Literal::BOOL(!val)),
+            (TokenType::BANG, Literal::NIL) => Ok(Literal::BOOL(true)),
+            (TokenType::BANG, _) => Ok(Literal::BOOL(false)),
+            (_, lit) => Err(
+                EvaluationError::new_unary(
+                    "Invalid operation on unary operand.".to_string(),
+                    lit
+                )
+            ),
+        }
+    }
+
+    pub fn binary_op(op: &TokenType, l: Literal, r: Literal) -> Result<Literal, EvaluationError> {
+        match (op.clone(), l, r) {
+            (op, Literal::INTEGER(lval), Literal::INTEGER(rval)) => Interpreter::integer_op(&op, lval, rval),
+            // Mixed integer and float operations are done in floats
+            (op, Literal::INTEGER(lval), Literal::NUMBER(rval)) if !Interpreter::is_bitwise(&op) => {
+                Interpreter::binary_op(&op, Literal::NUMBER(lval as f64), Literal::NUMBER(rval))
+            },
+            (op, Literal::NUMBER(lval), Literal::INTEGER(rval)) if !Interpreter::is_bitwise(&op) => {
+                Interpreter::binary_op(&op, Literal::NUMBER(lval), Literal::NUMBER(rval as f64))
+            },
+            (TokenType::MINUS, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::NUMBER(lval - rval)),
+            (TokenType::PLUS, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::NUMBER(lval + rval)),
+            (TokenType::PLUS, Literal::STRING(lval), Literal::STRING(rval)) => {
+                let mut appended = lval.clone();
+                appended.push_str(&rval);
+                Ok(Literal::STRING(appended))
+            },
+            (TokenType::PLUS, Literal::STRING(lval), rval @ (Literal::NUMBER(_) | Literal::INTEGER(_))) => {
+                let mut appended = lval.clone();
+                appended.push_str(&rval.to_string());
+                Ok(Literal::STRING(appended))
+            },
+            (TokenType::PLUS, lval @ (Literal::NUMBER(_) | Literal::INTEGER(_)), Literal::STRING(rval)) => {
+                let mut appended = lval.to_string();
+                appended.push_str(&rval);
+                Ok(Literal::STRING(appended))
+            },
+            (TokenType::SLASH | TokenType::SLASHSLASH | TokenType::PERCENT, Literal::NUMBER(lval), Literal::NUMBER(rval)) if rval == 0.0 => {
+                Err(
+                    EvaluationError::new_binary(
+                        "Division by zero".to_string(),
+                        Literal::NUMBER(lval),
+                        Literal::NUMBER(rval),
+                    )
+                )
+            },
+            (TokenType::SLASH, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::NUMBER(lval / rval)),
+            (TokenType::SLASHSLASH, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::NUMBER((lval / rval).floor())),
+            (TokenType::PERCENT, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::NUMBER(lval - rval * (lval / rval).floor())),
+            (TokenType::STAR, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::NUMBER(lval * rval)),
+            (TokenType::GREATER, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::BOOL(lval > rval)),
+            (TokenType::GREATEREQUAL, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::BOOL(lval >= rval)),
+            (TokenType::LESS, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::BOOL(lval < rval)),
+            (TokenType::LESSEQUAL, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::BOOL(lval <= rval)),
+            // Strings are ordered by their code points
+            (TokenType::GREATER, Literal::STRING(lval), Literal::STRING(rval)) => Ok(Literal::BOOL(lval > rval)),
+            (TokenType::GREATEREQUAL, Literal::STRING(lval), Literal::STRING(rval)) => Ok(Literal::BOOL(lval >= rval)),
+            (TokenType::LESS, Literal::STRING(lval), Literal::STRING(rval)) => Ok(Literal::BOOL(lval < rval)),
+            (TokenType::LESSEQUAL, Literal::STRING(lval), Literal::STRING(rval)) => Ok(Literal::BOOL(lval <= rval)),
+            (TokenType::EQUALEQUAL, lval, rval) => Ok(Literal::BOOL(Interpreter::equals(&lval, &rval))),
+            (TokenType::BANGEQUAL, lval, rval) => Ok(Literal::BOOL(!Interpreter::equals(&lval, &rval))),
+            (_, lit1, lit2) => return Err(
+                EvaluationError::new_binary(
+                    "Invalid operation on binary operand".to_string(),
+                    lit1,
+                    lit2,
+                )
+            ),
+        }
+    }
+
+    // Equality between any two values: numbers by value (integers equal to the same float),
+    // strings by content, and lists, maps, functions and modules by identity.
+    // Values of different types are never equal.
+    pub fn equals(l: &Literal, r: &Literal) -> bool {
+        match (l, r) {
+            (Literal::NUMBER(lval), Literal::NUMBER(rval)) => lval == rval,
+            (Literal::INTEGER(lval), Literal::INTEGER(rval)) => lval == rval,
+            (Literal::INTEGER(lval), Literal::NUMBER(rval)) => *lval as f64 == *rval,
+            (Literal::NUMBER(lval), Literal::INTEGER(rval)) => *lval == *rval as f64,
+            (Literal::STRING(lval), Literal::STRING(rval)) => lval == rval,
+            (Literal::BOOL(lval), Literal::BOOL(rval)) => lval == rval,
+            (Literal::NIL, Literal::NIL) => true,
+            (Literal::LIST(lval), Literal::LIST(rval)) => Rc::ptr_eq(lval, rval),
+            (Literal::MAP(lval), Literal::MAP(rval)) => Rc::ptr_eq(lval, rval),
+            (Literal::FUNCTION(lval), Literal::FUNCTION(rval)) => Rc::ptr_eq(lval, rval),
+            (Literal::NATIVE(lval), Literal::NATIVE(rval)) => lval == rval,
+            (Literal::MODULE(lval), Literal::MODULE(rval)) => Rc::ptr_eq(lval, rval),
+            _ => false,
+        }
+    }
+
+    fn is_bitwise(op: &TokenType) -> bool {
+        matches!(
+            op,
+            TokenType::AMPERSAND | TokenType::PIPE | TokenType::CARET | TokenType::LESSLESS | TokenType::GREATERGREATER
+        )
+    }
+
+    // Exact integer arithmetic, where overflowing the 64 bits is an error.
+    // `//` and `%` round toward negative infinity, so `%` takes the sign of the divisor.
+    fn integer_op(op: &TokenType, lval: i64, rval: i64) -> Result<Literal, EvaluationError> {
+        let error = |message: &str| Err(
+            EvaluationError::new_binary(message.to_string(), Literal::INTEGER(lval), Literal::INTEGER(rval))
+        );
+
+        let checked = |result: Option<i64>| match result {
+            Some(val) => Ok(Literal::INTEGER(val)),
+            None => error("Integer overflow"),
+        };
+
+        match op {
+            TokenType::PLUS => checked(lval.checked_add(rval)),
+            TokenType::MINUS => checked(lval.checked_sub(rval)),
+            TokenType::STAR => checked(lval.checked_mul(rval)),
+            TokenType::SLASH | TokenType::SLASHSLASH | TokenType::PERCENT if rval == 0 => error("Division by zero"),
+            TokenType::SLASH => Ok(Literal::NUMBER(lval as f64 / rval as f64)),
+            TokenType::SLASHSLASH => checked(lval.checked_div(rval).map(|q| {
+                if lval % rval != 0 && (lval < 0) != (rval < 0) { q - 1 } else { q }
+            })),
+            TokenType::PERCENT => checked(lval.checked_rem(rval).map(|m| {
+                if m != 0 && (m < 0) != (rval < 0) { m + rval } else { m }
+            })),
+            TokenType::AMPERSAND => Ok(Literal::INTEGER(lval & rval)),
+            TokenType::PIPE => Ok(Literal::INTEGER(lval | rval)),
+            TokenType::CARET => Ok(Literal::INTEGER(lval ^ rval)),
+            TokenType::LESSLESS | TokenType::GREATERGREATER if !(0..64).contains(&rval) => {
+                error("Shift amount must be between 0 and 63")
+            },
+            TokenType::LESSLESS => {
+                let shifted = lval << rval;
+                if shifted >> rval != lval {
+                    return error("Integer overflow");
+                }
+                Ok(Literal::INTEGER(shifted))
+            },
+            TokenType::GREATERGREATER => Ok(Literal::INTEGER(lval >> rval)),
+            TokenType::GREATER => Ok(Literal::BOOL(lval > rval)),
+            TokenType::GREATEREQUAL => Ok(Literal::BOOL(lval >= rval)),
+            TokenType::LESS => Ok(Literal::BOOL(lval < rval)),
+            TokenType::LESSEQUAL => Ok(Literal::BOOL(lval <= rval)),
+            TokenType::EQUALEQUAL => Ok(Literal::BOOL(lval == rval)),
+            TokenType::BANGEQUAL => Ok(Literal::BOOL(lval != rval)),
+            _ => error("Invalid operation on binary operand"),
+        }
+    }
+
+    // Element of a list or string at a position, or the value of a map key
+    fn get_element(object: &Literal, index: &Literal) -> Result<Literal, EvaluationError> {
+        let element = match (object, index) {
+            (Literal::LIST(list), Literal::NUMBER(_) | Literal::INTEGER(_)) => {
+                let list = list.borrow();
+                Interpreter::element_index(index, list.len()).map(|pos| list[pos].clone())
+            },
+            (Literal::STRING(val), Literal::NUMBER(_) | Literal::INTEGER(_)) => {
+                let chars: Vec<char> = val.chars().collect();
+                Interpreter::element_index(index, chars.len()).map(|pos| Literal::STRING(chars[pos].to_string()))
+            },
+            (Literal::MAP(_), key) if !LoxMap::is_valid_key(key) => return Err(
+                EvaluationError::new_unary(
+                    "Map keys must be strings or numbers".to_string(),
+                    index.clone(),
+                )
+            ),
+            (Literal::MAP(map), key) => match map.borrow().get(key) {
+                Some(value) => Some(value.clone()),
+                None => return Err(
+                    EvaluationError::new_unary(
+                        "Key not found in map".to_string(),
+                        index.clone(),
+                    )
+                ),
+            },
+            _ => return Err(
+                EvaluationError::new_binary(
+                    "Only lists and strings can be indexed by numbers".to_string(),
+                    object.clone(),
+                    index.clone(),
+                )
+            ),
+        };
+
+        match element {
+            Some(lit) => Ok(lit),
+            None => Err(
+                EvaluationError::new_binary(
+                    "Index out of bounds".to_string(),
+                    object.clone(),
+                    index.clone(),
+                )
+            ),
+        }
+    }
+
+    // Stores a list element or a map entry, giving back the value stored
+    fn set_element(object: &Literal, index: &Literal, value: Literal) -> Result<Literal, EvaluationError> {
+        let pos = match (object, index) {
+            (Literal::LIST(list), Literal::NUMBER(_) | Literal::INTEGER(_)) => Interpreter::element_index(index, list.borrow().len()),
+            (Literal::MAP(map), key) => {
+                if !LoxMap::is_valid_key(key) {
+                    return Err(
+                        EvaluationError::new_unary(
+                            "Map keys must be strings or numbers".to_string(),
+                            index.clone(),
+                        )
+                    );
+                }
+                map.borrow_mut().set(key.clone(), value.clone());
+                return Ok(value);
+            },
+            _ => return Err(
+                EvaluationError::new_binary(
+                    "Only list and map elements can be assigned".to_string(),
+                    object.clone(),
+                    index.clone(),
+                )
+            ),
+        };
+
+        match (pos, object) {
+            (Some(pos), Literal::LIST(list)) => {
+                list.borrow_mut()[pos] = value.clone();
+                Ok(value)
+            },
+            _ => Err(
+                EvaluationError::new_binary(
+                    "Index out of bounds".to_string(),
+                    object.clone(),
+                    index.clone(),
+                )
+            ),
+        }
+    }
+
+    fn assign_variable(&mut self, name: &Token, value: Literal) -> Result<Literal, EvaluationError> {
+        let name = match name.value {
+            Some(ref n) => n.clone(),
+            None => return Err(
+                EvaluationError::new_var("No name in token defined for variable".to_string())
+            ),
+        };
+
+        // Also rejected before running, this is the fallback for code that was not checked
+        if self.environment.is_constant(&name) {
+            return Err(
+                EvaluationError::new_var(format!("Cannot assign to constant '{}'", name))
+            );
+        }
+
+        if !self.environment.assign(name.clone(), value.clone()) {
+            return Err(
+                EvaluationError::new_var(format!("Undefined variable '{}'", name))
+            );
+        }
+
+        Ok(value)
+    }
+
+    // Position of an element, negative indexes count from the end
+    fn element_index(index: &Literal, len: usize) -> Option<usize> {
+        let idx = match index {
+            Literal::INTEGER(val) => *val as f64,
+            Literal::NUMBER(val) if val.fract() == 0.0 => *val,
+            _ => return None,
+        };
+
+        let pos = if idx < 0.0 { len as f64 + idx } else { idx };
+        if pos < 0.0 || pos >= len as f64 {
+            return None;
+        }
+
+        Some(pos as usize)
+    }
+
+    // Bound of a slice, clamped to the length like in Python
+    fn slice_bound(&mut self, bound: &Option<Box<Expr>>, default: usize, len: usize) -> Result<usize, EvaluationError> {
+        let idx = match bound {
+            Some(expr) => match self.visit_expr(&expr) {
+                Ok(Literal::NUMBER(val)) if val.fract() == 0.0 => val,
+                Ok(Literal::INTEGER(val)) => val as f64,
+                Ok(lit) => return Err(
+                    EvaluationError::new_unary("Slice bounds must be integers".to_string(), lit)
+                ),
+                Err(e) => return Err(e),
+            },
+            None => return Ok(default),
+        };
+
+        let pos = if idx < 0.0 { len as f64 + idx } else { idx };
+        Ok(pos.clamp(0.0, len as f64) as usize)
+    }
+
+    fn execute(&mut self, s: &Stmt) -> Result<Literal, EvaluationError> {
+        return match s {
+            Stmt::BLOCK(b) => self.visit_block(&b),
+            Stmt::EXPRESSION(e) => self.visit_expression(&e),
+            Stmt::PRINT(p) => self.visit_print(&p),
+            Stmt::VAR(v) => self.visit_var(&v),
+            Stmt::IF(i) => self.visit_if(&i),
+            Stmt::WHILE(w) => self.visit_while(&w),
+            Stmt::FORIN(f) => self.visit_for_in(&f),
+            Stmt::BREAK(b) => self.visit_break(&b),
+            Stmt::CONTINUE(c) => self.visit_continue(&c),
+            Stmt::IMPORT(i) => self.visit_import(&i),
+            Stmt::THROW(t) => self.visit_throw(&t),
+            Stmt::TRY(t) => self.visit_try(&t),
+            Stmt::RETURN(r) => self.visit_return(&r),
+        }
+    }
+
+    // Runs the module file in a fresh global scope, returning its namespace
+    fn load_module(&mut self, path: &Path) -> Result<Module, EvaluationError> {
+        let source = match utils::try_read_file(&path.to_path_buf()) {
+            Ok(source) => source,
+            Err(err) => return Err(
+                EvaluationError::new_import(format!("Cannot read module '{}' ({})", path.display(), err))
+            ),
+        };
+
+        let mut scanner = Scanner::new(source);
+        let mut parser = Parser::new(scanner.scan_tokens().clone());
+        let statements = parser.parse();
+
+        let mut type_checker = TypeChecker::new();
+        type_checker.check(&statements);
+
+        let first_error = scanner.errors.iter().map(|e| e.to_string())
+            .chain(parser.errors.iter().map(|e| e.to_string()))
+            .chain(type_checker.errors.iter().map(|e| e.to_string()))
+            .next();
+
+        if let Some(err) = first_error {
+            return Err(
+                EvaluationError::new_import(format!("Error in module '{}': {}", path.display(), err))
+            );
+        }
+
+        let name = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+
+        let mut globals = Environment::new();
+        natives::define_natives(&mut globals);
+
+        let outer = mem::replace(&mut self.environment, Environment::new_enclosed(globals));
+        let hooks = mem::take(&mut self.hooks);
+
+        let mut result = Ok(());
+        for stmt in &statements {
+            if let Err(e) = self.visit_stmt(stmt) {
+                result = Err(e);
+                break;
+            }
+        }
+
+        self.hooks = hooks;
+        let module_env = mem::replace(&mut self.environment, outer);
+
+        match result {
+            Ok(_) => Ok(Module::new(name, path.to_path_buf(), module_env.locals())),
+            // Errors of nested imports already name their module
+            Err(e) if e.operands == Operands::IMPORT => Err(e),
+            Err(e) => Err(
+                EvaluationError::new_import(format!("Error in module '{}': {}", path.display(), e))
+            ),
+        }
+    }
+
+    fn push_scope(&mut self) {
+        let outer = mem::replace(&mut self.environment, Environment::new());
+        self.environment = Environment::new_enclosed(outer);
+    }
+
+    fn pop_scope(&mut self) {
+        let inner = mem::replace(&mut self.environment, Environment::new());
+        self.environment = inner.into_enclosing();
+    }
+
+    // Runs the statements in a new scope, stopping at the first error
+    fn execute_block(&mut self, statements: &[Stmt]) -> Result<Literal, EvaluationError> {
+        self.push_scope();
+
+        let mut result = Ok(Literal::NIL);
+        for stmt in statements {
+            if let Err(e) = self.visit_stmt(stmt) {
+                result = Err(e);
+                break;
+            }
+        }
+
+        self.pop_scope();
+        result
+    }
+
+    // Name of a callee in the call hooks, the name it was called by if there is one
+    fn callee_name(expr: Option<&Expr>, callee: &Literal) -> String {
+        match (expr, callee) {
+            (Some(Expr::VARIABLE(v)), _) => v.name.value.clone().unwrap_or_default(),
+            (Some(Expr::GET(g)), _) => match &*g.object {
+                Expr::VARIABLE(v) => format!(
+                    "{}.{}",
+                    v.name.value.clone().unwrap_or_default(),
+                    g.name.value.clone().unwrap_or_default()
+                ),
+                _ => g.name.value.clone().unwrap_or_default(),
+            },
+            (_, Literal::NATIVE(native)) => native.name.clone(),
+            _ => "lambda".to_string(),
+        }
+    }
+
+    // Functions called by natives, like the callback of `map()`, are reported at the
+    // line of the call to the native
+    pub fn call(&mut self, callee: Literal, arguments: Vec<Literal>) -> Result<Literal, EvaluationError> {
+        let line = self.call_line;
+        let name = match self.call_name.take() {
+            Some(name) => name,
+            None => Interpreter::callee_name(None, &callee),
+        };
+
+        for hook in self.hooks.iter_mut() {
+            hook.before_call(line, &name);
+        }
+
+        let result = self.call_value(callee, arguments);
+
+        self.call_line = line;
+        for hook in self.hooks.iter_mut() {
+            hook.after_call(line, &name);
+        }
+
+        result
+    }
+
+    // Calls a native or a lambda, the arguments must already match its arity
+    fn call_value(&mut self, callee: Literal, arguments: Vec<Literal>) -> Result<Literal, EvaluationError> {
+        match callee {
+            Literal::NATIVE(native) => match native.capability {
+                Some(capability) if !self.capabilities.allows(capability) => Err(
+                    EvaluationError::new_call(
+                        format!("{}() requires the '{}' capability", native.name, capability)
+                    )
+                ),
+                _ => (native.func)(self, arguments),
+            },
+            Literal::FUNCTION(function) => {
+                if let Err(e) = self.sandbox.enter_call() {
+                    return Err(e);
+                }
+
+                let mut scope = Environment::new_enclosed(function.closure.clone());
+                for (param, value) in function.params.iter().zip(arguments) {
+                    scope.define(param.value.clone().unwrap_or_default(), value);
+                }
+
+                let caller = mem::replace(&mut self.environment, scope);
+
+                let mut result = Ok(Literal::NIL);
+                for stmt in function.body.iter() {
+                    if let Err(e) = self.visit_stmt(stmt) {
+                        result = Err(e);
+                        break;
+                    }
+                }
+
+                self.environment = caller;
+                self.sandbox.exit_call();
+
+                match result {
+                    Err(e) => match e.operands {
+                        Operands::RETURN(value) => Ok(value),
+                        _ => Err(e),
+                    },
+                    Ok(_) => Ok(Literal::NIL),
+                }
+            },
+            other => Err(
+                EvaluationError::new_unary(
+                    "Can only call functions".to_string(),
+                    other,
+                )
+            ),
+        }
+    }
+
+    pub fn pattern_matches(pattern: &Pattern, value: &Literal) -> bool {
+        match (pattern, value) {
+            (Pattern::WILDCARD | Pattern::BINDING(_), _) => true,
+            (Pattern::TYPE(name), value) => value.is_type(name.value.as_deref().unwrap_or_default()),
+            (Pattern::LITERAL(lit), value) => Interpreter::equals(lit, value),
+        }
+    }
+
+    // Body of an arm whose pattern matched, None if its guard is false
+    fn match_arm(&mut self, arm: &MatchArm, subject: &Literal) -> Result<Option<Literal>, EvaluationError> {
+        if let Some(Pattern::BINDING(name)) = arm.patterns.first() {
+            self.environment.define(name.value.clone().unwrap_or_default(), subject.clone());
+        }
+
+        if let Some(guard) = &arm.guard {
+            match self.visit_expr(&guard) {
+                Ok(cond) => match self.options.condition(&cond) {
+                    Ok(true) => (),
+                    Ok(false) => return Ok(None),
+                    Err(e) => return Err(e),
+                },
+                Err(e) => return Err(e),
+            }
+        }
+
+        match self.visit_expr(&arm.body) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Value received by a catch clause
+    fn caught_value(e: &EvaluationError) -> Literal {
+        match &e.operands {
+            Operands::THROW(value) => value.clone(),
+            _ => {
+                let mut error = LoxMap::new();
+                error.set(Literal::STRING("message".to_string()), Literal::STRING(e.message.clone()));
+                error.set(
+                    Literal::STRING("line".to_string()),
+                    e.line.map_or(Literal::NIL, |line| Literal::INTEGER(line as i64)),
+                );
+                Literal::MAP(Rc::new(RefCell::new(error)))
+            },
+        }
+    }
+
+    // Runs a loop body, true if the loop must stop because of a `break`
+    fn execute_body(&mut self, body: &Stmt) -> Result<bool, EvaluationError> {
+        match self.visit_stmt(body) {
+            Ok(_) => Ok(false),
+            Err(e) => match e.operands {
+                Operands::BREAK => Ok(true),
+                Operands::CONTINUE => Ok(false),
+                _ => Err(e),
+            },
+        }
+    }
+}
+
+impl ExprVisitor<Result<Literal, EvaluationError>> for Interpreter {
+
+
+    fn visit_expr(&mut self, e: &Expr) -> Result<Literal, EvaluationError> {
+        if let Err(err) = self.sandbox.step() {
+            return Err(err);
+        }
+
+        let result = match e {
+            Expr::UNARY(u) => self.visit_unary(&u),
+            Expr::BINARY(b) => self.visit_binary(&b),
+            Expr::LOGICAL(l) => self.visit_logical(&l),
+            Expr::ASSIGN(a) => self.visit_assign(&a),
+            Expr::GROUPING(g) => self.visit_grouping(&g),
+            Expr::LITERAL(l) => self.visit_literal(&l),
+            Expr::COMMA(c) => self.visit_comma(&c),
+            Expr::TERNARY(t) => self.visit_ternary(&t),
+            Expr::VARIABLE(v) => self.visit_variable(&v),
+            Expr::LIST(l) => self.visit_list(&l),
+            Expr::MAP(m) => self.visit_map(&m),
+            Expr::INDEX(i) => self.visit_index(&i),
+            Expr::SLICE(s) => self.visit_slice(&s),
+            Expr::SETINDEX(s) => self.visit_set_index(&s),
+            Expr::COMPOUND(c) => self.visit_compound(&c),
+            Expr::CALL(c) => self.visit_call(&c),
+            Expr::GET(g) => self.visit_get(&g),
+            Expr::FORMAT(f) => self.visit_format(&f),
+            Expr::LAMBDA(l) => self.visit_lambda(&l),
+            Expr::MATCH(m) => self.visit_match(&m),
+        };
+
+        match result {
+            Ok(value) => self.sandbox.allocate(e, &value).map(|_| value),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn visit_literal(&mut self, l: &Literal) -> Result<Literal, EvaluationError> {
+        Ok(l.clone())
+    }
+
+    fn visit_list(&mut self, l: &List) -> Result<Literal, EvaluationError> {
+        let mut elements = Vec::new();
+
+        for expr in &l.elements {
+            match self.visit_expr(expr) {
+                Ok(lit) => elements.push(lit),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(Literal::LIST(Rc::new(RefCell::new(elements))))
+    }
+
+    fn visit_map(&mut self, m: &Map) -> Result<Literal, EvaluationError> {
+        let mut map = LoxMap::new();
+
+        for (key, value) in &m.entries {
+            let key = match self.visit_expr(key) {
+                Ok(lit) => lit,
+                Err(e) => return Err(e)
+            };
+
+            let value = match self.visit_expr(value) {
+                Ok(lit) => lit,
+                Err(e) => return Err(e)
+            };
+
+            if !LoxMap::is_valid_key(&key) {
+                return Err(
+                    EvaluationError::new_unary(
+                        "Map keys must be strings or numbers".to_string(),
+                        key,
+                    )
+                );
+            }
+            map.set(key, value);
+        }
+
+        Ok(Literal::MAP(Rc::new(RefCell::new(map))))
+    }
+
+    fn visit_index(&mut self, i: &Index) -> Result<Literal, EvaluationError> {
+        let object = match self.visit_expr(&i.object) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
+
+        let index = match self.visit_expr(&i.index) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
+
+        Interpreter::get_element(&object, &index)
+    }
+
+    fn visit_slice(&mut self, s: &Slice) -> Result<Literal, EvaluationError> {
+        let object = match self.visit_expr(&s.object) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
+
+        let len = match &object {
+            Literal::LIST(list) => list.borrow().len(),
+            Literal::STRING(val) => val.chars().count(),
+            _ => return Err(
+                EvaluationError::new_unary(
+                    "Only lists and strings can be sliced".to_string(),
+                    object,
+                )
+            ),
+        };
+
+        let start = match self.slice_bound(&s.start, 0, len) {
+            Ok(pos) => pos,
+            Err(e) => return Err(e)
+        };
+
+        let end = match self.slice_bound(&s.end, len, len) {
+            Ok(pos) => pos.max(start),
+            Err(e) => return Err(e)
+        };
+
+        match object {
+            Literal::LIST(list) => Ok(
+                Literal::LIST(Rc::new(RefCell::new(list.borrow()[start..end].to_vec())))
+            ),
+            Literal::STRING(val) => Ok(
+                Literal::STRING(val.chars().skip(start).take(end - start).collect())
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    fn visit_set_index(&mut self, s: &SetIndex) -> Result<Literal, EvaluationError> {
+        let object = match self.visit_expr(&s.object) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
+
+        let index = match self.visit_expr(&s.index) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
 
-pub struct Interpreter {
-    environment: Environment,
-}
+        let value = match self.visit_expr(&s.value) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
 
-impl Interpreter {
+        // A new key grows the map
+        if let Literal::MAP(map) = &object {
+            if LoxMap::is_valid_key(&index) && !map.borrow().has(&index) {
+                if let Err(e) = self.grow(1) {
+                    return Err(e);
+                }
+            }
+        }
 
-    pub fn new() -> Self {
-        
-        let environment = Environment::new();
+        Interpreter::set_element(&object, &index, value)
+    }
 
-        Interpreter {
-            environment
+    fn visit_compound(&mut self, c: &Compound) -> Result<Literal, EvaluationError> {
+        // The object and index of an element are evaluated once, for the read and the write
+        let element = match &*c.target {
+            Expr::INDEX(i) => {
+                let object = match self.visit_expr(&i.object) {
+                    Ok(lit) => lit,
+                    Err(e) => return Err(e)
+                };
+
+                match self.visit_expr(&i.index) {
+                    Ok(index) => Some((object, index)),
+                    Err(e) => return Err(e)
+                }
+            },
+            _ => None,
+        };
+
+        let current = match (&element, &*c.target) {
+            (Some((object, index)), _) => Interpreter::get_element(object, index),
+            (None, target) => self.visit_expr(target),
+        };
+
+        let current = match current {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
+
+        if c.is_increment() && !matches!(current, Literal::NUMBER(_) | Literal::INTEGER(_)) {
+            let symbol = if c.operator.kind == TokenType::PLUSPLUS { "++" } else { "--" };
+            return Err(
+                EvaluationError::new_unary(
+                    format!("Operand of '{}' must be a number", symbol),
+                    current,
+                )
+            );
+        }
+
+        let value = match self.visit_expr(&c.value) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
+
+        let updated = match self.options.binary_op(&c.binary_op(), current.clone(), value) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
+
+        let stored = match (element, &*c.target) {
+            (Some((object, index)), _) => Interpreter::set_element(&object, &index, updated),
+            (None, Expr::VARIABLE(v)) => self.assign_variable(&v.name, updated),
+            (None, _) => Err(
+                EvaluationError::new_var("Invalid assignment target".to_string())
+            ),
+        };
+
+        match stored {
+            Ok(_) if c.postfix => Ok(current),
+            result => result,
         }
     }
 
-    pub fn interpret(&mut self, s: &Stmt) -> Result<Literal, EvaluationError> {
-        return self.visit_stmt(s);
+    fn visit_call(&mut self, c: &Call) -> Result<Literal, EvaluationError> {
+        let callee = match self.visit_expr(&c.callee) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
+
+        let mut arguments = Vec::new();
+        for expr in &c.arguments {
+            match self.visit_expr(expr) {
+                Ok(lit) => arguments.push(lit),
+                Err(e) => return Err(e),
+            }
+        }
+
+        let (name, arity) = match &callee {
+            Literal::NATIVE(native) => (native.name.clone(), native.arity),
+            Literal::FUNCTION(function) => ("lambda".to_string(), function.arity()),
+            _ => return Err(
+                EvaluationError::new_unary(
+                    "Can only call functions".to_string(),
+                    callee,
+                )
+            ),
+        };
+
+        if arguments.len() != arity {
+            return Err(
+                EvaluationError::new_call(
+                    format!(
+                        "{} expected {} arguments but got {} (line {})",
+                        name,
+                        arity,
+                        arguments.len(),
+                        c.paren.line,
+                    )
+                )
+            );
+        }
+
+        self.call_line = c.paren.line;
+        self.call_name = Some(Interpreter::callee_name(Some(&c.callee), &callee));
+        self.call(callee, arguments)
     }
-}
 
-impl ExprVisitor<Result<Literal, EvaluationError>> for Interpreter {
+    fn visit_match(&mut self, m: &Match) -> Result<Literal, EvaluationError> {
+        let subject = match self.visit_expr(&m.subject) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
 
+        for arm in &m.arms {
+            if !arm.patterns.iter().any(|p| Interpreter::pattern_matches(p, &subject)) {
+                continue;
+            }
 
-    fn visit_expr(&mut self, e: &Expr) -> Result<Literal, EvaluationError> {
-        match e {
-            Expr::UNARY(u) => self.visit_unary(&u),
-            Expr::BINARY(b) => self.visit_binary(&b),
-            Expr::ASSIGN(a) => self.visit_assign(&a),
-            Expr::GROUPING(g) => self.visit_grouping(&g),
-            Expr::LITERAL(l) => self.visit_literal(&l),
-            Expr::COMMA(c) => self.visit_comma(&c),
-            Expr::TERNARY(t) => self.visit_ternary(&t),
-            Expr::VARIABLE(v) => self.visit_variable(&v),
-        } 
+            // The bound name is only visible in the guard and the body of its arm
+            self.push_scope();
+            let result = self.match_arm(arm, &subject);
+            self.pop_scope();
+
+            match result {
+                Ok(Some(value)) => return Ok(value),
+                Ok(None) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(
+            EvaluationError::new_unary(
+                format!("No match arm for the value (line {})", m.keyword.line),
+                subject,
+            )
+        )
     }
 
-    fn visit_literal(&mut self, l: &Literal) -> Result<Literal, EvaluationError> {
-        Ok(l.clone())
+    fn visit_lambda(&mut self, l: &Lambda) -> Result<Literal, EvaluationError> {
+        Ok(
+            Literal::FUNCTION(
+                Rc::new(
+                    LoxFunction::new(
+                        l.params.clone(),
+                        l.body.clone(),
+                        self.environment.clone(),
+                    )
+                )
+            )
+        )
     }
 
-    fn visit_unary(&mut self, u: &Unary) -> Result<Literal, EvaluationError> {
-        let r = match self.visit_expr(&u.rhs) {
+    fn visit_get(&mut self, g: &Get) -> Result<Literal, EvaluationError> {
+        let object = match self.visit_expr(&g.object) {
             Ok(lit) => lit,
             Err(e) => return Err(e)
         };
 
-        match (u.op.kind.clone(), r) {
-            (TokenType::MINUS, Literal::NUMBER(val)) => Ok(Literal::NUMBER(-val)),
-            (TokenType::BANG, Literal::BOOL(val)) => Ok(Literal::BOOL(!val)),
-            (TokenType::BANG, Literal::NIL) => Ok(Literal::BOOL(true)),
-            (TokenType::BANG, _) => Ok(Literal::BOOL(false)),
-            (_, lit) => Err(
+        let member = g.name.value.clone().unwrap_or_default();
+
+        match &object {
+            Literal::MODULE(module) => match module.get(&member) {
+                Some(value) => Ok(value.clone()),
+                None => Err(
+                    EvaluationError::new_import(
+                        format!("Module '{}' has no member '{}' (line {})", module.name, member, g.name.line)
+                    )
+                ),
+            },
+            _ => Err(
                 EvaluationError::new_unary(
-                    "Invalid operation on unary operand.".to_string(),
-                    lit
+                    format!("Only modules have members, cannot read '{}'", member),
+                    object,
                 )
             ),
         }
     }
 
+    fn visit_format(&mut self, f: &Format) -> Result<Literal, EvaluationError> {
+        let mut formatted = String::new();
+
+        for part in &f.parts {
+            match self.visit_expr(part) {
+                Ok(lit) => formatted.push_str(&lit.to_string()),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(Literal::STRING(formatted))
+    }
+
+    fn visit_unary(&mut self, u: &Unary) -> Result<Literal, EvaluationError> {
+        let r = match self.visit_expr(&u.rhs) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
+
+        self.options.unary_op(&u.op.kind, r)
+    }
+
     fn visit_binary(&mut self, b: &Binary) -> Result<Literal, EvaluationError> {
 
         let l = match self.visit_expr(&b.lhs) {
@@ -97,57 +1150,35 @@ impl ExprVisitor<Result<Literal, EvaluationError>> for Interpreter {
             Err(e) => return Err(e)
         };
 
-        match (b.op.kind.clone(), l, r) {
-            (TokenType::MINUS, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::NUMBER(lval - rval)),
-            (TokenType::PLUS, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::NUMBER(lval + rval)),
-            (TokenType::PLUS, Literal::STRING(lval), Literal::STRING(rval)) => {
-                let mut appended = lval.clone();
-                appended.push_str(&rval);
-                Ok(Literal::STRING(appended))
-            },
-            (TokenType::PLUS, Literal::STRING(lval), Literal::NUMBER(rval)) => {
-                let mut appended = lval.clone();
-                appended.push_str(&rval.to_string());
-                Ok(Literal::STRING(appended))
-            },
-            (TokenType::PLUS, Literal::NUMBER(lval), Literal::STRING(rval)) => {
-                let mut appended = lval.to_string();
-                appended.push_str(&rval);
-                Ok(Literal::STRING(appended))
-            },
-            (TokenType::SLASH, Literal::NUMBER(lval), Literal::NUMBER(rval)) => {
-                
-                if rval == 0.0 {
-                    return Err(
-                        EvaluationError::new_binary(
-                            "Division by zero".to_string(),
-                            Literal::NUMBER(lval),
-                            Literal::NUMBER(rval),
-                        )
-                    )
-                }
-                Ok(Literal::NUMBER(lval / rval))
-            },
-            (TokenType::STAR, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::NUMBER(lval * rval)),
-            (TokenType::GREATER, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::BOOL(lval > rval)),
-            (TokenType::GREATEREQUAL, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::BOOL(lval >= rval)),
-            (TokenType::LESS, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::BOOL(lval < rval)),
-            (TokenType::LESSEQUAL, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::BOOL(lval <= rval)),
-            (TokenType::BANGEQUAL, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::BOOL(lval != rval)),
-            (TokenType::BANGEQUAL, Literal::NIL, Literal::NIL) => Ok(Literal::BOOL(false)),
-            (TokenType::BANGEQUAL, Literal::NUMBER(_), Literal::NIL) => Ok(Literal::BOOL(true)),
-            (TokenType::BANGEQUAL, Literal::NIL, Literal::NUMBER(_)) => Ok(Literal::BOOL(true)),
-            (TokenType::EQUALEQUAL, Literal::NUMBER(lval), Literal::NUMBER(rval)) => Ok(Literal::BOOL(lval == rval)),
-            (TokenType::EQUALEQUAL, Literal::NIL, Literal::NIL) => Ok(Literal::BOOL(true)),
-            (TokenType::EQUALEQUAL, Literal::NUMBER(_), Literal::NIL) => Ok(Literal::BOOL(false)),
-            (TokenType::EQUALEQUAL, Literal::NIL, Literal::NUMBER(_)) => Ok(Literal::BOOL(false)),
-            (_, lit1, lit2) => return Err(
-                EvaluationError::new_binary(
-                    "Invalid operation on binary operand".to_string(),
-                    lit1,
-                    lit2,
-                )
-            ),
+        self.options.binary_op(&b.op.kind, l, r)
+    }
+
+    // The value is the operand that decides, not a boolean: `nil or "default"`
+    fn visit_logical(&mut self, l: &Logical) -> Result<Literal, EvaluationError> {
+
+        let lhs = match self.visit_expr(&l.lhs) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
+
+        let truthy = match self.options.condition(&lhs) {
+            Ok(truthy) => truthy,
+            Err(e) => return Err(e)
+        };
+
+        let rhs_taken = match l.op.kind {
+            TokenType::OR => !truthy,
+            _ => truthy,
+        };
+
+        for hook in self.hooks.iter_mut() {
+            hook.on_logical(l, rhs_taken);
+        }
+
+        if rhs_taken {
+            self.visit_expr(&l.rhs)
+        } else {
+            Ok(lhs)
         }
     }
 
@@ -173,9 +1204,19 @@ impl ExprVisitor<Result<Literal, EvaluationError>> for Interpreter {
             Err(e) => return Err(e)
         };
 
-        match cond {
-            Literal::BOOL(false) | Literal::NIL => self.visit_expr(&t.else_expr),
-            _ => self.visit_expr(&t.then_expr),
+        let then_taken = match self.options.condition(&cond) {
+            Ok(taken) => taken,
+            Err(e) => return Err(e)
+        };
+
+        for hook in self.hooks.iter_mut() {
+            hook.on_ternary(t, then_taken);
+        }
+
+        if then_taken {
+            self.visit_expr(&t.then_expr)
+        } else {
+            self.visit_expr(&t.else_expr)
         }
     }
 
@@ -192,7 +1233,12 @@ impl ExprVisitor<Result<Literal, EvaluationError>> for Interpreter {
             ),
         };
 
-        Ok(self.environment.get(&name).clone())
+        match self.environment.get(&name) {
+            Some(value) => Ok(value),
+            None => Err(
+                EvaluationError::new_var(format!("Undefined variable '{}'", name))
+            ),
+        }
     }
 
     fn visit_assign(&mut self, a: &Assign) -> Result<Literal, EvaluationError> {
@@ -201,30 +1247,25 @@ impl ExprVisitor<Result<Literal, EvaluationError>> for Interpreter {
             Err(err) => return Err(err),
         };
 
-        let name = match a.name.value {
-            Some(ref n) => n.clone(),
-            None => return Err(
-                EvaluationError::new_var("No name in token defined for variable".to_string())
-            ),
-        };
-
-        self.environment.assign(
-            name,
-            value.clone()
-        );
-
-        Ok(value)
+        self.assign_variable(&a.name, value)
     }
 }
 
 impl StmtVisitor<Result<Literal, EvaluationError>> for Interpreter  {
     fn visit_stmt(&mut self, s: &Stmt) -> Result<Literal, EvaluationError> {
-        return match s {
-            Stmt::BLOCK(b) => self.visit_block(&b),
-            Stmt::EXPRESSION(e) => self.visit_expression(&e),
-            Stmt::PRINT(p) => self.visit_print(&p),
-            Stmt::VAR(v) => self.visit_var(&v)
-        }        
+        for hook in self.hooks.iter_mut() {
+            hook.before_stmt(s, &self.environment);
+        }
+
+        let result = self.sandbox.step()
+            .and_then(|_| self.execute(s))
+            .map_err(|e| e.at_line(s.line()));
+
+        for hook in self.hooks.iter_mut() {
+            hook.after_stmt(s, &self.environment);
+        }
+
+        result
     }
 
     fn visit_expression(&mut self, e: &Expression) -> Result<Literal, EvaluationError> {
@@ -234,7 +1275,7 @@ impl StmtVisitor<Result<Literal, EvaluationError>> for Interpreter  {
     fn visit_print(&mut self, p: &Print) -> Result<Literal, EvaluationError> {
         match self.visit_expr(&p.expr) {
             Ok(lit) => {
-                println!("{}", lit.to_string());
+                let _ = writeln!(self.output, "{}", lit.to_string());
                 return Ok(Literal::NIL);
             },
             Err(e) => return Err(e),
@@ -258,10 +1299,11 @@ impl StmtVisitor<Result<Literal, EvaluationError>> for Interpreter  {
                     };
 
 
-                    self.environment.define(
-                        name,
-                        value
-                    )
+                    if v.constant {
+                        self.environment.define_constant(name, value)
+                    } else {
+                        self.environment.define(name, value)
+                    }
                 },
                 None => self.environment.define(name, Literal::NIL),
         }
@@ -270,6 +1312,206 @@ impl StmtVisitor<Result<Literal, EvaluationError>> for Interpreter  {
     }
 
     fn visit_block(&mut self, b: &Block) -> Result<Literal, EvaluationError> {
-        unimplemented!()
+        self.execute_block(&b.statements)
+    }
+
+    fn visit_if(&mut self, i: &If) -> Result<Literal, EvaluationError> {
+        let cond = match self.visit_expr(&i.cond) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
+
+        let then_taken = match self.options.condition(&cond) {
+            Ok(taken) => taken,
+            Err(e) => return Err(e)
+        };
+
+        for hook in self.hooks.iter_mut() {
+            hook.on_if(i, then_taken);
+        }
+
+        if then_taken {
+            return self.visit_stmt(&i.then_branch);
+        }
+
+        match &i.else_branch {
+            Some(stmt) => self.visit_stmt(&stmt),
+            None => Ok(Literal::NIL),
+        }
+    }
+
+    fn visit_while(&mut self, w: &While) -> Result<Literal, EvaluationError> {
+        loop {
+            let cond = match self.visit_expr(&w.cond) {
+                Ok(lit) => lit,
+                Err(e) => return Err(e)
+            };
+
+            match self.options.condition(&cond) {
+                Ok(true) => (),
+                Ok(false) => break,
+                Err(e) => return Err(e),
+            }
+
+            match self.execute_body(&w.body) {
+                Ok(true) => break,
+                Ok(false) => (),
+                Err(e) => return Err(e),
+            };
+
+            // Reached after a `continue` too
+            if let Some(expr) = &w.increment {
+                if let Err(e) = self.visit_expr(&expr) {
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(Literal::NIL)
+    }
+
+    fn visit_for_in(&mut self, f: &ForIn) -> Result<Literal, EvaluationError> {
+        let name = match f.name.value {
+            Some(ref n) => n.clone(),
+            None => return Err(
+                EvaluationError::new_var("No name in token defined for variable".to_string())
+            ),
+        };
+
+        let iterable = match self.visit_expr(&f.iterable) {
+            Ok(lit) => lit,
+            Err(e) => return Err(e)
+        };
+
+        // The items are taken before the loop, so changing the container inside is safe
+        let items: Vec<Literal> = match &iterable {
+            Literal::LIST(list) => list.borrow().clone(),
+            Literal::MAP(map) => map.borrow().keys(),
+            Literal::STRING(val) => val.chars().map(|c| Literal::STRING(c.to_string())).collect(),
+            _ => return Err(
+                EvaluationError::new_unary(
+                    "Only lists, maps and strings can be iterated".to_string(),
+                    iterable,
+                )
+            ),
+        };
+
+        for item in items {
+            self.push_scope();
+            self.environment.define(name.clone(), item);
+            let result = self.execute_body(&f.body);
+            self.pop_scope();
+
+            match result {
+                Ok(true) => break,
+                Ok(false) => (),
+                Err(e) => return Err(e),
+            };
+        }
+
+        Ok(Literal::NIL)
+    }
+
+    fn visit_import(&mut self, i: &Import) -> Result<Literal, EvaluationError> {
+        // Checked before resolving, a missing file must not tell apart from a denied one
+        if !self.capabilities.allows(Capability::FS) {
+            return Err(EvaluationError::new_call(format!("import requires the '{}' capability", Capability::FS)));
+        }
+
+        let file = i.path.value.clone().unwrap_or_default();
+
+        let path = match self.modules.resolve(&file) {
+            Ok(path) => path,
+            Err(msg) => return Err(
+                EvaluationError::new_import(format!("{} (line {})", msg, i.keyword.line))
+            ),
+        };
+
+        // Without `as` the namespace takes the name of the file
+        let name = match &i.alias {
+            Some(alias) => alias.value.clone().unwrap_or_default(),
+            None => path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+        };
+
+        let module = match self.modules.cached(&path) {
+            Some(module) => module,
+            None => {
+                if let Err(msg) = self.modules.begin(&path) {
+                    return Err(
+                        EvaluationError::new_import(format!("{} (line {})", msg, i.keyword.line))
+                    );
+                }
+
+                match self.load_module(&path) {
+                    Ok(module) => {
+                        let module = Rc::new(module);
+                        self.modules.end(Some(module.clone()));
+                        module
+                    },
+                    Err(e) => {
+                        self.modules.end(None);
+                        return Err(e);
+                    },
+                }
+            },
+        };
+
+        self.environment.define(name, Literal::MODULE(module));
+        Ok(Literal::NIL)
+    }
+
+    fn visit_return(&mut self, r: &Return) -> Result<Literal, EvaluationError> {
+        let value = match &r.value {
+            Some(expr) => match self.visit_expr(&expr) {
+                Ok(lit) => lit,
+                Err(e) => return Err(e),
+            },
+            None => Literal::NIL,
+        };
+
+        Err(EvaluationError::new_return(value))
+    }
+
+    fn visit_throw(&mut self, t: &Throw) -> Result<Literal, EvaluationError> {
+        match self.visit_expr(&t.value) {
+            Ok(value) => Err(EvaluationError::new_throw(value)),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn visit_try(&mut self, t: &Try) -> Result<Literal, EvaluationError> {
+        let mut result = self.execute_block(&t.body);
+
+        if let (Err(e), Some(name), Some(catch_body)) = (&result, &t.catch_name, &t.catch_body) {
+            if !e.is_control_flow() && !e.is_limit() {
+                let value = Interpreter::caught_value(e);
+
+                self.push_scope();
+                self.environment.define(name.value.clone().unwrap_or_default(), value);
+                result = self.execute_block(catch_body);
+                self.pop_scope();
+            }
+        }
+
+        // An error in the finally replaces the one being unwound, except a limit reached,
+        // the script can't escape it with a `return` or a `break` in the finally
+        if let Some(finally_body) = &t.finally_body {
+            if let Err(e) = self.execute_block(finally_body) {
+                return match result {
+                    Err(limit) if limit.is_limit() => Err(limit),
+                    _ => Err(e),
+                };
+            }
+        }
+
+        result.map(|_| Literal::NIL)
+    }
+
+    fn visit_break(&mut self, _b: &Break) -> Result<Literal, EvaluationError> {
+        Err(EvaluationError::new_break())
+    }
+
+    fn visit_continue(&mut self, _c: &Continue) -> Result<Literal, EvaluationError> {
+        Err(EvaluationError::new_continue())
     }
 }
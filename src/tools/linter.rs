@@ -0,0 +1,548 @@
+/* -------------------------------------------------------------------------------------
+*
+*   Lint pass for the Language Lox
+*
+*   Walks the AST without running it, looking for code that is valid but likely a
+*   mistake. Each lint has a severity (allow, warning or error) that can be changed from
+*   the command line, the lints at `allow` are not reported.
+*
+*   Lints:
+*       - unused-variable: a local `var` or `const` that is never read;
+*       - unused-parameter: a lambda parameter that is never read;
+*       - shadowing: a local that hides a variable of an outer scope (allowed by default,
+*         as shadowing is part of the language);
+*       - unreachable-code: statements after a `return`, `break`, `continue` or `throw`;
+*       - constant-condition: a ternary whose condition is a literal;
+*       - mixed-comparison: `==` or `!=` between literals of different types.
+*
+*   Globals are not reported as unused, since they can be read by the next lines of the
+*   prompt or by the scripts importing them.
+*
+* ------------------------------------------------------------------------------------- */
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use crate::visit_expr::ExprVisitor;
+use crate::visit_stmt::StmtVisitor;
+use crate::ast_expr::{Unary, Binary, Logical, Grouping, Expr, Literal, Comma, Ternary, Variable, Assign};
+use crate::ast_expr::{List, Map, Index, Slice, SetIndex, Compound, Call, Get, Format, Lambda, Match, Pattern};
+use crate::ast_expr::TYPE_PATTERNS;
+use crate::ast_stmt::{Stmt, Expression, Print, Var, Block, If, While, ForIn, Break, Continue, Import};
+use crate::ast_stmt::{Throw, Try, Return};
+use crate::token::Token;
+use crate::token_type::TokenType;
+use crate::interpreter::Interpreter;
+use crate::scanner::Scanner;
+use crate::parser::Parser;
+use crate::utils;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Severity {
+    ALLOW,
+    WARNING,
+    ERROR,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::ALLOW => write!(f, "allow"),
+            Severity::WARNING => write!(f, "warning"),
+            Severity::ERROR => write!(f, "error"),
+        }
+    }
+}
+
+// Name and default severity of each lint
+pub const LINTS: [(&str, Severity); 6] = [
+    ("unused-variable", Severity::WARNING),
+    ("unused-parameter", Severity::WARNING),
+    ("shadowing", Severity::ALLOW),
+    ("unreachable-code", Severity::WARNING),
+    ("constant-condition", Severity::WARNING),
+    ("mixed-comparison", Severity::WARNING),
+];
+
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub lint: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub line: usize,
+}
+
+impl Diagnostic {
+    pub fn new(lint: &'static str, severity: Severity, message: String, line: usize) -> Self {
+        Diagnostic {
+            lint,
+            severity,
+            message,
+            line,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "lint": self.lint,
+            "severity": self.severity.to_string(),
+            "message": self.message,
+            "line": self.line,
+        })
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}: {} [{}]", self.line, self.severity, self.message, self.lint)
+    }
+}
+
+// A name in scope, the ones reported if never read keep the lint and their declaration
+struct Local {
+    unused: Option<(&'static str, Token)>,
+    used: bool,
+}
+
+pub struct Linter {
+    severities: HashMap<&'static str, Severity>,
+    scopes: Vec<HashMap<String, Local>>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter {
+    pub fn new() -> Self {
+        Linter {
+            severities: LINTS.iter().cloned().collect(),
+            scopes: vec![HashMap::new()],
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn set_severity(&mut self, lint: &str, severity: Severity) -> Result<(), String> {
+        match LINTS.iter().find(|(name, _)| *name == lint) {
+            Some((name, _)) => {
+                self.severities.insert(name, severity);
+                Ok(())
+            },
+            None => Err(format!("Unknown lint '{}'", lint)),
+        }
+    }
+
+    // Lints a script and prints what was found, returns whether there were errors
+    pub fn run(&mut self, filename: &PathBuf, json: bool) -> bool {
+        let mut scanner = Scanner::new(utils::read_file(filename));
+        let mut parser = Parser::new(scanner.scan_tokens().clone());
+        let statements = parser.parse();
+
+        if scanner.errors.is_empty() && parser.errors.is_empty() {
+            self.lint(&statements);
+        } else {
+            for err in &scanner.errors {
+                self.diagnostics.push(Diagnostic::new("syntax", Severity::ERROR, err.to_string(), err.line));
+            }
+            for err in &parser.errors {
+                self.diagnostics.push(Diagnostic::new("syntax", Severity::ERROR, err.to_string(), err.token.line));
+            }
+        }
+
+        let path = filename.display().to_string();
+        if json {
+            let diagnostics: Vec<Value> = self.diagnostics.iter().map(|d| d.to_json()).collect();
+            println!("{}", json!({ "file": path, "diagnostics": diagnostics }));
+        } else {
+            for diagnostic in &self.diagnostics {
+                println!("{}:{}", path, diagnostic);
+            }
+        }
+
+        self.diagnostics.iter().any(|d| d.severity == Severity::ERROR)
+    }
+
+    pub fn lint(&mut self, statements: &[Stmt]) {
+        self.visit_statements(statements);
+        // Scopes are hash maps, so the diagnostics of a line are sorted to keep a stable order
+        self.diagnostics.sort_by(|a, b| (a.line, a.lint, &a.message).cmp(&(b.line, b.lint, &b.message)));
+    }
+
+    fn report(&mut self, lint: &'static str, message: String, line: usize) {
+        let severity = self.severities.get(lint).cloned().unwrap_or(Severity::WARNING);
+        if severity != Severity::ALLOW {
+            self.diagnostics.push(Diagnostic::new(lint, severity, message, line));
+        }
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn pop_scope(&mut self) {
+        if let Some(scope) = self.scopes.pop() {
+            for local in scope.into_values().filter(|local| !local.used) {
+                if let Some((lint, name)) = local.unused {
+                    let kind = if lint == "unused-parameter" { "Parameter" } else { "Variable" };
+                    self.report(
+                        lint,
+                        format!("{} '{}' is never used", kind, name.value.clone().unwrap_or_default()),
+                        name.line,
+                    );
+                }
+            }
+        }
+    }
+
+    // Declares a name in the innermost scope, `unused` is the lint reported if it is never read
+    fn declare(&mut self, name: &Token, unused: Option<&'static str>) {
+        let key = name.value.clone().unwrap_or_default();
+        let depth = self.scopes.len();
+
+        let shadows = depth > 1 && self.scopes[..depth - 1].iter().any(|scope| scope.contains_key(&key));
+        if shadows {
+            self.report("shadowing", format!("'{}' shadows a variable of an outer scope", key), name.line);
+        }
+
+        let local = Local {
+            // Globals can be read from outside the script
+            unused: if depth > 1 { unused.map(|lint| (lint, name.clone())) } else { None },
+            used: false,
+        };
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(key, local);
+        }
+    }
+
+    fn mark_used(&mut self, name: &Token) {
+        let key = name.value.clone().unwrap_or_default();
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(local) = scope.get_mut(&key) {
+                local.used = true;
+                return;
+            }
+        }
+    }
+
+    // Lints the statements of a block, reporting the first one after a jump
+    fn visit_statements(&mut self, statements: &[Stmt]) {
+        let mut jump: Option<String> = None;
+
+        for stmt in statements {
+            if let Some(keyword) = jump.take() {
+                self.report("unreachable-code", format!("Unreachable code after '{}'", keyword), stmt.line());
+            }
+
+            self.visit_stmt(stmt);
+
+            if let Some(keyword) = Linter::jumps(stmt) {
+                jump = Some(keyword);
+            }
+        }
+    }
+
+    // The keyword that always leaves the statement, if any
+    fn jumps(stmt: &Stmt) -> Option<String> {
+        match stmt {
+            Stmt::RETURN(r) => Some(r.keyword.get_lexeme().to_lowercase()),
+            Stmt::BREAK(b) => Some(b.keyword.get_lexeme().to_lowercase()),
+            Stmt::CONTINUE(c) => Some(c.keyword.get_lexeme().to_lowercase()),
+            Stmt::THROW(t) => Some(t.keyword.get_lexeme().to_lowercase()),
+            Stmt::BLOCK(b) => b.statements.iter().find_map(Linter::jumps),
+            Stmt::IF(i) => match &i.else_branch {
+                Some(else_branch) => Linter::jumps(&i.then_branch).and(Linter::jumps(else_branch)),
+                None => None,
+            },
+            _ => None,
+        }
+    }
+
+    // The literal an expression always is, looking through the parentheses
+    fn constant(e: &Expr) -> Option<&Literal> {
+        match e {
+            Expr::LITERAL(lit) => Some(lit),
+            Expr::GROUPING(g) => Linter::constant(&g.expr),
+            _ => None,
+        }
+    }
+
+    fn type_name(lit: &Literal) -> &'static str {
+        match lit {
+            Literal::NIL => "nil",
+            Literal::MODULE(_) => "module",
+            other => TYPE_PATTERNS.iter().find(|name| other.is_type(name)).cloned().unwrap_or("any"),
+        }
+    }
+}
+
+impl ExprVisitor<()> for Linter {
+    fn visit_expr(&mut self, e: &Expr) {
+        match e {
+            Expr::UNARY(u) => self.visit_unary(u),
+            Expr::BINARY(b) => self.visit_binary(b),
+            Expr::LOGICAL(l) => self.visit_logical(l),
+            Expr::ASSIGN(a) => self.visit_assign(a),
+            Expr::GROUPING(g) => self.visit_grouping(g),
+            Expr::LITERAL(l) => self.visit_literal(l),
+            Expr::COMMA(c) => self.visit_comma(c),
+            Expr::TERNARY(t) => self.visit_ternary(t),
+            Expr::VARIABLE(v) => self.visit_variable(v),
+            Expr::LIST(l) => self.visit_list(l),
+            Expr::MAP(m) => self.visit_map(m),
+            Expr::INDEX(i) => self.visit_index(i),
+            Expr::SLICE(s) => self.visit_slice(s),
+            Expr::SETINDEX(s) => self.visit_set_index(s),
+            Expr::COMPOUND(c) => self.visit_compound(c),
+            Expr::CALL(c) => self.visit_call(c),
+            Expr::GET(g) => self.visit_get(g),
+            Expr::FORMAT(f) => self.visit_format(f),
+            Expr::LAMBDA(l) => self.visit_lambda(l),
+            Expr::MATCH(m) => self.visit_match(m),
+        }
+    }
+
+    fn visit_comma(&mut self, c: &Comma) {
+        self.visit_expr(&c.lhs);
+        self.visit_expr(&c.rhs);
+    }
+
+    fn visit_ternary(&mut self, t: &Ternary) {
+        if let Some(lit) = Linter::constant(&t.cond) {
+            self.report(
+                "constant-condition",
+                format!("Condition of the ternary is always {}", Interpreter::is_truthy(lit)),
+                t.line,
+            );
+        }
+
+        self.visit_expr(&t.cond);
+        self.visit_expr(&t.then_expr);
+        self.visit_expr(&t.else_expr);
+    }
+
+    fn visit_unary(&mut self, u: &Unary) {
+        self.visit_expr(&u.rhs);
+    }
+
+    fn visit_binary(&mut self, b: &Binary) {
+        if matches!(b.op.kind, TokenType::EQUALEQUAL | TokenType::BANGEQUAL) {
+            if let (Some(lhs), Some(rhs)) = (Linter::constant(&b.lhs), Linter::constant(&b.rhs)) {
+                let (lhs, rhs) = (Linter::type_name(lhs), Linter::type_name(rhs));
+                if lhs != rhs {
+                    self.report(
+                        "mixed-comparison",
+                        format!("Comparing {} with {}", lhs, rhs),
+                        b.op.line,
+                    );
+                }
+            }
+        }
+
+        self.visit_expr(&b.lhs);
+        self.visit_expr(&b.rhs);
+    }
+
+    fn visit_logical(&mut self, l: &Logical) {
+        self.visit_expr(&l.lhs);
+        self.visit_expr(&l.rhs);
+    }
+
+    fn visit_grouping(&mut self, g: &Grouping) {
+        self.visit_expr(&g.expr);
+    }
+
+    fn visit_literal(&mut self, _l: &Literal) {}
+
+    fn visit_variable(&mut self, v: &Variable) {
+        self.mark_used(&v.name);
+    }
+
+    // Storing into a variable is not a use of it
+    fn visit_assign(&mut self, a: &Assign) {
+        self.visit_expr(&a.value);
+    }
+
+    fn visit_list(&mut self, l: &List) {
+        for element in &l.elements {
+            self.visit_expr(element);
+        }
+    }
+
+    fn visit_map(&mut self, m: &Map) {
+        for (key, value) in &m.entries {
+            self.visit_expr(key);
+            self.visit_expr(value);
+        }
+    }
+
+    fn visit_index(&mut self, i: &Index) {
+        self.visit_expr(&i.object);
+        self.visit_expr(&i.index);
+    }
+
+    fn visit_slice(&mut self, s: &Slice) {
+        self.visit_expr(&s.object);
+        if let Some(start) = &s.start {
+            self.visit_expr(start);
+        }
+        if let Some(end) = &s.end {
+            self.visit_expr(end);
+        }
+    }
+
+    fn visit_set_index(&mut self, s: &SetIndex) {
+        self.visit_expr(&s.object);
+        self.visit_expr(&s.index);
+        self.visit_expr(&s.value);
+    }
+
+    fn visit_compound(&mut self, c: &Compound) {
+        self.visit_expr(&c.target);
+        self.visit_expr(&c.value);
+    }
+
+    fn visit_call(&mut self, c: &Call) {
+        self.visit_expr(&c.callee);
+        for argument in &c.arguments {
+            self.visit_expr(argument);
+        }
+    }
+
+    fn visit_get(&mut self, g: &Get) {
+        self.visit_expr(&g.object);
+    }
+
+    fn visit_format(&mut self, f: &Format) {
+        for part in &f.parts {
+            self.visit_expr(part);
+        }
+    }
+
+    fn visit_match(&mut self, m: &Match) {
+        self.visit_expr(&m.subject);
+
+        for arm in &m.arms {
+            self.push_scope();
+            if let Some(Pattern::BINDING(name)) = arm.patterns.first() {
+                self.declare(name, None);
+            }
+            if let Some(guard) = &arm.guard {
+                self.visit_expr(guard);
+            }
+            self.visit_expr(&arm.body);
+            self.pop_scope();
+        }
+    }
+
+    fn visit_lambda(&mut self, l: &Lambda) {
+        self.push_scope();
+        for param in &l.params {
+            self.declare(param, Some("unused-parameter"));
+        }
+        self.visit_statements(&l.body);
+        self.pop_scope();
+    }
+}
+
+impl StmtVisitor<()> for Linter {
+    fn visit_stmt(&mut self, s: &Stmt) {
+        match s {
+            Stmt::BLOCK(b) => self.visit_block(b),
+            Stmt::EXPRESSION(e) => self.visit_expression(e),
+            Stmt::PRINT(p) => self.visit_print(p),
+            Stmt::VAR(v) => self.visit_var(v),
+            Stmt::IF(i) => self.visit_if(i),
+            Stmt::WHILE(w) => self.visit_while(w),
+            Stmt::FORIN(f) => self.visit_for_in(f),
+            Stmt::BREAK(b) => self.visit_break(b),
+            Stmt::CONTINUE(c) => self.visit_continue(c),
+            Stmt::IMPORT(i) => self.visit_import(i),
+            Stmt::THROW(t) => self.visit_throw(t),
+            Stmt::TRY(t) => self.visit_try(t),
+            Stmt::RETURN(r) => self.visit_return(r),
+        }
+    }
+
+    fn visit_expression(&mut self, e: &Expression) {
+        self.visit_expr(&e.expr);
+    }
+
+    fn visit_print(&mut self, p: &Print) {
+        self.visit_expr(&p.expr);
+    }
+
+    fn visit_var(&mut self, v: &Var) {
+        if let Some(expr) = &v.initializer {
+            self.visit_expr(expr);
+        }
+        self.declare(&v.name, Some("unused-variable"));
+    }
+
+    fn visit_block(&mut self, b: &Block) {
+        self.push_scope();
+        self.visit_statements(&b.statements);
+        self.pop_scope();
+    }
+
+    fn visit_if(&mut self, i: &If) {
+        self.visit_expr(&i.cond);
+        self.visit_stmt(&i.then_branch);
+        if let Some(stmt) = &i.else_branch {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn visit_while(&mut self, w: &While) {
+        self.visit_expr(&w.cond);
+        self.visit_stmt(&w.body);
+        if let Some(expr) = &w.increment {
+            self.visit_expr(expr);
+        }
+    }
+
+    // Loop variables are often only there to repeat the body, so they are not reported
+    fn visit_for_in(&mut self, f: &ForIn) {
+        self.visit_expr(&f.iterable);
+
+        self.push_scope();
+        self.declare(&f.name, None);
+        self.visit_stmt(&f.body);
+        self.pop_scope();
+    }
+
+    fn visit_break(&mut self, _b: &Break) {}
+
+    fn visit_continue(&mut self, _c: &Continue) {}
+
+    fn visit_return(&mut self, r: &Return) {
+        if let Some(expr) = &r.value {
+            self.visit_expr(expr);
+        }
+    }
+
+    fn visit_throw(&mut self, t: &Throw) {
+        self.visit_expr(&t.value);
+    }
+
+    fn visit_try(&mut self, t: &Try) {
+        self.visit_block(&Block::new(t.body.clone(), t.keyword.line));
+
+        if let (Some(name), Some(body)) = (&t.catch_name, &t.catch_body) {
+            self.push_scope();
+            self.declare(name, None);
+            self.visit_block(&Block::new(body.clone(), name.line));
+            self.pop_scope();
+        }
+
+        if let Some(body) = &t.finally_body {
+            self.visit_block(&Block::new(body.clone(), t.keyword.line));
+        }
+    }
+
+    fn visit_import(&mut self, i: &Import) {
+        if let Some(alias) = &i.alias {
+            self.declare(alias, None);
+        }
+    }
+}
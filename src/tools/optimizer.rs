@@ -0,0 +1,380 @@
+/* -------------------------------------------------------------------------------------
+*
+*   AST Optimizer for the Language Lox
+*
+*   Rewrites the AST before the execution, enabled with `-O`. Each visit returns the
+*   simplified node, so the folding happens bottom-up.
+*
+*   Simplifications:
+*       - Unary and binary operations over literals are folded into a literal;
+*       - Ternaries with a literal condition are replaced by the branch taken;
+*       - `and`/`or` with a literal left operand are replaced by the operand they give;
+*       - Ifs with a literal condition are replaced by the branch taken (or an empty block);
+*       - Groupings are collapsed into the grouped expression;
+*       - Interpolated strings with only literal parts are joined into a string;
+*       - The left side of a comma is dropped when it is a literal.
+*
+*   Folding uses the same operations and options as the interpreter, and an operation
+*   that fails (like a division by zero, or a number added to a string in strict mode) is
+*   kept unfolded, so the error still happens at runtime.
+*
+* ------------------------------------------------------------------------------------- */
+
+use crate::visit_expr::ExprVisitor;
+use crate::visit_stmt::StmtVisitor;
+use crate::ast_expr::{Unary, Binary, Logical, Grouping, Expr, Literal, Comma, Ternary, Variable, Assign};
+use crate::ast_expr::{List, Map, Index, Slice, SetIndex, Compound, Call, Get, Format, Lambda, Match, MatchArm};
+use crate::ast_stmt::{Stmt, Expression, Print, Var, Block, If, While, ForIn, Break, Continue, Import};
+use crate::ast_stmt::{Throw, Try, Return};
+use crate::interpreter::InterpreterOptions;
+use crate::token_type::TokenType;
+
+pub struct Optimizer {
+    options: InterpreterOptions,
+}
+
+impl Optimizer {
+    pub fn new(options: InterpreterOptions) -> Self {
+        Optimizer {
+            options,
+        }
+    }
+
+    pub fn optimize(&mut self, statements: &[Stmt]) -> Vec<Stmt> {
+        statements.iter().map(|stmt| self.visit_stmt(stmt)).collect()
+    }
+}
+
+impl ExprVisitor<Expr> for Optimizer {
+    fn visit_expr(&mut self, e: &Expr) -> Expr {
+        match e {
+            Expr::UNARY(u) => self.visit_unary(u),
+            Expr::BINARY(b) => self.visit_binary(b),
+            Expr::LOGICAL(l) => self.visit_logical(l),
+            Expr::ASSIGN(a) => self.visit_assign(a),
+            Expr::GROUPING(g) => self.visit_grouping(g),
+            Expr::LITERAL(l) => self.visit_literal(l),
+            Expr::COMMA(c) => self.visit_comma(c),
+            Expr::TERNARY(t) => self.visit_ternary(t),
+            Expr::VARIABLE(v) => self.visit_variable(v),
+            Expr::LIST(l) => self.visit_list(l),
+            Expr::MAP(m) => self.visit_map(m),
+            Expr::INDEX(i) => self.visit_index(i),
+            Expr::SLICE(s) => self.visit_slice(s),
+            Expr::SETINDEX(s) => self.visit_set_index(s),
+            Expr::COMPOUND(c) => self.visit_compound(c),
+            Expr::CALL(c) => self.visit_call(c),
+            Expr::GET(g) => self.visit_get(g),
+            Expr::FORMAT(f) => self.visit_format(f),
+            Expr::LAMBDA(l) => self.visit_lambda(l),
+            Expr::MATCH(m) => self.visit_match(m),
+        }
+    }
+
+    fn visit_comma(&mut self, c: &Comma) -> Expr {
+        let lhs = self.visit_expr(&c.lhs);
+        let rhs = self.visit_expr(&c.rhs);
+
+        match lhs {
+            Expr::LITERAL(_) => rhs,
+            _ => Expr::COMMA(Comma::new(Box::new(lhs), Box::new(rhs))),
+        }
+    }
+
+    fn visit_ternary(&mut self, t: &Ternary) -> Expr {
+        let cond = self.visit_expr(&t.cond);
+        let then_expr = self.visit_expr(&t.then_expr);
+        let else_expr = self.visit_expr(&t.else_expr);
+
+        match &cond {
+            Expr::LITERAL(lit) if matches!(self.options.condition(lit), Ok(true)) => then_expr,
+            Expr::LITERAL(lit) if matches!(self.options.condition(lit), Ok(false)) => else_expr,
+            _ => Expr::TERNARY(
+                Ternary::new(
+                    Box::new(cond),
+                    Box::new(then_expr),
+                    Box::new(else_expr),
+                    t.line,
+                )
+            ),
+        }
+    }
+
+    fn visit_assign(&mut self, a: &Assign) -> Expr {
+        Expr::ASSIGN(
+            Assign::new(
+                a.name.clone(),
+                Box::new(self.visit_expr(&a.value)),
+            )
+        )
+    }
+
+    fn visit_literal(&mut self, l: &Literal) -> Expr {
+        Expr::LITERAL(l.clone())
+    }
+
+    fn visit_unary(&mut self, u: &Unary) -> Expr {
+        let rhs = self.visit_expr(&u.rhs);
+
+        if let Expr::LITERAL(lit) = &rhs {
+            if let Ok(folded) = self.options.unary_op(&u.op.kind, lit.clone()) {
+                return Expr::LITERAL(folded);
+            }
+        }
+
+        Expr::UNARY(Unary::new(u.op.clone(), Box::new(rhs)))
+    }
+
+    fn visit_binary(&mut self, b: &Binary) -> Expr {
+        let lhs = self.visit_expr(&b.lhs);
+        let rhs = self.visit_expr(&b.rhs);
+
+        if let (Expr::LITERAL(l), Expr::LITERAL(r)) = (&lhs, &rhs) {
+            if let Ok(folded) = self.options.binary_op(&b.op.kind, l.clone(), r.clone()) {
+                return Expr::LITERAL(folded);
+            }
+        }
+
+        Expr::BINARY(Binary::new(b.op.clone(), Box::new(lhs), Box::new(rhs)))
+    }
+
+    // A constant left operand decides whether the right one is kept
+    fn visit_logical(&mut self, l: &Logical) -> Expr {
+        let lhs = self.visit_expr(&l.lhs);
+        let rhs = self.visit_expr(&l.rhs);
+
+        if let Expr::LITERAL(lit) = &lhs {
+            if let Ok(truthy) = self.options.condition(lit) {
+                let rhs_taken = match l.op.kind {
+                    TokenType::OR => !truthy,
+                    _ => truthy,
+                };
+                return if rhs_taken { rhs } else { lhs };
+            }
+        }
+
+        Expr::LOGICAL(Logical::new(l.op.clone(), Box::new(lhs), Box::new(rhs)))
+    }
+
+    fn visit_grouping(&mut self, g: &Grouping) -> Expr {
+        self.visit_expr(&g.expr)
+    }
+
+    fn visit_variable(&mut self, v: &Variable) -> Expr {
+        Expr::VARIABLE(v.clone())
+    }
+
+    fn visit_list(&mut self, l: &List) -> Expr {
+        Expr::LIST(List::new(l.elements.iter().map(|e| self.visit_expr(e)).collect()))
+    }
+
+    fn visit_map(&mut self, m: &Map) -> Expr {
+        Expr::MAP(
+            Map::new(
+                m.brace.clone(),
+                m.entries.iter().map(|(k, v)| (self.visit_expr(k), self.visit_expr(v))).collect(),
+            )
+        )
+    }
+
+    fn visit_index(&mut self, i: &Index) -> Expr {
+        Expr::INDEX(
+            Index::new(
+                Box::new(self.visit_expr(&i.object)),
+                i.bracket.clone(),
+                Box::new(self.visit_expr(&i.index)),
+            )
+        )
+    }
+
+    fn visit_slice(&mut self, s: &Slice) -> Expr {
+        Expr::SLICE(
+            Slice::new(
+                Box::new(self.visit_expr(&s.object)),
+                s.bracket.clone(),
+                s.start.as_ref().map(|e| Box::new(self.visit_expr(e))),
+                s.end.as_ref().map(|e| Box::new(self.visit_expr(e))),
+            )
+        )
+    }
+
+    fn visit_set_index(&mut self, s: &SetIndex) -> Expr {
+        Expr::SETINDEX(
+            SetIndex::new(
+                Box::new(self.visit_expr(&s.object)),
+                s.bracket.clone(),
+                Box::new(self.visit_expr(&s.index)),
+                Box::new(self.visit_expr(&s.value)),
+            )
+        )
+    }
+
+    fn visit_compound(&mut self, c: &Compound) -> Expr {
+        Expr::COMPOUND(
+            Compound::new(
+                Box::new(self.visit_expr(&c.target)),
+                c.operator.clone(),
+                Box::new(self.visit_expr(&c.value)),
+                c.postfix,
+            )
+        )
+    }
+
+    fn visit_format(&mut self, f: &Format) -> Expr {
+        let parts: Vec<Expr> = f.parts.iter().map(|e| self.visit_expr(e)).collect();
+
+        if parts.iter().all(|e| matches!(e, Expr::LITERAL(_))) {
+            let joined: String = parts.iter().map(|e| match e {
+                Expr::LITERAL(lit) => lit.to_string(),
+                _ => unreachable!(),
+            }).collect();
+
+            return Expr::LITERAL(Literal::STRING(joined));
+        }
+
+        Expr::FORMAT(Format::new(parts, f.line))
+    }
+
+    fn visit_match(&mut self, m: &Match) -> Expr {
+        let arms: Vec<MatchArm> = m.arms.iter().map(|arm| {
+            MatchArm::new(
+                arm.token.clone(),
+                arm.patterns.clone(),
+                arm.guard.as_ref().map(|e| Box::new(self.visit_expr(e))),
+                Box::new(self.visit_expr(&arm.body)),
+            )
+        }).collect();
+
+        Expr::MATCH(Match::new(m.keyword.clone(), Box::new(self.visit_expr(&m.subject)), arms))
+    }
+
+    fn visit_lambda(&mut self, l: &Lambda) -> Expr {
+        Expr::LAMBDA(Lambda::new(
+            l.keyword.clone(),
+            l.params.clone(),
+            l.annotations.clone(),
+            l.returns.clone(),
+            self.optimize(&l.body),
+        ))
+    }
+
+    fn visit_get(&mut self, g: &Get) -> Expr {
+        Expr::GET(Get::new(Box::new(self.visit_expr(&g.object)), g.name.clone()))
+    }
+
+    fn visit_call(&mut self, c: &Call) -> Expr {
+        Expr::CALL(
+            Call::new(
+                Box::new(self.visit_expr(&c.callee)),
+                c.paren.clone(),
+                c.arguments.iter().map(|e| self.visit_expr(e)).collect(),
+            )
+        )
+    }
+}
+
+impl StmtVisitor<Stmt> for Optimizer {
+    fn visit_stmt(&mut self, s: &Stmt) -> Stmt {
+        match s {
+            Stmt::BLOCK(b) => self.visit_block(b),
+            Stmt::EXPRESSION(e) => self.visit_expression(e),
+            Stmt::PRINT(p) => self.visit_print(p),
+            Stmt::VAR(v) => self.visit_var(v),
+            Stmt::IF(i) => self.visit_if(i),
+            Stmt::WHILE(w) => self.visit_while(w),
+            Stmt::FORIN(f) => self.visit_for_in(f),
+            Stmt::BREAK(b) => self.visit_break(b),
+            Stmt::CONTINUE(c) => self.visit_continue(c),
+            Stmt::IMPORT(i) => self.visit_import(i),
+            Stmt::THROW(t) => self.visit_throw(t),
+            Stmt::TRY(t) => self.visit_try(t),
+            Stmt::RETURN(r) => self.visit_return(r),
+        }
+    }
+
+    fn visit_expression(&mut self, e: &Expression) -> Stmt {
+        Stmt::EXPRESSION(Expression::new(Box::new(self.visit_expr(&e.expr)), e.line))
+    }
+
+    fn visit_print(&mut self, p: &Print) -> Stmt {
+        Stmt::PRINT(Print::new(Box::new(self.visit_expr(&p.expr)), p.line))
+    }
+
+    fn visit_var(&mut self, v: &Var) -> Stmt {
+        let initializer = v.initializer.as_ref().map(|expr| Box::new(self.visit_expr(expr)));
+
+        Stmt::VAR(Var::new(v.name.clone(), v.annotation.clone(), initializer, v.constant))
+    }
+
+    fn visit_block(&mut self, b: &Block) -> Stmt {
+        Stmt::BLOCK(Block::new(self.optimize(&b.statements), b.line))
+    }
+
+    fn visit_if(&mut self, i: &If) -> Stmt {
+        let cond = self.visit_expr(&i.cond);
+        let then_branch = self.visit_stmt(&i.then_branch);
+        let else_branch = i.else_branch.as_ref().map(|stmt| Box::new(self.visit_stmt(stmt)));
+
+        match &cond {
+            Expr::LITERAL(lit) if matches!(self.options.condition(lit), Ok(true)) => then_branch,
+            Expr::LITERAL(lit) if matches!(self.options.condition(lit), Ok(false)) => match else_branch {
+                Some(stmt) => *stmt,
+                None => Stmt::BLOCK(Block::new(Vec::new(), i.line)),
+            },
+            _ => Stmt::IF(If::new(Box::new(cond), Box::new(then_branch), else_branch, i.line)),
+        }
+    }
+
+    fn visit_while(&mut self, w: &While) -> Stmt {
+        Stmt::WHILE(
+            While::new(
+                Box::new(self.visit_expr(&w.cond)),
+                Box::new(self.visit_stmt(&w.body)),
+                w.increment.as_ref().map(|expr| Box::new(self.visit_expr(expr))),
+                w.line,
+            )
+        )
+    }
+
+    fn visit_for_in(&mut self, f: &ForIn) -> Stmt {
+        Stmt::FORIN(
+            ForIn::new(
+                f.name.clone(),
+                Box::new(self.visit_expr(&f.iterable)),
+                Box::new(self.visit_stmt(&f.body)),
+            )
+        )
+    }
+
+    fn visit_import(&mut self, i: &Import) -> Stmt {
+        Stmt::IMPORT(i.clone())
+    }
+
+    fn visit_return(&mut self, r: &Return) -> Stmt {
+        Stmt::RETURN(Return::new(r.keyword.clone(), r.value.as_ref().map(|e| Box::new(self.visit_expr(e)))))
+    }
+
+    fn visit_throw(&mut self, t: &Throw) -> Stmt {
+        Stmt::THROW(Throw::new(t.keyword.clone(), Box::new(self.visit_expr(&t.value))))
+    }
+
+    fn visit_try(&mut self, t: &Try) -> Stmt {
+        Stmt::TRY(
+            Try::new(
+                t.keyword.clone(),
+                self.optimize(&t.body),
+                t.catch_name.clone(),
+                t.catch_body.as_ref().map(|body| self.optimize(body)),
+                t.finally_body.as_ref().map(|body| self.optimize(body)),
+            )
+        )
+    }
+
+    fn visit_break(&mut self, b: &Break) -> Stmt {
+        Stmt::BREAK(b.clone())
+    }
+
+    fn visit_continue(&mut self, c: &Continue) -> Stmt {
+        Stmt::CONTINUE(c.clone())
+    }
+}
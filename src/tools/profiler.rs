@@ -0,0 +1,206 @@
+/* -------------------------------------------------------------------------------------
+*
+*   Instrumenting profiler for the Interpreter
+*
+*   Counts how many times each statement runs and each call site is called, and how
+*   long they take, using the instrumentation hooks. Statements are identified by their
+*   line and kind, call sites by their line and the name of the callee.
+*
+*   At the end of the run it writes:
+*       - A report of the statements and one of the call sites, sorted by total time,
+*         to stderr (count, total, self and average time);
+*       - A folded-stack file (`main;line 3: print;line 3: call fib;line 1: return 42`)
+*         accepted by flamegraph tools, where the value is the self time in
+*         microseconds of each stack.
+*
+*   Self time excludes the time spent in nested statements (like inside blocks) and
+*   calls, so the time of a function is under its call sites.
+*
+* ------------------------------------------------------------------------------------- */
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::ast_stmt::Stmt;
+use crate::environment::Environment;
+use crate::instrument::Instrument;
+
+#[derive(Default)]
+struct Entry {
+    count: u64,
+    total: Duration,
+    self_time: Duration,
+}
+
+struct Frame {
+    label: String,
+    start: Instant,
+    children: Duration,
+}
+
+pub struct Profiler {
+    folded_path: PathBuf,
+    entries: BTreeMap<(usize, String), Entry>,
+    calls: BTreeMap<(usize, String), Entry>,
+    folded: BTreeMap<String, u128>,
+    stack: Vec<Frame>,
+}
+
+impl Profiler {
+    pub fn new(folded_path: PathBuf) -> Self {
+        Profiler {
+            folded_path,
+            entries: BTreeMap::new(),
+            calls: BTreeMap::new(),
+            folded: BTreeMap::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    fn enter(&mut self, label: String) {
+        self.stack.push(Frame {
+            label,
+            start: Instant::now(),
+            children: Duration::ZERO,
+        });
+    }
+
+    // Closes the innermost frame, returning its total and self time
+    fn exit(&mut self) -> Option<(Duration, Duration)> {
+        let frame = self.stack.pop()?;
+
+        let total = frame.start.elapsed();
+        let self_time = total.saturating_sub(frame.children);
+
+        let mut path: Vec<&str> = vec!["main"];
+        path.extend(self.stack.iter().map(|f| f.label.as_str()));
+        path.push(&frame.label);
+        *self.folded.entry(path.join(";")).or_insert(0) += self_time.as_micros();
+
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children += total;
+        }
+
+        Some((total, self_time))
+    }
+
+    fn describe(stmt: &Stmt) -> String {
+        match stmt {
+            Stmt::BLOCK(_) => "block".to_string(),
+            Stmt::EXPRESSION(_) => "expression".to_string(),
+            Stmt::PRINT(_) => "print".to_string(),
+            Stmt::VAR(v) => format!(
+                "{} {}",
+                if v.constant { "const" } else { "var" },
+                v.name.value.clone().unwrap_or_default()
+            ),
+            Stmt::IF(_) => "if".to_string(),
+            Stmt::WHILE(_) => "while".to_string(),
+            Stmt::FORIN(f) => format!("for {}", f.name.value.clone().unwrap_or_default()),
+            Stmt::BREAK(_) => "break".to_string(),
+            Stmt::CONTINUE(_) => "continue".to_string(),
+            Stmt::THROW(_) => "throw".to_string(),
+            Stmt::TRY(_) => "try".to_string(),
+            Stmt::RETURN(_) => "return".to_string(),
+            Stmt::IMPORT(i) => format!("import {}", i.path.value.clone().unwrap_or_default()),
+        }
+    }
+
+    fn write_report(&self, out: &mut dyn Write) -> io::Result<()> {
+        Profiler::write_table(out, "statement", &self.entries)?;
+
+        if !self.calls.is_empty() {
+            writeln!(out)?;
+            Profiler::write_table(out, "call", &self.calls)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_table(out: &mut dyn Write, kind: &str, entries: &BTreeMap<(usize, String), Entry>) -> io::Result<()> {
+        let mut rows: Vec<(&(usize, String), &Entry)> = entries.iter().collect();
+        rows.sort_by(|a, b| b.1.total.cmp(&a.1.total).then(a.0.cmp(b.0)));
+
+        writeln!(
+            out,
+            "{:>6}  {:<24} {:>10} {:>12} {:>12} {:>12}",
+            "line", kind, "count", "total (ms)", "self (ms)", "avg (us)"
+        )?;
+
+        for ((line, label), entry) in rows {
+            writeln!(
+                out,
+                "{:>6}  {:<24} {:>10} {:>12.3} {:>12.3} {:>12.3}",
+                line,
+                label,
+                entry.count,
+                entry.total.as_secs_f64() * 1e3,
+                entry.self_time.as_secs_f64() * 1e3,
+                entry.total.as_secs_f64() * 1e6 / entry.count as f64,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_folded(&self, out: &mut dyn Write) -> io::Result<()> {
+        for (stack, micros) in &self.folded {
+            writeln!(out, "{} {}", stack, micros)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Instrument for Profiler {
+    fn before_stmt(&mut self, stmt: &Stmt, _env: &Environment) {
+        self.enter(format!("line {}: {}", stmt.line(), Profiler::describe(stmt)));
+    }
+
+    fn after_stmt(&mut self, stmt: &Stmt, _env: &Environment) {
+        let (total, self_time) = match self.exit() {
+            Some(times) => times,
+            None => return,
+        };
+
+        let entry = self.entries
+            .entry((stmt.line(), Profiler::describe(stmt)))
+            .or_default();
+        entry.count += 1;
+        entry.total += total;
+        entry.self_time += self_time;
+    }
+
+    fn before_call(&mut self, line: usize, callee: &str) {
+        self.enter(format!("line {}: call {}", line, callee));
+    }
+
+    fn after_call(&mut self, line: usize, callee: &str) {
+        let (total, self_time) = match self.exit() {
+            Some(times) => times,
+            None => return,
+        };
+
+        let entry = self.calls.entry((line, callee.to_string())).or_default();
+        entry.count += 1;
+        entry.total += total;
+        entry.self_time += self_time;
+    }
+
+    fn finish(&mut self) {
+        if let Err(err) = self.write_report(&mut io::stderr()) {
+            eprintln!("[PROFILER] {err}");
+        }
+
+        let result = File::create(&self.folded_path)
+            .and_then(|mut file| self.write_folded(&mut file));
+
+        match result {
+            Ok(_) => eprintln!("[PROFILER] Folded stacks written to {}", self.folded_path.display()),
+            Err(err) => eprintln!("[PROFILER] {err} | {}", self.folded_path.display()),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+/* -------------------------------------------------------------------------------------
+*
+*   Resource limits for running untrusted scripts
+*
+*   The interpreter reports its work to the sandbox as it goes, and a limit reached
+*   stops the run with a LIMIT error. These errors go through `catch` clauses, so a
+*   script can't recover from them and keep running.
+*
+*   Limits:
+*       - Steps: every statement and expression evaluated counts as one step;
+*       - Depth: nested calls of lambdas, this one is always on, turning a deep recursion
+*         into a "Stack overflow" error instead of overflowing the stack of the process.
+*         The calls are also stopped once they use `stack_bytes` of the stack, whatever
+*         the depth: a frame costs a few KB, more in debug builds, so the stack of the
+*         thread running the interpreter must hold `stack_bytes` and some more. The
+*         default fits in the 2 MiB of a thread spawned by `std`, the command line runs
+*         in a larger one (see `main.rs`);
+*       - Values: lists, maps, lambdas and strings created while running (the literals of
+*         the source don't count), and each element added to a list or a map, by `push()`,
+*         by a new key or in the lists built by natives like `map()`;
+*       - String bytes: the total size of the strings created while running;
+*       - Cancellation: a token shared with another thread, which can stop the run
+*         at the next step.
+*
+*   The counters are never reset, so for the prompt they add up across the lines.
+*
+* ------------------------------------------------------------------------------------- */
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::ast_expr::{Expr, Literal};
+use crate::error::EvaluationError;
+
+pub const DEFAULT_MAX_DEPTH: usize = 4096;
+
+// Half of an ordinary thread stack, the other half is left to what runs before the
+// first call and to the frames of the innermost one
+pub const DEFAULT_STACK_BYTES: usize = 1024 * 1024;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    pub max_steps: Option<u64>,
+    pub max_depth: usize,
+    pub stack_bytes: usize,
+    pub max_values: Option<u64>,
+    pub max_string_bytes: Option<u64>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_steps: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            stack_bytes: DEFAULT_STACK_BYTES,
+            max_values: None,
+            max_string_bytes: None,
+        }
+    }
+}
+
+// Cloned into the thread that decides when to stop, like a timer
+#[derive(Clone, Default, Debug)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+pub struct Sandbox {
+    limits: Limits,
+    cancel: Option<CancelToken>,
+    steps: u64,
+    depth: usize,
+    // Stack position of the outermost call
+    stack_base: usize,
+    values: u64,
+    string_bytes: u64,
+}
+
+impl Sandbox {
+    pub fn new(limits: Limits) -> Self {
+        Sandbox {
+            limits,
+            cancel: None,
+            steps: 0,
+            depth: 0,
+            stack_base: 0,
+            values: 0,
+            string_bytes: 0,
+        }
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn set_cancel_token(&mut self, token: CancelToken) {
+        self.cancel = Some(token);
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn step(&mut self) -> Result<(), EvaluationError> {
+        if self.cancel.as_ref().is_some_and(|token| token.is_cancelled()) {
+            return Err(EvaluationError::new_limit("Execution cancelled".to_string()));
+        }
+
+        self.steps += 1;
+        match self.limits.max_steps {
+            Some(max) if self.steps > max => Err(
+                EvaluationError::new_limit(format!("Step limit of {} exceeded", max))
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn enter_call(&mut self) -> Result<(), EvaluationError> {
+        let position = stack_position();
+        if self.depth == 0 {
+            self.stack_base = position;
+        }
+
+        if self.depth >= self.limits.max_depth || self.stack_base.abs_diff(position) > self.limits.stack_bytes {
+            return Err(EvaluationError::new_limit("Stack overflow".to_string()));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub fn exit_call(&mut self) {
+        self.depth -= 1;
+    }
+
+    // Counts the value produced by an expression, if evaluating it created a new one
+    pub fn allocate(&mut self, e: &Expr, value: &Literal) -> Result<(), EvaluationError> {
+        let created = matches!(
+            (e, value),
+            (Expr::LIST(_) | Expr::MAP(_) | Expr::LAMBDA(_) | Expr::SLICE(_), _)
+                | (Expr::BINARY(_) | Expr::COMPOUND(_) | Expr::FORMAT(_) | Expr::INDEX(_), Literal::STRING(_))
+        );
+
+        if created {
+            self.count(value)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Counts a value created outside of the expressions, like the lists made by natives
+    pub fn count(&mut self, value: &Literal) -> Result<(), EvaluationError> {
+        self.grow(1)?;
+
+        if let Literal::STRING(val) = value {
+            self.string_bytes += val.len() as u64;
+            if let Some(max) = self.limits.max_string_bytes {
+                if self.string_bytes > max {
+                    return Err(
+                        EvaluationError::new_limit(format!("String limit of {} bytes exceeded", max))
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Counts the elements added to an existing list or map
+    pub fn grow(&mut self, elements: u64) -> Result<(), EvaluationError> {
+        self.values += elements;
+        match self.limits.max_values {
+            Some(max) if self.values > max => Err(
+                EvaluationError::new_limit(format!("Value limit of {} exceeded", max))
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Address of a local in a frame of its own, the distance between two of them is the
+// stack used by the frames in between
+#[inline(never)]
+fn stack_position() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
@@ -0,0 +1,740 @@
+/* -------------------------------------------------------------------------------------
+*
+*   Machine-readable forms of the AST for the Language Lox
+*
+*   Unlike the `AstPrinter`, these forms keep every node and token of the tree, so the
+*   JSON can be read back into the same `Stmt`s by the `AstDeserializer`.
+*
+*   JSON:
+*       - A program is `{"kind": "PROGRAM", "version": 1, "statements": [...]}`, the
+*         version changes when the shape of the nodes does;
+*       - A node is an object with its `kind`, the name of its variant in `Stmt` or `Expr`
+*         (`"BINARY"`, `"FORIN"`, ...), and its fields. Statements also have their `line`;
+*       - A token is `{"type": "IDENTIFIER", "value": "x", "line": 3}`, with a null value
+*         for the operators and keywords. Tokens only know their line, so the spans
+*         have no column nor offset;
+*       - A literal is `{"kind": "LITERAL", "type": "NUMBER", "value": 1.5}`, of type
+*         BOOL, NUMBER, INTEGER, STRING or NIL;
+*       - Absent optional fields (an `else`, an initializer, ...) are null.
+*
+*   S-expressions:
+*       - The same tree, a node is `(KIND :field value ...)` with the fields sorted, a
+*         token `(TYPE "value" line)`, a list `(...)` and null is `nil`.
+*
+* ------------------------------------------------------------------------------------- */
+
+use serde_json::{json, Map as JsonMap, Value};
+
+use crate::visit_expr::ExprVisitor;
+use crate::visit_stmt::StmtVisitor;
+use crate::ast_expr::{Unary, Binary, Logical, Grouping, Expr, Literal, Comma, Ternary, Variable, Assign};
+use crate::ast_expr::{List, Map, Index, Slice, SetIndex, Compound, Call, Get, Format, Lambda, Match};
+use crate::ast_expr::{MatchArm, Pattern};
+use crate::ast_stmt::{Stmt, Expression, Print, Var, Block, If, While, ForIn, Break, Continue, Import};
+use crate::ast_stmt::{Throw, Try, Return};
+use crate::token::Token;
+use crate::token_type::TokenType;
+
+pub const FORMAT_VERSION: u64 = 1;
+
+pub struct AstSerializer;
+
+impl AstSerializer {
+    pub fn new() -> Self {
+        AstSerializer {}
+    }
+
+    pub fn serialize(&mut self, statements: &[Stmt]) -> Value {
+        json!({
+            "kind": "PROGRAM",
+            "version": FORMAT_VERSION,
+            "statements": self.statements(statements),
+        })
+    }
+
+    fn statements(&mut self, statements: &[Stmt]) -> Value {
+        Value::Array(statements.iter().map(|s| self.visit_stmt(s)).collect())
+    }
+
+    fn expressions(&mut self, expressions: &[Expr]) -> Value {
+        Value::Array(expressions.iter().map(|e| self.visit_expr(e)).collect())
+    }
+
+    fn optional(&mut self, expr: &Option<Box<Expr>>) -> Value {
+        match expr {
+            Some(expr) => self.visit_expr(expr),
+            None => Value::Null,
+        }
+    }
+
+    fn token(token: &Token) -> Value {
+        json!({ "type": token.kind.to_string(), "value": token.value, "line": token.line })
+    }
+
+    fn optional_token(token: &Option<Token>) -> Value {
+        match token {
+            Some(token) => AstSerializer::token(token),
+            None => Value::Null,
+        }
+    }
+
+    fn literal(lit: &Literal) -> Value {
+        match lit {
+            Literal::BOOL(val) => json!({ "kind": "LITERAL", "type": "BOOL", "value": val }),
+            Literal::NUMBER(val) => json!({ "kind": "LITERAL", "type": "NUMBER", "value": val }),
+            Literal::INTEGER(val) => json!({ "kind": "LITERAL", "type": "INTEGER", "value": val }),
+            Literal::STRING(val) => json!({ "kind": "LITERAL", "type": "STRING", "value": val }),
+            Literal::NIL => json!({ "kind": "LITERAL", "type": "NIL", "value": null }),
+            // Runtime values are never in a parsed tree, only their display form is kept
+            other => json!({ "kind": "LITERAL", "type": "VALUE", "value": other.to_string() }),
+        }
+    }
+
+    fn pattern(pattern: &Pattern) -> Value {
+        match pattern {
+            Pattern::LITERAL(lit) => json!({ "kind": "LITERAL", "value": AstSerializer::literal(lit) }),
+            Pattern::TYPE(name) => json!({ "kind": "TYPE", "name": AstSerializer::token(name) }),
+            Pattern::BINDING(name) => json!({ "kind": "BINDING", "name": AstSerializer::token(name) }),
+            Pattern::WILDCARD => json!({ "kind": "WILDCARD" }),
+        }
+    }
+
+    fn arm(&mut self, arm: &MatchArm) -> Value {
+        let guard = self.optional(&arm.guard);
+        json!({
+            "token": AstSerializer::token(&arm.token),
+            "patterns": arm.patterns.iter().map(AstSerializer::pattern).collect::<Vec<Value>>(),
+            "guard": guard,
+            "body": self.visit_expr(&arm.body),
+        })
+    }
+}
+
+impl ExprVisitor<Value> for AstSerializer {
+    fn visit_expr(&mut self, e: &Expr) -> Value {
+        match e {
+            Expr::UNARY(u) => self.visit_unary(u),
+            Expr::BINARY(b) => self.visit_binary(b),
+            Expr::LOGICAL(l) => self.visit_logical(l),
+            Expr::ASSIGN(a) => self.visit_assign(a),
+            Expr::GROUPING(g) => self.visit_grouping(g),
+            Expr::LITERAL(l) => self.visit_literal(l),
+            Expr::COMMA(c) => self.visit_comma(c),
+            Expr::TERNARY(t) => self.visit_ternary(t),
+            Expr::VARIABLE(v) => self.visit_variable(v),
+            Expr::LIST(l) => self.visit_list(l),
+            Expr::MAP(m) => self.visit_map(m),
+            Expr::INDEX(i) => self.visit_index(i),
+            Expr::SLICE(s) => self.visit_slice(s),
+            Expr::SETINDEX(s) => self.visit_set_index(s),
+            Expr::COMPOUND(c) => self.visit_compound(c),
+            Expr::CALL(c) => self.visit_call(c),
+            Expr::GET(g) => self.visit_get(g),
+            Expr::FORMAT(f) => self.visit_format(f),
+            Expr::LAMBDA(l) => self.visit_lambda(l),
+            Expr::MATCH(m) => self.visit_match(m),
+        }
+    }
+
+    fn visit_comma(&mut self, c: &Comma) -> Value {
+        json!({ "kind": "COMMA", "lhs": self.visit_expr(&c.lhs), "rhs": self.visit_expr(&c.rhs) })
+    }
+
+    fn visit_ternary(&mut self, t: &Ternary) -> Value {
+        json!({
+            "kind": "TERNARY",
+            "cond": self.visit_expr(&t.cond),
+            "then": self.visit_expr(&t.then_expr),
+            "else": self.visit_expr(&t.else_expr),
+            "line": t.line,
+        })
+    }
+
+    fn visit_assign(&mut self, a: &Assign) -> Value {
+        json!({ "kind": "ASSIGN", "name": AstSerializer::token(&a.name), "value": self.visit_expr(&a.value) })
+    }
+
+    fn visit_literal(&mut self, t: &Literal) -> Value {
+        AstSerializer::literal(t)
+    }
+
+    fn visit_unary(&mut self, u: &Unary) -> Value {
+        json!({ "kind": "UNARY", "op": AstSerializer::token(&u.op), "rhs": self.visit_expr(&u.rhs) })
+    }
+
+    fn visit_binary(&mut self, b: &Binary) -> Value {
+        json!({
+            "kind": "BINARY",
+            "op": AstSerializer::token(&b.op),
+            "lhs": self.visit_expr(&b.lhs),
+            "rhs": self.visit_expr(&b.rhs),
+        })
+    }
+
+    fn visit_logical(&mut self, l: &Logical) -> Value {
+        json!({
+            "kind": "LOGICAL",
+            "op": AstSerializer::token(&l.op),
+            "lhs": self.visit_expr(&l.lhs),
+            "rhs": self.visit_expr(&l.rhs),
+        })
+    }
+
+    fn visit_grouping(&mut self, g: &Grouping) -> Value {
+        json!({ "kind": "GROUPING", "expr": self.visit_expr(&g.expr) })
+    }
+
+    fn visit_variable(&mut self, v: &Variable) -> Value {
+        json!({ "kind": "VARIABLE", "name": AstSerializer::token(&v.name) })
+    }
+
+    fn visit_list(&mut self, l: &List) -> Value {
+        json!({ "kind": "LIST", "elements": self.expressions(&l.elements) })
+    }
+
+    fn visit_map(&mut self, m: &Map) -> Value {
+        let entries: Vec<Value> = m.entries.iter()
+            .map(|(k, v)| json!([self.visit_expr(k), self.visit_expr(v)]))
+            .collect();
+        json!({ "kind": "MAP", "brace": AstSerializer::token(&m.brace), "entries": entries })
+    }
+
+    fn visit_index(&mut self, i: &Index) -> Value {
+        json!({
+            "kind": "INDEX",
+            "object": self.visit_expr(&i.object),
+            "bracket": AstSerializer::token(&i.bracket),
+            "index": self.visit_expr(&i.index),
+        })
+    }
+
+    fn visit_slice(&mut self, s: &Slice) -> Value {
+        json!({
+            "kind": "SLICE",
+            "object": self.visit_expr(&s.object),
+            "bracket": AstSerializer::token(&s.bracket),
+            "start": self.optional(&s.start),
+            "end": self.optional(&s.end),
+        })
+    }
+
+    fn visit_set_index(&mut self, s: &SetIndex) -> Value {
+        json!({
+            "kind": "SETINDEX",
+            "object": self.visit_expr(&s.object),
+            "bracket": AstSerializer::token(&s.bracket),
+            "index": self.visit_expr(&s.index),
+            "value": self.visit_expr(&s.value),
+        })
+    }
+
+    fn visit_compound(&mut self, c: &Compound) -> Value {
+        json!({
+            "kind": "COMPOUND",
+            "target": self.visit_expr(&c.target),
+            "operator": AstSerializer::token(&c.operator),
+            "value": self.visit_expr(&c.value),
+            "postfix": c.postfix,
+        })
+    }
+
+    fn visit_call(&mut self, c: &Call) -> Value {
+        json!({
+            "kind": "CALL",
+            "callee": self.visit_expr(&c.callee),
+            "paren": AstSerializer::token(&c.paren),
+            "arguments": self.expressions(&c.arguments),
+        })
+    }
+
+    fn visit_get(&mut self, g: &Get) -> Value {
+        json!({ "kind": "GET", "object": self.visit_expr(&g.object), "name": AstSerializer::token(&g.name) })
+    }
+
+    fn visit_format(&mut self, f: &Format) -> Value {
+        json!({ "kind": "FORMAT", "parts": self.expressions(&f.parts), "line": f.line })
+    }
+
+    fn visit_lambda(&mut self, l: &Lambda) -> Value {
+        json!({
+            "kind": "LAMBDA",
+            "keyword": AstSerializer::token(&l.keyword),
+            "params": l.params.iter().map(AstSerializer::token).collect::<Vec<Value>>(),
+            "annotations": l.annotations.iter().map(AstSerializer::optional_token).collect::<Vec<Value>>(),
+            "returns": AstSerializer::optional_token(&l.returns),
+            "body": self.statements(&l.body),
+        })
+    }
+
+    fn visit_match(&mut self, m: &Match) -> Value {
+        let arms: Vec<Value> = m.arms.iter().map(|arm| self.arm(arm)).collect();
+        json!({
+            "kind": "MATCH",
+            "keyword": AstSerializer::token(&m.keyword),
+            "subject": self.visit_expr(&m.subject),
+            "arms": arms,
+        })
+    }
+}
+
+impl StmtVisitor<Value> for AstSerializer {
+    fn visit_stmt(&mut self, s: &Stmt) -> Value {
+        let mut node = match s {
+            Stmt::BLOCK(b) => self.visit_block(b),
+            Stmt::PRINT(e) => self.visit_print(e),
+            Stmt::EXPRESSION(p) => self.visit_expression(p),
+            Stmt::VAR(v) => self.visit_var(v),
+            Stmt::IF(i) => self.visit_if(i),
+            Stmt::WHILE(w) => self.visit_while(w),
+            Stmt::FORIN(f) => self.visit_for_in(f),
+            Stmt::BREAK(b) => self.visit_break(b),
+            Stmt::CONTINUE(c) => self.visit_continue(c),
+            Stmt::IMPORT(i) => self.visit_import(i),
+            Stmt::THROW(t) => self.visit_throw(t),
+            Stmt::TRY(t) => self.visit_try(t),
+            Stmt::RETURN(r) => self.visit_return(r),
+        };
+
+        node["line"] = json!(s.line());
+        node
+    }
+
+    fn visit_expression(&mut self, e: &Expression) -> Value {
+        json!({ "kind": "EXPRESSION", "expr": self.visit_expr(&e.expr) })
+    }
+
+    fn visit_print(&mut self, p: &Print) -> Value {
+        json!({ "kind": "PRINT", "expr": self.visit_expr(&p.expr) })
+    }
+
+    fn visit_var(&mut self, v: &Var) -> Value {
+        json!({
+            "kind": "VAR",
+            "name": AstSerializer::token(&v.name),
+            "annotation": AstSerializer::optional_token(&v.annotation),
+            "initializer": self.optional(&v.initializer),
+            "constant": v.constant,
+        })
+    }
+
+    fn visit_block(&mut self, b: &Block) -> Value {
+        json!({ "kind": "BLOCK", "statements": self.statements(&b.statements) })
+    }
+
+    fn visit_if(&mut self, i: &If) -> Value {
+        let else_branch = match &i.else_branch {
+            Some(stmt) => self.visit_stmt(stmt),
+            None => Value::Null,
+        };
+
+        json!({
+            "kind": "IF",
+            "cond": self.visit_expr(&i.cond),
+            "then": self.visit_stmt(&i.then_branch),
+            "else": else_branch,
+        })
+    }
+
+    fn visit_while(&mut self, w: &While) -> Value {
+        json!({
+            "kind": "WHILE",
+            "cond": self.visit_expr(&w.cond),
+            "body": self.visit_stmt(&w.body),
+            "increment": self.optional(&w.increment),
+        })
+    }
+
+    fn visit_for_in(&mut self, f: &ForIn) -> Value {
+        json!({
+            "kind": "FORIN",
+            "name": AstSerializer::token(&f.name),
+            "iterable": self.visit_expr(&f.iterable),
+            "body": self.visit_stmt(&f.body),
+        })
+    }
+
+    fn visit_break(&mut self, b: &Break) -> Value {
+        json!({ "kind": "BREAK", "keyword": AstSerializer::token(&b.keyword) })
+    }
+
+    fn visit_continue(&mut self, c: &Continue) -> Value {
+        json!({ "kind": "CONTINUE", "keyword": AstSerializer::token(&c.keyword) })
+    }
+
+    fn visit_import(&mut self, i: &Import) -> Value {
+        json!({
+            "kind": "IMPORT",
+            "keyword": AstSerializer::token(&i.keyword),
+            "path": AstSerializer::token(&i.path),
+            "alias": AstSerializer::optional_token(&i.alias),
+        })
+    }
+
+    fn visit_throw(&mut self, t: &Throw) -> Value {
+        json!({ "kind": "THROW", "keyword": AstSerializer::token(&t.keyword), "value": self.visit_expr(&t.value) })
+    }
+
+    fn visit_try(&mut self, t: &Try) -> Value {
+        let catch_body = match &t.catch_body {
+            Some(body) => self.statements(body),
+            None => Value::Null,
+        };
+
+        let finally_body = match &t.finally_body {
+            Some(body) => self.statements(body),
+            None => Value::Null,
+        };
+
+        json!({
+            "kind": "TRY",
+            "keyword": AstSerializer::token(&t.keyword),
+            "body": self.statements(&t.body),
+            "catchName": AstSerializer::optional_token(&t.catch_name),
+            "catchBody": catch_body,
+            "finallyBody": finally_body,
+        })
+    }
+
+    fn visit_return(&mut self, r: &Return) -> Value {
+        json!({ "kind": "RETURN", "keyword": AstSerializer::token(&r.keyword), "value": self.optional(&r.value) })
+    }
+}
+
+// Writes a serialized tree as an S-expression
+pub fn to_sexpr(value: &Value) -> String {
+    match value {
+        Value::Null => "nil".to_string(),
+        Value::Bool(val) => val.to_string(),
+        Value::Number(val) => val.to_string(),
+        Value::String(val) => Value::String(val.clone()).to_string(),
+        Value::Array(elements) => {
+            let elements: Vec<String> = elements.iter().map(to_sexpr).collect();
+            format!("({})", elements.join(" "))
+        },
+        Value::Object(fields) => {
+            // Tokens are short enough to be positional
+            if let (Some(Value::String(kind)), Some(line)) = (fields.get("type"), fields.get("line")) {
+                if fields.len() == 3 && fields.contains_key("value") {
+                    return format!("({} {} {})", kind, to_sexpr(&fields["value"]), line);
+                }
+            }
+
+            let mut parts = match fields.get("kind") {
+                Some(Value::String(kind)) => vec![kind.clone()],
+                _ => vec![],
+            };
+
+            for (name, field) in fields.iter().filter(|(name, _)| *name != "kind") {
+                parts.push(format!(":{} {}", name, to_sexpr(field)));
+            }
+
+            format!("({})", parts.join(" "))
+        },
+    }
+}
+
+/* ---------------------------------------------------------------------------------- */
+
+// Reads back the JSON written by the `AstSerializer`, errors name the field that is wrong
+pub struct AstDeserializer;
+
+impl AstDeserializer {
+    pub fn new() -> Self {
+        AstDeserializer {}
+    }
+
+    pub fn deserialize(&mut self, value: &Value) -> Result<Vec<Stmt>, String> {
+        match value["version"].as_u64() {
+            Some(FORMAT_VERSION) => (),
+            Some(other) => return Err(format!("Unsupported AST version {}", other)),
+            None => return Err("Missing AST version".to_string()),
+        }
+
+        self.statements(&value["statements"])
+    }
+
+    fn fields<'a>(value: &'a Value, what: &str) -> Result<&'a JsonMap<String, Value>, String> {
+        value.as_object().ok_or_else(|| format!("Expected an object for {}", what))
+    }
+
+    fn kind(value: &Value) -> Result<&str, String> {
+        value["kind"].as_str().ok_or_else(|| "Missing the kind of a node".to_string())
+    }
+
+    fn array<'a>(value: &'a Value, field: &str) -> Result<&'a Vec<Value>, String> {
+        value[field].as_array().ok_or_else(|| format!("Expected a list for '{}'", field))
+    }
+
+    fn bool(value: &Value, field: &str) -> Result<bool, String> {
+        value[field].as_bool().ok_or_else(|| format!("Expected a boolean for '{}'", field))
+    }
+
+    fn line(value: &Value, field: &str) -> Result<usize, String> {
+        match value[field].as_u64() {
+            Some(line) => Ok(line as usize),
+            None => Err(format!("Expected a line number for '{}'", field)),
+        }
+    }
+
+    fn token(&mut self, value: &Value, field: &str) -> Result<Token, String> {
+        AstDeserializer::read_token(&value[field], field)
+    }
+
+    fn read_token(value: &Value, field: &str) -> Result<Token, String> {
+        let token = AstDeserializer::fields(value, field)?;
+
+        let kind = match token.get("type").and_then(Value::as_str).and_then(TokenType::from_name) {
+            Some(kind) => kind,
+            None => return Err(format!("Unknown token type in '{}'", field)),
+        };
+
+        let text = match token.get("value") {
+            Some(Value::String(text)) => Some(text.clone()),
+            Some(Value::Null) | None => None,
+            Some(_) => return Err(format!("Expected a string value in '{}'", field)),
+        };
+
+        Ok(Token::new(kind, AstDeserializer::line(value, "line")?, text))
+    }
+
+    fn optional_token(&mut self, value: &Value, field: &str) -> Result<Option<Token>, String> {
+        match value[field] {
+            Value::Null => Ok(None),
+            _ => self.token(value, field).map(Some),
+        }
+    }
+
+    fn statements(&mut self, value: &Value) -> Result<Vec<Stmt>, String> {
+        match value.as_array() {
+            Some(statements) => statements.iter().map(|s| self.stmt(s)).collect(),
+            None => Err("Expected a list of statements".to_string()),
+        }
+    }
+
+    fn optional_statements(&mut self, value: &Value) -> Result<Option<Vec<Stmt>>, String> {
+        match value {
+            Value::Null => Ok(None),
+            other => self.statements(other).map(Some),
+        }
+    }
+
+    fn expressions(&mut self, value: &Value, field: &str) -> Result<Vec<Expr>, String> {
+        AstDeserializer::array(value, field)?.iter().map(|e| self.expr(e)).collect()
+    }
+
+    fn boxed(&mut self, value: &Value, field: &str) -> Result<Box<Expr>, String> {
+        match value.get(field) {
+            Some(expr) => self.expr(expr).map(Box::new),
+            None => Err(format!("Missing the expression '{}'", field)),
+        }
+    }
+
+    fn optional(&mut self, value: &Value, field: &str) -> Result<Option<Box<Expr>>, String> {
+        match value[field] {
+            Value::Null => Ok(None),
+            _ => self.boxed(value, field).map(Some),
+        }
+    }
+
+    fn body(&mut self, value: &Value, field: &str) -> Result<Box<Stmt>, String> {
+        match value.get(field) {
+            Some(stmt) => self.stmt(stmt).map(Box::new),
+            None => Err(format!("Missing the statement '{}'", field)),
+        }
+    }
+
+    pub fn stmt(&mut self, value: &Value) -> Result<Stmt, String> {
+        AstDeserializer::fields(value, "a statement")?;
+        let line = AstDeserializer::line(value, "line")?;
+
+        let stmt = match AstDeserializer::kind(value)? {
+            "EXPRESSION" => Stmt::EXPRESSION(Expression::new(self.boxed(value, "expr")?, line)),
+            "PRINT" => Stmt::PRINT(Print::new(self.boxed(value, "expr")?, line)),
+            "VAR" => Stmt::VAR(Var::new(
+                self.token(value, "name")?,
+                self.optional_token(value, "annotation")?,
+                self.optional(value, "initializer")?,
+                AstDeserializer::bool(value, "constant")?,
+            )),
+            "BLOCK" => Stmt::BLOCK(Block::new(self.statements(&value["statements"])?, line)),
+            "IF" => {
+                let else_branch = match value["else"] {
+                    Value::Null => None,
+                    _ => Some(self.body(value, "else")?),
+                };
+                Stmt::IF(If::new(self.boxed(value, "cond")?, self.body(value, "then")?, else_branch, line))
+            },
+            "WHILE" => Stmt::WHILE(While::new(
+                self.boxed(value, "cond")?,
+                self.body(value, "body")?,
+                self.optional(value, "increment")?,
+                line,
+            )),
+            "FORIN" => Stmt::FORIN(ForIn::new(
+                self.token(value, "name")?,
+                self.boxed(value, "iterable")?,
+                self.body(value, "body")?,
+            )),
+            "BREAK" => Stmt::BREAK(Break::new(self.token(value, "keyword")?)),
+            "CONTINUE" => Stmt::CONTINUE(Continue::new(self.token(value, "keyword")?)),
+            "IMPORT" => Stmt::IMPORT(Import::new(
+                self.token(value, "keyword")?,
+                self.token(value, "path")?,
+                self.optional_token(value, "alias")?,
+            )),
+            "THROW" => Stmt::THROW(Throw::new(self.token(value, "keyword")?, self.boxed(value, "value")?)),
+            "TRY" => Stmt::TRY(Try::new(
+                self.token(value, "keyword")?,
+                self.statements(&value["body"])?,
+                self.optional_token(value, "catchName")?,
+                self.optional_statements(&value["catchBody"])?,
+                self.optional_statements(&value["finallyBody"])?,
+            )),
+            "RETURN" => Stmt::RETURN(Return::new(self.token(value, "keyword")?, self.optional(value, "value")?)),
+            other => return Err(format!("Unknown statement kind '{}'", other)),
+        };
+
+        Ok(stmt)
+    }
+
+    pub fn expr(&mut self, value: &Value) -> Result<Expr, String> {
+        AstDeserializer::fields(value, "an expression")?;
+
+        let expr = match AstDeserializer::kind(value)? {
+            "COMMA" => Expr::COMMA(Comma::new(self.boxed(value, "lhs")?, self.boxed(value, "rhs")?)),
+            "TERNARY" => Expr::TERNARY(Ternary::new(
+                self.boxed(value, "cond")?,
+                self.boxed(value, "then")?,
+                self.boxed(value, "else")?,
+                AstDeserializer::line(value, "line")?,
+            )),
+            "ASSIGN" => Expr::ASSIGN(Assign::new(self.token(value, "name")?, self.boxed(value, "value")?)),
+            "LITERAL" => Expr::LITERAL(self.literal(value)?),
+            "UNARY" => Expr::UNARY(Unary::new(self.token(value, "op")?, self.boxed(value, "rhs")?)),
+            "BINARY" => Expr::BINARY(Binary::new(
+                self.token(value, "op")?,
+                self.boxed(value, "lhs")?,
+                self.boxed(value, "rhs")?,
+            )),
+            "LOGICAL" => Expr::LOGICAL(Logical::new(
+                self.token(value, "op")?,
+                self.boxed(value, "lhs")?,
+                self.boxed(value, "rhs")?,
+            )),
+            "GROUPING" => Expr::GROUPING(Grouping::new(self.boxed(value, "expr")?)),
+            "VARIABLE" => Expr::VARIABLE(Variable::new(self.token(value, "name")?)),
+            "LIST" => Expr::LIST(List::new(self.expressions(value, "elements")?)),
+            "MAP" => {
+                let mut entries = vec![];
+                for entry in AstDeserializer::array(value, "entries")? {
+                    match entry.as_array().map(|pair| pair.as_slice()) {
+                        Some([k, v]) => entries.push((self.expr(k)?, self.expr(v)?)),
+                        _ => return Err("Expected a key and a value for a map entry".to_string()),
+                    }
+                }
+                Expr::MAP(Map::new(self.token(value, "brace")?, entries))
+            },
+            "INDEX" => Expr::INDEX(Index::new(
+                self.boxed(value, "object")?,
+                self.token(value, "bracket")?,
+                self.boxed(value, "index")?,
+            )),
+            "SLICE" => Expr::SLICE(Slice::new(
+                self.boxed(value, "object")?,
+                self.token(value, "bracket")?,
+                self.optional(value, "start")?,
+                self.optional(value, "end")?,
+            )),
+            "SETINDEX" => Expr::SETINDEX(SetIndex::new(
+                self.boxed(value, "object")?,
+                self.token(value, "bracket")?,
+                self.boxed(value, "index")?,
+                self.boxed(value, "value")?,
+            )),
+            "COMPOUND" => Expr::COMPOUND(Compound::new(
+                self.boxed(value, "target")?,
+                self.token(value, "operator")?,
+                self.boxed(value, "value")?,
+                AstDeserializer::bool(value, "postfix")?,
+            )),
+            "CALL" => Expr::CALL(Call::new(
+                self.boxed(value, "callee")?,
+                self.token(value, "paren")?,
+                self.expressions(value, "arguments")?,
+            )),
+            "GET" => Expr::GET(Get::new(self.boxed(value, "object")?, self.token(value, "name")?)),
+            "FORMAT" => Expr::FORMAT(Format::new(
+                self.expressions(value, "parts")?,
+                AstDeserializer::line(value, "line")?,
+            )),
+            "LAMBDA" => {
+                let params = AstDeserializer::array(value, "params")?.iter()
+                    .map(|param| AstDeserializer::read_token(param, "params"))
+                    .collect::<Result<Vec<Token>, String>>()?;
+                let annotations = AstDeserializer::array(value, "annotations")?.iter()
+                    .map(|annotation| match annotation {
+                        Value::Null => Ok(None),
+                        _ => AstDeserializer::read_token(annotation, "annotations").map(Some),
+                    })
+                    .collect::<Result<Vec<Option<Token>>, String>>()?;
+
+                if annotations.len() != params.len() {
+                    return Err("Expected an annotation or null for each parameter".to_string());
+                }
+
+                Expr::LAMBDA(Lambda::new(
+                    self.token(value, "keyword")?,
+                    params,
+                    annotations,
+                    self.optional_token(value, "returns")?,
+                    self.statements(&value["body"])?,
+                ))
+            },
+            "MATCH" => {
+                let mut arms = vec![];
+                for arm in AstDeserializer::array(value, "arms")? {
+                    arms.push(self.arm(arm)?);
+                }
+                Expr::MATCH(Match::new(self.token(value, "keyword")?, self.boxed(value, "subject")?, arms))
+            },
+            other => return Err(format!("Unknown expression kind '{}'", other)),
+        };
+
+        Ok(expr)
+    }
+
+    fn literal(&mut self, value: &Value) -> Result<Literal, String> {
+        let lit = match (value["type"].as_str(), &value["value"]) {
+            (Some("BOOL"), Value::Bool(val)) => Literal::BOOL(*val),
+            (Some("NUMBER"), Value::Number(val)) if val.as_f64().is_some() => Literal::NUMBER(val.as_f64().unwrap()),
+            (Some("INTEGER"), Value::Number(val)) if val.as_i64().is_some() => Literal::INTEGER(val.as_i64().unwrap()),
+            (Some("STRING"), Value::String(val)) => Literal::STRING(val.clone()),
+            (Some("NIL"), Value::Null) => Literal::NIL,
+            (Some(kind), _) => return Err(format!("Invalid literal of type '{}'", kind)),
+            (None, _) => return Err("Missing the type of a literal".to_string()),
+        };
+
+        Ok(lit)
+    }
+
+    fn arm(&mut self, value: &Value) -> Result<MatchArm, String> {
+        let mut patterns = vec![];
+        for pattern in AstDeserializer::array(value, "patterns")? {
+            let pattern = match AstDeserializer::kind(pattern)? {
+                "LITERAL" => Pattern::LITERAL(self.literal(&pattern["value"])?),
+                "TYPE" => Pattern::TYPE(self.token(pattern, "name")?),
+                "BINDING" => Pattern::BINDING(self.token(pattern, "name")?),
+                "WILDCARD" => Pattern::WILDCARD,
+                other => return Err(format!("Unknown pattern kind '{}'", other)),
+            };
+            patterns.push(pattern);
+        }
+
+        Ok(MatchArm::new(
+            self.token(value, "token")?,
+            patterns,
+            self.optional(value, "guard")?,
+            self.boxed(value, "body")?,
+        ))
+    }
+}
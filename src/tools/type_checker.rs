@@ -0,0 +1,832 @@
+/* -------------------------------------------------------------------------------------
+*
+*   Static Type Checker for the Language Lox
+*
+*   Runs over the AST before the interpreter, inferring the type of each expression
+*   and flagging operations that would fail at runtime with invalid operands.
+*
+*   The typing is gradual: variables, parameters and return values can be annotated
+*   (`var x: number = 1;`, `fun (a: number): string { ... }`) and the checker holds them
+*   to the declared type. Unannotated ones are `any`, and any operation involving `any`
+*   is accepted and left to be checked at runtime.
+*
+*   Some Considerations:
+*       - Types: number, string, bool, nil, list, map and any;
+*       - Integers and floats are both `number`;
+*       - `-`, `*`, `/`, `//`, `%` and the bitwise operators need numbers, comparisons
+*         also accept two strings and `+` strings (with the number to string coercion of
+*         the interpreter);
+*       - `==` and `!=` take any two values;
+*       - Annotated variables declared without initializer are an error, as they
+*         would hold nil;
+*       - The signature of a lambda follows the variable it is stored in, so the calls
+*         through that name are checked for the number and the types of the arguments,
+*         and give the declared return type. Once the variable is assigned a lambda of
+*         another signature, its calls are no longer checked (the assignment may not run);
+*       - Assigning to a `const`, or declaring its name again in the same scope, is an error,
+*         and when asked for (`--prefer-const`) the `var`s that are never reassigned are
+*         reported as warnings;
+*       - In strict mode `+` does not take a string and a number, and `!` only takes bools.
+*
+* ------------------------------------------------------------------------------------- */
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::visit_expr::ExprVisitor;
+use crate::visit_stmt::StmtVisitor;
+use crate::ast_expr::{Unary, Binary, Logical, Grouping, Expr, Literal, Comma, Ternary, Variable, Assign};
+use crate::ast_expr::{List, Map, Index, Slice, SetIndex, Compound, Call, Get, Format, Lambda, Match, Pattern};
+use crate::ast_stmt::{Stmt, Expression, Print, Var, Block, If, While, ForIn, Break, Continue, Import};
+use crate::ast_stmt::{Throw, Try, Return};
+use crate::token::Token;
+use crate::token_type::TokenType;
+use crate::error::{TypeError, Warning};
+use crate::interpreter::Interpreter;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Type {
+    NUMBER,
+    STRING,
+    BOOL,
+    NIL,
+    LIST,
+    MAP,
+    ANY,
+}
+
+impl Type {
+    pub fn from_name(name: &str) -> Option<Type> {
+        match name {
+            "number" => Some(Type::NUMBER),
+            "string" => Some(Type::STRING),
+            "bool" => Some(Type::BOOL),
+            "nil" => Some(Type::NIL),
+            "list" => Some(Type::LIST),
+            "map" => Some(Type::MAP),
+            "any" => Some(Type::ANY),
+            _ => None,
+        }
+    }
+
+    // Whether a value of type `other` can be stored where `self` is expected
+    fn accepts(&self, other: Type) -> bool {
+        *self == Type::ANY || other == Type::ANY || *self == other
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::NUMBER => write!(f, "number"),
+            Type::STRING => write!(f, "string"),
+            Type::BOOL => write!(f, "bool"),
+            Type::NIL => write!(f, "nil"),
+            Type::LIST => write!(f, "list"),
+            Type::MAP => write!(f, "map"),
+            Type::ANY => write!(f, "any"),
+        }
+    }
+}
+
+// Declared types of a lambda, unannotated parameters and returns are `any`
+#[derive(Clone, PartialEq, Debug)]
+struct Signature {
+    params: Vec<Type>,
+    returns: Type,
+}
+
+// A name in scope, `var`s keep their declaration to report them if never reassigned
+struct Binding {
+    ty: Type,
+    constant: bool,
+    var: Option<Token>,
+    reassigned: bool,
+    signature: Option<Signature>,
+}
+
+impl Binding {
+    fn new(ty: Type) -> Self {
+        Binding {
+            ty,
+            constant: false,
+            var: None,
+            reassigned: false,
+            signature: None,
+        }
+    }
+}
+
+pub struct TypeChecker {
+    scopes: Vec<HashMap<String, Binding>>,
+    // Return types of the lambdas being checked, the innermost last
+    returns: Vec<Type>,
+    strict: bool,
+    prefer_const: bool,
+    pub errors: Vec<TypeError>,
+    pub warnings: Vec<Warning>,
+}
+
+impl TypeChecker {
+    pub fn new() -> Self {
+        TypeChecker {
+            scopes: vec![HashMap::new()],
+            returns: Vec::new(),
+            strict: false,
+            prefer_const: false,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    // Checks the operators with the strict rules of the interpreter
+    pub fn enable_strict(&mut self) {
+        self.strict = true;
+    }
+
+    // Warns about the `var`s that are never reassigned, and could be `const`s
+    pub fn enable_prefer_const(&mut self) {
+        self.prefer_const = true;
+    }
+
+    pub fn check(&mut self, statements: &[Stmt]) {
+        for stmt in statements {
+            self.visit_stmt(stmt);
+        }
+
+        // Globals are kept for the next lines of the prompt, but reported only once
+        if let Some(globals) = self.scopes.first_mut() {
+            let unassigned: Vec<Token> = globals.values_mut()
+                .filter(|binding| !binding.reassigned)
+                .filter_map(|binding| binding.var.take())
+                .collect();
+            self.report_unassigned(unassigned);
+        }
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn pop_scope(&mut self) {
+        if let Some(scope) = self.scopes.pop() {
+            let unassigned: Vec<Token> = scope.into_values()
+                .filter(|binding| !binding.reassigned)
+                .filter_map(|binding| binding.var)
+                .collect();
+            self.report_unassigned(unassigned);
+        }
+    }
+
+    fn report_unassigned(&mut self, mut names: Vec<Token>) {
+        if !self.prefer_const {
+            return;
+        }
+
+        // Scopes are hash maps, the names also sort within a line to keep a stable order
+        names.sort_by_key(|name| (name.line, name.value.clone()));
+        for name in names {
+            self.warning(
+                format!("Variable '{}' is never reassigned, it could be a const", name.value.clone().unwrap_or_default()),
+                &name,
+            );
+        }
+    }
+
+    // Assignments to constants are errors, assigned variables are not reported. The
+    // assignment may be in a branch or a lambda that never runs, so a variable keeps its
+    // signature only while every lambda assigned to it has that same one
+    fn assign(&mut self, name: &Token, signature: Option<Signature>) {
+        let key = name.value.clone().unwrap_or_default();
+
+        let constant = match self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(&key)) {
+            Some(binding) => {
+                binding.reassigned = true;
+                if binding.signature != signature {
+                    binding.signature = None;
+                }
+                binding.constant
+            },
+            None => false,
+        };
+
+        if constant {
+            self.error(format!("Cannot assign to constant '{}'", key), name);
+        }
+    }
+
+    fn error(&mut self, message: String, token: &Token) {
+        self.errors.push(TypeError::new(message, token.clone()));
+    }
+
+    fn warning(&mut self, message: String, token: &Token) {
+        self.warnings.push(Warning::new(message, token.clone()));
+    }
+
+    // Whether every value matched by the later pattern is already matched by the earlier one
+    fn covers(earlier: &Pattern, later: &Pattern) -> bool {
+        match (earlier, later) {
+            (Pattern::WILDCARD | Pattern::BINDING(_), _) => true,
+            (_, Pattern::LITERAL(lit)) => Interpreter::pattern_matches(earlier, lit),
+            (Pattern::TYPE(a), Pattern::TYPE(b)) => a.value == b.value,
+            _ => false,
+        }
+    }
+
+    // Result of a binary operation, flagging the operands it can't take
+    fn binary_type(&mut self, op: &TokenType, lhs: Type, rhs: Type, token: &Token) -> Type {
+        if lhs == Type::ANY || rhs == Type::ANY {
+            return match op {
+                TokenType::PLUS => Type::ANY,
+                op if TypeChecker::is_arithmetic(op) => Type::NUMBER,
+                _ => Type::BOOL,
+            };
+        }
+
+        match (op.clone(), lhs, rhs) {
+            (ref op, Type::NUMBER, Type::NUMBER) if TypeChecker::is_arithmetic(op) => Type::NUMBER,
+            (TokenType::PLUS, Type::NUMBER, Type::NUMBER) => Type::NUMBER,
+            (TokenType::PLUS, Type::STRING, Type::STRING) => Type::STRING,
+            (TokenType::PLUS, Type::STRING, Type::NUMBER) | (TokenType::PLUS, Type::NUMBER, Type::STRING) if !self.strict => {
+                Type::STRING
+            },
+            (
+                TokenType::GREATER | TokenType::GREATEREQUAL | TokenType::LESS | TokenType::LESSEQUAL,
+                Type::NUMBER,
+                Type::NUMBER
+            ) => Type::BOOL,
+            (
+                TokenType::GREATER | TokenType::GREATEREQUAL | TokenType::LESS | TokenType::LESSEQUAL,
+                Type::STRING,
+                Type::STRING
+            ) => Type::BOOL,
+            (TokenType::EQUALEQUAL | TokenType::BANGEQUAL, _, _) => Type::BOOL,
+            (TokenType::PLUS, _, _) => {
+                self.error(format!("Invalid operands for '+': {} and {}", lhs, rhs), token);
+                Type::ANY
+            },
+            (ref op, _, _) if TypeChecker::is_arithmetic(op) => {
+                self.error(format!("Operands must be numbers, found {} and {}", lhs, rhs), token);
+                Type::NUMBER
+            },
+            (_, _, _) => {
+                self.error(format!("Cannot compare {} and {}", lhs, rhs), token);
+                Type::BOOL
+            },
+        }
+    }
+
+    // Operators that only take numbers (`+` also takes strings)
+    fn is_arithmetic(op: &TokenType) -> bool {
+        matches!(
+            op,
+            TokenType::MINUS
+                | TokenType::SLASH
+                | TokenType::SLASHSLASH
+                | TokenType::PERCENT
+                | TokenType::STAR
+                | TokenType::AMPERSAND
+                | TokenType::PIPE
+                | TokenType::CARET
+                | TokenType::LESSLESS
+                | TokenType::GREATERGREATER
+        )
+    }
+
+    // Type named by an annotation, `any` without one
+    fn annotated(&mut self, annotation: &Option<Token>) -> Type {
+        match annotation {
+            Some(t) => {
+                let name = t.value.clone().unwrap_or_default();
+                match Type::from_name(&name) {
+                    Some(ty) => ty,
+                    None => {
+                        self.error(format!("Unknown type '{}'", name), t);
+                        Type::ANY
+                    },
+                }
+            },
+            None => Type::ANY,
+        }
+    }
+
+    // Signature of the lambda an expression evaluates to, when it is known statically.
+    // Unknown type names are reported when the lambda itself is checked
+    fn signature(&self, e: &Expr) -> Option<Signature> {
+        let declared = |annotation: &Option<Token>| annotation.as_ref()
+            .and_then(|t| Type::from_name(&t.value.clone().unwrap_or_default()))
+            .unwrap_or(Type::ANY);
+
+        match e {
+            Expr::LAMBDA(l) => Some(Signature {
+                params: l.annotations.iter().map(declared).collect(),
+                returns: declared(&l.returns),
+            }),
+            Expr::GROUPING(g) => self.signature(&g.expr),
+            Expr::VARIABLE(v) => {
+                let name = v.name.value.clone().unwrap_or_default();
+                self.scopes.iter().rev()
+                    .find_map(|scope| scope.get(&name))
+                    .and_then(|binding| binding.signature.clone())
+            },
+            _ => None,
+        }
+    }
+
+    fn lookup(&self, name: &Token) -> Type {
+        let name = name.value.clone().unwrap_or_default();
+
+        for scope in self.scopes.iter().rev() {
+            if let Some(binding) = scope.get(&name) {
+                return binding.ty;
+            }
+        }
+
+        Type::ANY
+    }
+
+    // Lists and strings are indexed by numbers, maps also by strings
+    fn check_index(&mut self, object: Type, index: Type, bracket: &Token) {
+        match object {
+            Type::LIST | Type::STRING if !Type::NUMBER.accepts(index) => self.error(
+                format!("Index must be a number, found {}", index),
+                bracket,
+            ),
+            Type::MAP | Type::ANY if !matches!(index, Type::STRING | Type::NUMBER | Type::ANY) => self.error(
+                format!("Index must be a number or a string, found {}", index),
+                bracket,
+            ),
+            _ => (),
+        }
+    }
+
+    fn declare(&mut self, name: &Token, ty: Type) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.value.clone().unwrap_or_default(), Binding::new(ty));
+        }
+    }
+}
+
+impl ExprVisitor<Type> for TypeChecker {
+    fn visit_expr(&mut self, e: &Expr) -> Type {
+        match e {
+            Expr::UNARY(u) => self.visit_unary(u),
+            Expr::BINARY(b) => self.visit_binary(b),
+            Expr::LOGICAL(l) => self.visit_logical(l),
+            Expr::ASSIGN(a) => self.visit_assign(a),
+            Expr::GROUPING(g) => self.visit_grouping(g),
+            Expr::LITERAL(l) => self.visit_literal(l),
+            Expr::COMMA(c) => self.visit_comma(c),
+            Expr::TERNARY(t) => self.visit_ternary(t),
+            Expr::VARIABLE(v) => self.visit_variable(v),
+            Expr::LIST(l) => self.visit_list(l),
+            Expr::MAP(m) => self.visit_map(m),
+            Expr::INDEX(i) => self.visit_index(i),
+            Expr::SLICE(s) => self.visit_slice(s),
+            Expr::SETINDEX(s) => self.visit_set_index(s),
+            Expr::COMPOUND(c) => self.visit_compound(c),
+            Expr::CALL(c) => self.visit_call(c),
+            Expr::GET(g) => self.visit_get(g),
+            Expr::FORMAT(f) => self.visit_format(f),
+            Expr::LAMBDA(l) => self.visit_lambda(l),
+            Expr::MATCH(m) => self.visit_match(m),
+        }
+    }
+
+    fn visit_comma(&mut self, c: &Comma) -> Type {
+        self.visit_expr(&c.lhs);
+        self.visit_expr(&c.rhs)
+    }
+
+    fn visit_ternary(&mut self, t: &Ternary) -> Type {
+        self.visit_expr(&t.cond);
+        let then_type = self.visit_expr(&t.then_expr);
+        let else_type = self.visit_expr(&t.else_expr);
+
+        if then_type == else_type { then_type } else { Type::ANY }
+    }
+
+    fn visit_assign(&mut self, a: &Assign) -> Type {
+        let value = self.visit_expr(&a.value);
+        let declared = self.lookup(&a.name);
+        let signature = self.signature(&a.value);
+        self.assign(&a.name, signature);
+
+        if !declared.accepts(value) {
+            self.error(
+                format!("Cannot assign {} to variable of type {}", value, declared),
+                &a.name,
+            );
+        }
+
+        value
+    }
+
+    fn visit_literal(&mut self, l: &Literal) -> Type {
+        match l {
+            Literal::BOOL(_) => Type::BOOL,
+            Literal::NUMBER(_) | Literal::INTEGER(_) => Type::NUMBER,
+            Literal::STRING(_) => Type::STRING,
+            Literal::NIL => Type::NIL,
+            Literal::LIST(_) => Type::LIST,
+            Literal::MAP(_) => Type::MAP,
+            Literal::NATIVE(_) | Literal::FUNCTION(_) | Literal::MODULE(_) => Type::ANY,
+        }
+    }
+
+    fn visit_unary(&mut self, u: &Unary) -> Type {
+        let rhs = self.visit_expr(&u.rhs);
+
+        match u.op.kind {
+            TokenType::MINUS => {
+                if !Type::NUMBER.accepts(rhs) {
+                    self.error(format!("Operand of '-' must be a number, found {}", rhs), &u.op);
+                }
+                Type::NUMBER
+            },
+            TokenType::BANG if self.strict && !Type::BOOL.accepts(rhs) => {
+                self.error(format!("Operand of '!' must be a boolean, found {}", rhs), &u.op);
+                Type::BOOL
+            },
+            _ => Type::BOOL,
+        }
+    }
+
+    fn visit_binary(&mut self, b: &Binary) -> Type {
+        let lhs = self.visit_expr(&b.lhs);
+        let rhs = self.visit_expr(&b.rhs);
+
+        self.binary_type(&b.op.kind, lhs, rhs, &b.op)
+    }
+
+    // Gives one of the operands, so a type only when both have it
+    fn visit_logical(&mut self, l: &Logical) -> Type {
+        let lhs = self.visit_expr(&l.lhs);
+        let rhs = self.visit_expr(&l.rhs);
+
+        if self.strict && !Type::BOOL.accepts(lhs) {
+            self.error(
+                format!("Left operand of '{}' must be a boolean, found {}", l.op.get_lexeme().to_lowercase(), lhs),
+                &l.op,
+            );
+        }
+
+        if lhs == rhs { lhs } else { Type::ANY }
+    }
+
+    fn visit_grouping(&mut self, g: &Grouping) -> Type {
+        self.visit_expr(&g.expr)
+    }
+
+    fn visit_variable(&mut self, v: &Variable) -> Type {
+        self.lookup(&v.name)
+    }
+
+    fn visit_list(&mut self, l: &List) -> Type {
+        for expr in &l.elements {
+            self.visit_expr(expr);
+        }
+
+        Type::LIST
+    }
+
+    fn visit_map(&mut self, m: &Map) -> Type {
+        for (key, value) in &m.entries {
+            let ty = self.visit_expr(key);
+            if !matches!(ty, Type::STRING | Type::NUMBER | Type::ANY) {
+                self.error(format!("Map keys must be strings or numbers, found {}", ty), &m.brace);
+            }
+            self.visit_expr(value);
+        }
+
+        Type::MAP
+    }
+
+    fn visit_index(&mut self, i: &Index) -> Type {
+        let object = self.visit_expr(&i.object);
+        let index = self.visit_expr(&i.index);
+
+        if !matches!(object, Type::LIST | Type::STRING | Type::MAP | Type::ANY) {
+            self.error(format!("Cannot index a value of type {}", object), &i.bracket);
+        }
+
+        self.check_index(object, index, &i.bracket);
+
+        match object {
+            Type::STRING => Type::STRING,
+            _ => Type::ANY,
+        }
+    }
+
+    fn visit_slice(&mut self, s: &Slice) -> Type {
+        let object = self.visit_expr(&s.object);
+
+        for bound in [&s.start, &s.end].into_iter().flatten() {
+            let ty = self.visit_expr(bound);
+            if !Type::NUMBER.accepts(ty) {
+                self.error(format!("Slice bounds must be numbers, found {}", ty), &s.bracket);
+            }
+        }
+
+        match object {
+            Type::LIST | Type::STRING | Type::ANY => object,
+            _ => {
+                self.error(format!("Cannot slice a value of type {}", object), &s.bracket);
+                Type::ANY
+            },
+        }
+    }
+
+    fn visit_set_index(&mut self, s: &SetIndex) -> Type {
+        let object = self.visit_expr(&s.object);
+        let index = self.visit_expr(&s.index);
+        let value = self.visit_expr(&s.value);
+
+        if !matches!(object, Type::LIST | Type::MAP | Type::ANY) {
+            self.error(format!("Cannot assign to an element of {}", object), &s.bracket);
+        }
+
+        self.check_index(object, index, &s.bracket);
+
+        value
+    }
+
+    fn visit_compound(&mut self, c: &Compound) -> Type {
+        let current = self.visit_expr(&c.target);
+        let value = self.visit_expr(&c.value);
+
+        if c.is_increment() && !Type::NUMBER.accepts(current) {
+            let symbol = if c.operator.kind == TokenType::PLUSPLUS { "++" } else { "--" };
+            self.error(format!("Operand of '{}' must be a number, found {}", symbol, current), &c.operator);
+        }
+
+        let updated = self.binary_type(&c.binary_op(), current, value, &c.operator);
+
+        if let Expr::VARIABLE(v) = &*c.target {
+            self.assign(&v.name, None);
+            let declared = self.lookup(&v.name);
+            if !declared.accepts(updated) {
+                self.error(
+                    format!("Cannot assign {} to variable of type {}", updated, declared),
+                    &v.name,
+                );
+            }
+        }
+
+        if c.postfix { current } else { updated }
+    }
+
+    // Calls of a lambda with a known signature are checked against its annotations
+    fn visit_call(&mut self, c: &Call) -> Type {
+        let signature = self.signature(&c.callee);
+        self.visit_expr(&c.callee);
+        let arguments: Vec<Type> = c.arguments.iter().map(|expr| self.visit_expr(expr)).collect();
+
+        let signature = match signature {
+            Some(signature) => signature,
+            None => return Type::ANY,
+        };
+
+        if signature.params.len() != arguments.len() {
+            self.error(
+                format!("Expected {} arguments but got {}", signature.params.len(), arguments.len()),
+                &c.paren,
+            );
+        } else {
+            for (i, (param, argument)) in signature.params.iter().zip(arguments).enumerate() {
+                if !param.accepts(argument) {
+                    self.error(format!("Argument {} must be {}, found {}", i + 1, param, argument), &c.paren);
+                }
+            }
+        }
+
+        signature.returns
+    }
+
+    // Any value can be interpolated
+    fn visit_format(&mut self, f: &Format) -> Type {
+        for expr in &f.parts {
+            self.visit_expr(expr);
+        }
+
+        Type::STRING
+    }
+
+    // An arm is unreachable if all its patterns are covered by earlier arms without guard
+    fn visit_match(&mut self, m: &Match) -> Type {
+        let subject = self.visit_expr(&m.subject);
+        let mut result: Option<Type> = None;
+
+        for (i, arm) in m.arms.iter().enumerate() {
+            let unreachable = arm.patterns.iter().all(|later| {
+                m.arms[..i].iter()
+                    .filter(|earlier| earlier.guard.is_none())
+                    .any(|earlier| earlier.patterns.iter().any(|p| TypeChecker::covers(p, later)))
+            });
+
+            if unreachable {
+                self.warning("Unreachable match arm".to_string(), &arm.token);
+            }
+
+            self.push_scope();
+            if let Some(Pattern::BINDING(name)) = arm.patterns.first() {
+                self.declare(name, subject);
+            }
+            if let Some(guard) = &arm.guard {
+                self.visit_expr(guard);
+            }
+            let body = self.visit_expr(&arm.body);
+            self.pop_scope();
+
+            result = match result {
+                Some(ty) if ty != body => Some(Type::ANY),
+                _ => Some(body),
+            };
+        }
+
+        result.unwrap_or(Type::ANY)
+    }
+
+    // Parameters have their declared types inside the body, and the returns are checked
+    // against the declared return type
+    fn visit_lambda(&mut self, l: &Lambda) -> Type {
+        self.push_scope();
+        for (param, annotation) in l.params.iter().zip(&l.annotations) {
+            let ty = self.annotated(annotation);
+            self.declare(param, ty);
+        }
+
+        let returns = self.annotated(&l.returns);
+        self.returns.push(returns);
+        for stmt in l.body.iter() {
+            self.visit_stmt(stmt);
+        }
+        self.returns.pop();
+        self.pop_scope();
+
+        Type::ANY
+    }
+
+    // Modules are loaded at runtime, so their members are not known here
+    fn visit_get(&mut self, g: &Get) -> Type {
+        self.visit_expr(&g.object);
+        Type::ANY
+    }
+}
+
+impl StmtVisitor<()> for TypeChecker {
+    fn visit_stmt(&mut self, s: &Stmt) {
+        match s {
+            Stmt::BLOCK(b) => self.visit_block(b),
+            Stmt::EXPRESSION(e) => self.visit_expression(e),
+            Stmt::PRINT(p) => self.visit_print(p),
+            Stmt::VAR(v) => self.visit_var(v),
+            Stmt::IF(i) => self.visit_if(i),
+            Stmt::WHILE(w) => self.visit_while(w),
+            Stmt::FORIN(f) => self.visit_for_in(f),
+            Stmt::BREAK(b) => self.visit_break(b),
+            Stmt::CONTINUE(c) => self.visit_continue(c),
+            Stmt::IMPORT(i) => self.visit_import(i),
+            Stmt::THROW(t) => self.visit_throw(t),
+            Stmt::TRY(t) => self.visit_try(t),
+            Stmt::RETURN(r) => self.visit_return(r),
+        }
+    }
+
+    fn visit_expression(&mut self, e: &Expression) {
+        self.visit_expr(&e.expr);
+    }
+
+    fn visit_print(&mut self, p: &Print) {
+        self.visit_expr(&p.expr);
+    }
+
+    fn visit_var(&mut self, v: &Var) {
+        let value = v.initializer.as_ref().map(|expr| self.visit_expr(expr));
+
+        let declared = self.annotated(&v.annotation);
+        let signature = v.initializer.as_ref().and_then(|expr| self.signature(expr));
+
+        match value {
+            Some(ty) if !declared.accepts(ty) => self.error(
+                format!("Cannot initialize variable of type {} with {}", declared, ty),
+                &v.name,
+            ),
+            None if !declared.accepts(Type::NIL) => self.error(
+                format!("Variable of type {} must be initialized", declared),
+                &v.name,
+            ),
+            _ => (),
+        }
+
+        // A constant cannot be shadowed by a declaration in its own scope
+        let key = v.name.value.clone().unwrap_or_default();
+        if self.scopes.last().and_then(|scope| scope.get(&key)).is_some_and(|binding| binding.constant) {
+            self.error(format!("Cannot redeclare constant '{}'", key), &v.name);
+        }
+
+        if let Some(scope) = self.scopes.last_mut() {
+            let mut binding = Binding::new(declared);
+            binding.constant = v.constant;
+            binding.var = if v.constant || v.initializer.is_none() { None } else { Some(v.name.clone()) };
+            binding.signature = signature;
+            scope.insert(key, binding);
+        }
+    }
+
+    fn visit_block(&mut self, b: &Block) {
+        self.push_scope();
+        for stmt in &b.statements {
+            self.visit_stmt(stmt);
+        }
+        self.pop_scope();
+    }
+
+    fn visit_if(&mut self, i: &If) {
+        self.visit_expr(&i.cond);
+        self.visit_stmt(&i.then_branch);
+        if let Some(stmt) = &i.else_branch {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    fn visit_while(&mut self, w: &While) {
+        self.visit_expr(&w.cond);
+        self.visit_stmt(&w.body);
+        if let Some(expr) = &w.increment {
+            self.visit_expr(expr);
+        }
+    }
+
+    // The loop variable is a new scope, holding an element, a key or a character
+    fn visit_for_in(&mut self, f: &ForIn) {
+        let iterable = self.visit_expr(&f.iterable);
+
+        let element = match iterable {
+            Type::STRING => Type::STRING,
+            Type::LIST | Type::MAP | Type::ANY => Type::ANY,
+            other => {
+                self.error(format!("Cannot iterate over {}", other), &f.name);
+                Type::ANY
+            },
+        };
+
+        self.push_scope();
+        self.declare(&f.name, element);
+        self.visit_stmt(&f.body);
+        self.pop_scope();
+    }
+
+    fn visit_break(&mut self, _b: &Break) {}
+
+    fn visit_continue(&mut self, _c: &Continue) {}
+
+    // A bare `return` gives nil
+    fn visit_return(&mut self, r: &Return) {
+        let value = match &r.value {
+            Some(expr) => self.visit_expr(expr),
+            None => Type::NIL,
+        };
+
+        if let Some(declared) = self.returns.last().cloned() {
+            if !declared.accepts(value) {
+                self.error(format!("Cannot return {} from a function returning {}", value, declared), &r.keyword);
+            }
+        }
+    }
+
+    fn visit_throw(&mut self, t: &Throw) {
+        self.visit_expr(&t.value);
+    }
+
+    // The caught value can be anything thrown, so it is `any`
+    fn visit_try(&mut self, t: &Try) {
+        self.visit_block(&Block::new(t.body.clone(), t.keyword.line));
+
+        if let (Some(name), Some(body)) = (&t.catch_name, &t.catch_body) {
+            self.push_scope();
+            self.declare(name, Type::ANY);
+            self.visit_block(&Block::new(body.clone(), name.line));
+            self.pop_scope();
+        }
+
+        if let Some(body) = &t.finally_body {
+            self.visit_block(&Block::new(body.clone(), t.keyword.line));
+        }
+    }
+
+    fn visit_import(&mut self, i: &Import) {
+        let name = match &i.alias {
+            Some(alias) => alias.value.clone().unwrap_or_default(),
+            None => Path::new(&i.path.value.clone().unwrap_or_default())
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        };
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name, Binding::new(Type::ANY));
+        }
+    }
+}
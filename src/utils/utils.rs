@@ -1,18 +1,42 @@
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, Read};
 use std::path::PathBuf;
+use std::process;
 
 pub fn read_file(filename: &PathBuf) -> Vec<char> {
-    let mut f = match File::open(filename) {
-        Ok(file) => file,
+    match try_read_file(filename) {
+        Ok(source) => source,
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+            let path = filename.display();
+            eprintln!("[UTILS] {err} | {path}");
+            process::exit(exitcode::DATAERR);
+        },
         Err(err) => {
             let path = filename.display();
             panic!("[UTILS] {err} | {path}");
         },
-    };
+    }
+}
+
+// Source files must be UTF-8, invalid bytes are reported with their position
+pub fn try_read_file(filename: &PathBuf) -> io::Result<Vec<char>> {
+    let mut f = File::open(filename)?;
 
     let mut raw: Vec<u8> = Vec::new();
-    let _ = f.read_to_end(&mut raw);
-    
-    raw.iter().map(|b| *b as char).collect::<Vec<_>>()
+    f.read_to_end(&mut raw)?;
+
+    match String::from_utf8(raw) {
+        Ok(source) => Ok(source.chars().collect::<Vec<_>>()),
+        Err(err) => {
+            let valid = &err.as_bytes()[..err.utf8_error().valid_up_to()];
+            let line = valid.iter().filter(|b| **b == b'\n').count() + 1;
+
+            Err(
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid UTF-8 at line {}, byte {}", line, valid.len()),
+                )
+            )
+        },
+    }
 }
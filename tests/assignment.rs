@@ -0,0 +1,30 @@
+// End-to-end tests for assignment targets, running scripts through `rslox -i`.
+
+mod common;
+
+#[test]
+fn variables_and_elements() {
+    let source = "var a = 1;\nvar l = [1, 2];\n{ a += 2; a++; --a; l[0] *= 10; l[1]++; }\nprint a;\nprint l;\n";
+    let output = common::run("valid", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["3", "[10, 3]"]);
+}
+
+#[test]
+fn invalid_targets_are_parse_errors() {
+    let source = "var f = fun() { return 1; };\n1 += 2;\nf()++;\n++1;\n(1) -= 3;\n1 = 2;\nprint \"after\";\n";
+    let output = common::run("invalid", source, &[]);
+
+    // Reported like the other syntax errors instead of crashing, and the parsing goes on
+    assert_eq!(output.code, Some(65), "{:?}", output.lines());
+    assert_eq!(
+        output.lines(),
+        vec![
+            "2 at 'PLUSEQUAL' Invalid assignment target.",
+            "3 at 'PLUSPLUS' Invalid assignment target.",
+            "4 at 'PLUSPLUS' Invalid assignment target.",
+            "5 at 'MINUSEQUAL' Invalid assignment target.",
+            "6 at 'EQUAL' Invalid assignment target.",
+        ]
+    );
+}
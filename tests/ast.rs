@@ -0,0 +1,130 @@
+// End-to-end tests for `rslox ast`, writing trees and reading them back.
+
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+// Uses every kind of statement and expression
+const SCRIPT: &str = r#"
+var a: number = 1, 2;
+const b = a > 0 ? "yes" : "no";
+var l = [1, 2.5, "s", true, false, nil];
+var m = {"k": l};
+l[0] = l[1:2];
+var s = l[:1];
+a += 2; a++; --a;
+print m.k;
+var f = fun (x, y) { return x + -y; };
+var g = (x) => x * 2;
+var h = fun (a: number, b): string { return "${a}${b}"; };
+var k = (x: number): number => x;
+var t = "v=${a}!";
+var r = match (a) { 1 | 2 => "small", number if a > 3 => "big", _ => "other" };
+var q = match (l) { "s" => 1, n => n };
+{ var inner = (a); }
+if (a == 1) print a; else print b;
+while (a < 10) { a = a + 1; if (a == 5) break; else continue; }
+for (var i = 0; i < 3; i = i + 1) print i;
+for (var k in m) print k;
+try { throw "x"; } catch (e) { print e; } finally { print "done"; }
+import "lib.lox" as lib;
+print len(l);
+"#;
+
+fn write(name: &str, source: &str) -> PathBuf {
+    let path = common::temp_path(name);
+    fs::write(&path, source).unwrap();
+    path
+}
+
+// Runs `rslox ast` on a file, returning stdout, stderr and whether it succeeded
+fn ast(path: &PathBuf, args: &[&str]) -> (String, String, bool) {
+    let path = path.to_string_lossy().to_string();
+    let mut all = vec!["ast", path.as_str()];
+    all.extend(args);
+
+    let output = common::rslox(&all);
+    let success = output.success();
+    (output.stdout, output.stderr, success)
+}
+
+#[test]
+fn json_round_trip() {
+    let source = write("round-trip.lox", SCRIPT);
+    let (json, stderr, success) = ast(&source, &["--format", "json"]);
+    assert!(success, "{}", stderr);
+
+    let serialized = write("round-trip.json", &json);
+    let (again, stderr, success) = ast(&serialized, &["--input", "json"]);
+    assert!(success, "{}", stderr);
+    assert_eq!(json, again);
+
+    let (from_source, _, _) = ast(&source, &["--format", "sexpr"]);
+    let (from_json, _, _) = ast(&serialized, &["--input", "json", "--format", "sexpr"]);
+    assert_eq!(from_source, from_json);
+
+    let _ = fs::remove_file(source);
+    let _ = fs::remove_file(serialized);
+}
+
+#[test]
+fn json_nodes() {
+    let source = write("nodes.lox", "var x = 1;\nprint x + 2.5;\n");
+    let (json, _, success) = ast(&source, &[]);
+    assert!(success);
+
+    let tree: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(tree["kind"], "PROGRAM");
+    assert_eq!(tree["version"], 1);
+
+    let statements = tree["statements"].as_array().unwrap();
+    assert_eq!(statements[0]["kind"], "VAR");
+    assert_eq!(statements[0]["name"], serde_json::json!({ "type": "IDENTIFIER", "value": "x", "line": 1 }));
+    assert_eq!(statements[0]["initializer"]["type"], "INTEGER");
+
+    let print = &statements[1];
+    assert_eq!(print["line"], 2);
+    assert_eq!(print["expr"]["kind"], "BINARY");
+    assert_eq!(print["expr"]["op"], serde_json::json!({ "type": "PLUS", "value": null, "line": 2 }));
+    assert_eq!(print["expr"]["rhs"]["value"], 2.5);
+
+    let _ = fs::remove_file(source);
+}
+
+#[test]
+fn sexpr_output() {
+    let source = write("sexpr.lox", "print -x;\n");
+    let (sexpr, _, success) = ast(&source, &["--format", "sexpr"]);
+    assert!(success);
+    assert_eq!(
+        sexpr.trim(),
+        r#"(PROGRAM :statements ((PRINT :expr (UNARY :op (MINUS nil 1) :rhs (VARIABLE :name (IDENTIFIER "x" 1))) :line 1)) :version 1)"#
+    );
+
+    let _ = fs::remove_file(source);
+}
+
+#[test]
+fn invalid_json() {
+    let cases = [
+        ("version", r#"{"version": 2, "statements": []}"#, "Unsupported AST version 2"),
+        ("kind", r#"{"version": 1, "statements": [{"kind": "LOOP", "line": 1}]}"#, "Unknown statement kind 'LOOP'"),
+        (
+            "token",
+            r#"{"version": 1, "statements": [{"kind": "BREAK", "line": 1, "keyword": {"type": "NOPE", "line": 1}}]}"#,
+            "Unknown token type in 'keyword'",
+        ),
+        ("syntax", "{", "EOF while parsing"),
+    ];
+
+    for (name, json, expected) in cases {
+        let path = write(&format!("invalid-{}.json", name), json);
+        let (_, stderr, success) = ast(&path, &["--input", "json"]);
+        assert!(!success, "{}", name);
+        assert!(stderr.contains(expected), "{}: {}", name, stderr);
+        let _ = fs::remove_file(path);
+    }
+}
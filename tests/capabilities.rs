@@ -0,0 +1,80 @@
+// End-to-end tests for `--capabilities`, running scripts through `rslox -i`.
+
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// A directory of its own for each test, with the files of the scripts in it
+fn directory(name: &str) -> PathBuf {
+    let dir = common::temp_path(name);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Runs a script from the directory with the arguments, returning its stdout and whether it succeeded
+fn run(dir: &Path, source: &str, args: &[&str]) -> (String, bool) {
+    let path = dir.join("main.lox");
+    fs::write(&path, source).unwrap();
+
+    let output = common::run_file(&path, args);
+    let success = output.success();
+    (output.stdout, success)
+}
+
+#[test]
+fn import_requires_fs() {
+    let dir = directory("import");
+    fs::write(dir.join("lib.lox"), "print \"loaded\";\nvar x = 1;\n").unwrap();
+
+    let (output, success) = run(&dir, "import \"lib.lox\";\n", &["--capabilities", "none"]);
+    assert!(!success);
+    assert!(output.contains("import requires the 'fs' capability"), "{}", output);
+    assert!(!output.contains("loaded"), "{}", output);
+
+    let (output, success) = run(&dir, "import \"lib.lox\";\nprint lib.x;\n", &["--capabilities", "fs"]);
+    assert!(success, "{}", output);
+    assert!(output.contains("loaded"), "{}", output);
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn denied_import_hides_files() {
+    let dir = directory("hidden");
+    fs::write(dir.join("lib.lox"), "var x = 1;\n").unwrap();
+
+    // The error is the same whether the file exists or not
+    let source = "\
+        try { import \"lib.lox\"; } catch (e) { print e; }\n\
+        try { import \"missing.lox\"; } catch (e) { print e; }\n";
+    let (output, _) = run(&dir, source, &["--capabilities", "time"]);
+    let denied = output.matches("import requires the 'fs' capability").count();
+    assert_eq!(denied, 2, "{}", output);
+    assert!(!output.contains("Cannot find"), "{}", output);
+
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn natives_require_their_capability() {
+    let dir = directory("natives");
+    let script = format!("readFile({:?})", dir.join("main.lox").display().to_string());
+    let cases = [
+        ("clock()", "time"),
+        ("random()", "random"),
+        ("env(\"HOME\")", "env"),
+        (script.as_str(), "fs"),
+    ];
+
+    for (call, capability) in cases {
+        let (output, success) = run(&dir, &format!("print {};\n", call), &["--capabilities", "none"]);
+        assert!(!success, "{}", call);
+        assert!(output.contains(&format!("requires the '{}' capability", capability)), "{}", output);
+
+        let (output, success) = run(&dir, &format!("print {};\n", call), &["--capabilities", capability]);
+        assert!(success, "{}: {}", call, output);
+    }
+
+    let _ = fs::remove_dir_all(dir);
+}
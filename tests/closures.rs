@@ -0,0 +1,99 @@
+// End-to-end tests for closures, running scripts through `rslox -i`.
+
+mod common;
+
+// Runs the script and compares what it printed with the expected values
+fn check(name: &str, source: &str, expected: &[&str]) {
+    let output = common::run(name, source, &[]);
+    assert!(output.success(), "script failed: {:?}", output.lines());
+    assert_eq!(output.lines(), expected);
+}
+
+#[test]
+fn counters_keep_their_state_across_calls() {
+    let source = "
+var makeCounter = fun() {
+    var count = 0;
+    return fun() { count = count + 1; return count; };
+};
+var a = makeCounter();
+var b = makeCounter();
+print a();
+print a();
+print b();
+print a();
+print b();
+";
+
+    // Each counter has its own `count`, kept between its calls
+    check("counters", source, &["1", "2", "1", "3", "2"]);
+}
+
+#[test]
+fn closures_share_the_captured_variable() {
+    let source = "
+var make = fun() {
+    var value = 0;
+    var get = fun() { return value; };
+    var add = fun(n) { value = value + n; };
+    return [get, add];
+};
+var pair = make();
+var get = pair[0];
+var add = pair[1];
+add(5);
+add(2);
+print get();
+";
+
+    check("shared", source, &["7"]);
+}
+
+#[test]
+fn recursion_through_a_global() {
+    let source = "
+var fib = fun(n) { if (n < 2) return n; return fib(n - 1) + fib(n - 2); };
+print fib(10);
+var fact = fun(n) { return n <= 1 ? 1 : n * fact(n - 1); };
+print fact(10);
+";
+
+    // The lambda finds itself through the global it is assigned to, looked up at each call
+    check("recursion", source, &["55", "3628800"]);
+}
+
+#[test]
+fn return_inside_filter() {
+    let source = "
+var evens = fun(l) {
+    return filter(l, fun(x) { if (x % 2 == 0) return true; return false; });
+};
+print evens([1, 2, 3, 4, 5, 6]);
+var count = fun(l, limit) {
+    var kept = filter(l, fun(x) { return x > limit; });
+    return len(kept);
+};
+print count([1, 5, 2, 8, 3], 2);
+";
+
+    // `return` leaves the callback only, the enclosing function goes on after `filter`
+    check("filter", source, &["[2, 4, 6]", "3"]);
+}
+
+#[test]
+fn malformed_lambdas_are_parse_errors() {
+    // Each malformed lambda is reported and the parsing goes on with the next statement
+    let source = "var f = fun(1) {};\nvar g = fun(a, ) {};\nvar h = fun(a) print a;\nvar k = fun(a {};\nvar m = fun(a: ) {};\n";
+    let output = common::run("malformed", source, &[]);
+    assert_eq!(output.code, Some(65));
+    assert_eq!(
+        output.lines(),
+        vec![
+            "1 at 'INTEGER' Expect parameter name",
+            "2 at 'RIGHTPAREN' Expect parameter name",
+            "3 at 'PRINT' Expect '{' before lambda body",
+            "4 at 'LEFTBRACE' Expect ')' after parameters",
+            "5 at 'RIGHTPAREN' Expect type name after ':'",
+        ]
+    );
+}
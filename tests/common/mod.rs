@@ -0,0 +1,61 @@
+// Helpers shared by the end-to-end tests, running scripts through the `rslox` binary.
+// Each test file only uses some of them.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// What a run wrote and how it exited
+pub struct Output {
+    pub stdout: String,
+    pub stderr: String,
+    pub code: Option<i32>,
+}
+
+impl Output {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+
+    // With `--quiet`, the lines printed by the script and the errors reported
+    pub fn lines(&self) -> Vec<String> {
+        self.stdout.lines().map(|line| line.to_string()).collect()
+    }
+}
+
+// A path of its own in the temporary directory, for this test file and this file name
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("rslox-{}-{}-{}", env!("CARGO_CRATE_NAME"), std::process::id(), name))
+}
+
+// Runs `rslox` with the arguments, as they are
+pub fn rslox(args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_rslox"))
+        .args(args)
+        .output()
+        .expect("failed to run rslox");
+
+    Output {
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        code: output.status.code(),
+    }
+}
+
+// Runs a script file with `--quiet` and the arguments
+pub fn run_file(path: &Path, args: &[&str]) -> Output {
+    let path = path.to_string_lossy().to_string();
+    let mut all: Vec<&str> = args.to_vec();
+    all.extend(["--quiet", "-i", &path]);
+    rslox(&all)
+}
+
+// Runs a script from a temporary file with `--quiet` and the arguments
+pub fn run(name: &str, source: &str, args: &[&str]) -> Output {
+    let path = temp_path(&format!("{}.lox", name));
+    fs::write(&path, source).unwrap();
+    let output = run_file(&path, args);
+    let _ = fs::remove_file(&path);
+    output
+}
@@ -0,0 +1,60 @@
+// End-to-end tests for `const` declarations, running scripts through `rslox -i`.
+
+mod common;
+
+#[test]
+fn constants_are_read_like_variables() {
+    let source = "const x = 1;\nconst f = fun () { return x + 1; };\n{ var x = 2; print x; }\nprint x;\nprint f();\n";
+    let output = common::run("constants", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["2", "1", "2"]);
+}
+
+#[test]
+fn constants_cannot_be_assigned() {
+    let cases = [
+        "const x = 1;\nx = 2;\n",
+        "const x = 1;\nx += 1;\n",
+        "const x = 1;\nvar f = fun () { x = 2; };\n",
+    ];
+
+    for source in cases {
+        let output = common::run("assign-constant", source, &[]);
+        assert_eq!(output.code, Some(65), "{}", source);
+        assert!(
+            output.lines()[0].ends_with("Type error: Cannot assign to constant 'x'"),
+            "{}: {:?}",
+            source,
+            output.lines()
+        );
+    }
+}
+
+#[test]
+fn constants_cannot_be_redeclared_in_their_scope() {
+    let cases = [
+        ("const x = 1;\nvar x = 2;\nprint x;\n", "2 at 'x' Type error: Cannot redeclare constant 'x'"),
+        ("const x = 1;\nconst x = 2;\n", "2 at 'x' Type error: Cannot redeclare constant 'x'"),
+        ("{\n    const x = 1;\n    var x = 2;\n}\n", "3 at 'x' Type error: Cannot redeclare constant 'x'"),
+    ];
+
+    for (source, expected) in cases {
+        let output = common::run("redeclare-constant", source, &[]);
+        assert_eq!(output.code, Some(65), "{}", source);
+        assert_eq!(output.lines(), vec![expected], "{}", source);
+    }
+}
+
+#[test]
+fn variables_can_become_constants() {
+    let output = common::run("var-to-constant", "var x = 1;\nconst x = 2;\nprint x;\n", &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["2"]);
+}
+
+#[test]
+fn constants_must_be_initialized() {
+    let output = common::run("uninitialized-constant", "const x;\n", &[]);
+    assert_eq!(output.code, Some(65));
+    assert_eq!(output.lines(), vec!["1 at 'SEMICOLON' Expect '=' after constant name."]);
+}
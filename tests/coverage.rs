@@ -0,0 +1,117 @@
+// End-to-end tests for `--coverage`, checking the lcov and the annotated reports.
+
+mod common;
+
+use std::fs;
+
+// Runs the script with coverage, returning the lcov and the annotated reports
+fn coverage(name: &str, source: &str) -> (String, String) {
+    let path = common::temp_path(&format!("{}.lox", name));
+    fs::write(&path, source).unwrap();
+
+    let output = common::run_file(&path, &["--coverage"]);
+    assert!(output.success(), "{:?}", output.lines());
+
+    let lcov = fs::read_to_string(path.with_extension("info")).unwrap();
+    let report = fs::read_to_string(path.with_extension("cov")).unwrap();
+    (lcov, report)
+}
+
+#[test]
+fn lcov_records() {
+    let source = "\
+var x = 1;
+if (x > 0) print \"positive\";
+var y = x > 5 ? \"big\" : \"small\";
+var z = nil or x;
+var f = fun () {
+    return 1;
+};
+";
+    let (lcov, _) = coverage("lcov", source);
+
+    let lines: Vec<&str> = lcov.lines().collect();
+    assert_eq!(lines[0], "TN:");
+    assert!(lines[1].starts_with("SF:") && lines[1].ends_with("lcov.lox"), "{}", lines[1]);
+    assert_eq!(
+        lines[2..].to_vec(),
+        vec![
+            "DA:1,1",
+            // The `if` and the `print`, as each statement counts on the line it starts
+            "DA:2,2",
+            "DA:3,1",
+            "DA:4,1",
+            "DA:5,1",
+            // The return of the lambda never ran
+            "DA:6,0",
+            "BRDA:2,0,0,1",
+            "BRDA:2,0,1,0",
+            "BRDA:3,1,0,0",
+            "BRDA:3,1,1,1",
+            "BRDA:4,2,0,1",
+            "BRDA:4,2,1,0",
+            "BRF:6",
+            "BRH:3",
+            "LF:6",
+            "LH:5",
+            "end_of_record",
+        ]
+    );
+}
+
+#[test]
+fn branches_never_reached() {
+    let source = "\
+var f = fun (n) { return n > 0 and n < 10; };
+";
+    let (lcov, _) = coverage("unreached", source);
+
+    // A branch point that never ran has `-` as its counts
+    assert!(lcov.contains("BRDA:1,0,0,-\nBRDA:1,0,1,-\n"), "{}", lcov);
+    assert!(lcov.contains("BRH:0\n"), "{}", lcov);
+}
+
+#[test]
+fn annotated_report() {
+    let source = "\
+var check = fun (n) {
+    if (n > 0 and n < 10) return \"digit\";
+    return \"other\";
+};
+check(5);
+check(-1);
+";
+    let (_, report) = coverage("annotated", source);
+
+    let lines: Vec<&str> = report.lines().collect();
+    assert!(lines[0].starts_with("Coverage for ") && lines[0].ends_with("annotated.lox"), "{}", lines[0]);
+    assert_eq!(lines[1], "Lines: 5/5 (100.0%)  Branches: 4/4 (100.0%)");
+    assert_eq!(lines[2], "");
+    assert_eq!(
+        lines[3..].to_vec(),
+        vec![
+            "     1 |      1 | var check = fun (n) {",
+            "     2 |      3 |     if (n > 0 and n < 10) return \"digit\";",
+            "       |        |   branch 0: then 1, else 1",
+            "       |        |   branch 1: right 1, short-circuit 1",
+            "     3 |      1 |     return \"other\";",
+            "     4 |      - | };",
+            "     5 |      1 | check(5);",
+            "     6 |      1 | check(-1);",
+        ]
+    );
+}
+
+#[test]
+fn annotated_report_marks_lines_never_run() {
+    let source = "\
+var x = 1;
+if (x > 5) {
+    print \"big\";
+}
+";
+    let (_, report) = coverage("never-run", source);
+
+    assert!(report.contains("Lines: 2/3 (66.7%)  Branches: 1/2 (50.0%)"), "{}", report);
+    assert!(report.contains("     3 |  ##### |     print \"big\";"), "{}", report);
+}
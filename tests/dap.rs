@@ -0,0 +1,326 @@
+// End-to-end tests for the Debug Adapter Protocol server, driving `rslox dap`
+// with a scripted client over stdin/stdout.
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use serde_json::{json, Value};
+
+struct Client {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    seq: i64,
+    output: String,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_rslox"))
+            .arg("dap")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("failed to start rslox dap");
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+
+        Client { child, stdin, stdout, seq: 0, output: String::new() }
+    }
+
+    fn send(&mut self, command: &str, arguments: Value) -> i64 {
+        self.seq += 1;
+        let body = json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        }).to_string();
+
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.stdin.flush().unwrap();
+        self.seq
+    }
+
+    fn read(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            assert!(self.stdout.read_line(&mut header).unwrap() > 0, "adapter closed stdout");
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse().unwrap();
+            }
+        }
+
+        let mut body = vec![0; length];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    // Reads until the wanted message, collecting program output along the way
+    fn expect(&mut self, kind: &str, name: &str) -> Value {
+        loop {
+            let msg = self.read();
+            if msg["type"] == "event" && msg["event"] == "output" {
+                self.output.push_str(msg["body"]["output"].as_str().unwrap());
+            }
+
+            let key = if kind == "event" { "event" } else { "command" };
+            if msg["type"] == kind && msg[key] == name {
+                return msg;
+            }
+        }
+    }
+
+    fn request(&mut self, command: &str, arguments: Value) -> Value {
+        let seq = self.send(command, arguments);
+        let response = self.expect("response", command);
+        assert_eq!(response["request_seq"], seq);
+        assert_eq!(response["success"], true, "{} failed: {}", command, response);
+        response["body"].clone()
+    }
+
+    fn finish(mut self) -> String {
+        self.request("disconnect", json!({}));
+        assert!(self.child.wait().unwrap().success());
+        self.output
+    }
+}
+
+fn write_program(name: &str, source: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rslox_dap_{}_{}.lox", name, std::process::id()));
+    fs::write(&path, source).unwrap();
+    path
+}
+
+fn launch(client: &mut Client, program: &PathBuf, stop_on_entry: bool, lines: &[usize]) {
+    let caps = client.request("initialize", json!({ "adapterID": "rslox" }));
+    assert_eq!(caps["supportsConfigurationDoneRequest"], true);
+    client.expect("event", "initialized");
+
+    client.request("launch", json!({ "program": program, "stopOnEntry": stop_on_entry }));
+
+    let bps: Vec<Value> = lines.iter().map(|l| json!({ "line": l })).collect();
+    let body = client.request("setBreakpoints", json!({
+        "source": { "path": program },
+        "breakpoints": bps,
+    }));
+    assert_eq!(body["breakpoints"].as_array().unwrap().len(), lines.len());
+
+    client.request("configurationDone", json!({}));
+}
+
+// Variables of the scope with that name in the innermost frame
+fn scope_variables(client: &mut Client, scope: &str) -> Vec<(String, String)> {
+    let trace = client.request("stackTrace", json!({ "threadId": 1 }));
+    let frame = trace["stackFrames"][0]["id"].clone();
+    let scopes = client.request("scopes", json!({ "frameId": frame }));
+    let reference = scopes["scopes"].as_array().unwrap().iter()
+        .find(|s| s["name"] == scope)
+        .map(|s| s["variablesReference"].clone())
+        .unwrap_or_else(|| panic!("no {} scope in {}", scope, scopes));
+    let body = client.request("variables", json!({ "variablesReference": reference }));
+
+    body["variables"].as_array().unwrap().iter()
+        .map(|v| (v["name"].as_str().unwrap().to_string(), v["value"].as_str().unwrap().to_string()))
+        .collect()
+}
+
+fn current_line(client: &mut Client) -> u64 {
+    let body = client.request("stackTrace", json!({ "threadId": 1 }));
+    body["stackFrames"][0]["line"].as_u64().unwrap()
+}
+
+#[test]
+fn breakpoint_step_and_continue() {
+    let program = write_program("breakpoint", "var a = 1;\nvar b = \"two\";\nprint a;\nprint b;\n");
+    let mut client = Client::start();
+    launch(&mut client, &program, false, &[2]);
+
+    let stopped = client.expect("event", "stopped");
+    assert_eq!(stopped["body"]["reason"], "breakpoint");
+
+    let threads = client.request("threads", json!({}));
+    assert_eq!(threads["threads"][0]["name"], "main");
+    assert_eq!(current_line(&mut client), 2);
+    assert_eq!(scope_variables(&mut client, "Globals"), vec![("a".to_string(), "1".to_string())]);
+
+    client.request("next", json!({ "threadId": 1 }));
+    let stopped = client.expect("event", "stopped");
+    assert_eq!(stopped["body"]["reason"], "step");
+    assert_eq!(current_line(&mut client), 3);
+    assert_eq!(
+        scope_variables(&mut client, "Globals"),
+        vec![("a".to_string(), "1".to_string()), ("b".to_string(), "\"two\"".to_string())]
+    );
+
+    client.request("continue", json!({ "threadId": 1 }));
+    let exited = client.expect("event", "exited");
+    assert_eq!(exited["body"]["exitCode"], 0);
+    client.expect("event", "terminated");
+
+    assert_eq!(client.finish(), "1\ntwo\n");
+    fs::remove_file(program).unwrap();
+}
+
+#[test]
+fn stop_on_entry_and_step_in() {
+    let program = write_program("entry", "var x = 10;\nx = x * 2;\nprint x;\n");
+    let mut client = Client::start();
+    launch(&mut client, &program, true, &[]);
+
+    let stopped = client.expect("event", "stopped");
+    assert_eq!(stopped["body"]["reason"], "entry");
+    assert_eq!(current_line(&mut client), 1);
+    assert!(scope_variables(&mut client, "Globals").is_empty());
+
+    client.request("stepIn", json!({ "threadId": 1 }));
+    client.expect("event", "stopped");
+    assert_eq!(current_line(&mut client), 2);
+
+    client.request("stepIn", json!({ "threadId": 1 }));
+    client.expect("event", "stopped");
+    assert_eq!(current_line(&mut client), 3);
+    assert_eq!(scope_variables(&mut client, "Globals"), vec![("x".to_string(), "20".to_string())]);
+
+    client.request("continue", json!({ "threadId": 1 }));
+    client.expect("event", "terminated");
+
+    assert_eq!(client.finish(), "20\n");
+    fs::remove_file(program).unwrap();
+}
+
+#[test]
+fn runtime_error_is_reported() {
+    let program = write_program("error", "print 1 / 0;\n");
+    let mut client = Client::start();
+    launch(&mut client, &program, false, &[]);
+
+    let exited = client.expect("event", "exited");
+    assert_ne!(exited["body"]["exitCode"], 0);
+    client.expect("event", "terminated");
+
+    assert!(client.finish().contains("Division by zero"));
+    fs::remove_file(program).unwrap();
+}
+
+#[test]
+fn breakpoint_in_one_line_loop() {
+    let program = write_program("loop", "var i = 0;\nwhile (i < 3) i = i + 1;\nprint i;\n");
+    let mut client = Client::start();
+    launch(&mut client, &program, false, &[2]);
+
+    // The first stop is the loop itself, then one per later iteration of its body
+    for expected in ["0", "1", "2"] {
+        let stopped = client.expect("event", "stopped");
+        assert_eq!(stopped["body"]["reason"], "breakpoint");
+        assert_eq!(current_line(&mut client), 2);
+        assert_eq!(scope_variables(&mut client, "Globals"), vec![("i".to_string(), expected.to_string())]);
+        client.request("continue", json!({ "threadId": 1 }));
+    }
+
+    let exited = client.expect("event", "exited");
+    assert_eq!(exited["body"]["exitCode"], 0);
+    client.expect("event", "terminated");
+
+    assert_eq!(client.finish(), "3\n");
+    fs::remove_file(program).unwrap();
+}
+
+// Name and line of each frame, the innermost first
+fn frames(client: &mut Client) -> Vec<(String, u64)> {
+    let body = client.request("stackTrace", json!({ "threadId": 1 }));
+    assert_eq!(body["totalFrames"].as_u64().unwrap() as usize, body["stackFrames"].as_array().unwrap().len());
+
+    body["stackFrames"].as_array().unwrap().iter()
+        .map(|f| (f["name"].as_str().unwrap().to_string(), f["line"].as_u64().unwrap()))
+        .collect()
+}
+
+fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+    expected.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+}
+
+const ADD: &str = "\
+var add = fun (a, b) {
+    var sum = a + b;
+    return sum;
+};
+var total = add(1, 2);
+print total;
+";
+
+#[test]
+fn breakpoint_inside_a_lambda() {
+    let program = write_program("lambda", ADD);
+    let mut client = Client::start();
+    launch(&mut client, &program, false, &[3]);
+
+    let stopped = client.expect("event", "stopped");
+    assert_eq!(stopped["body"]["reason"], "breakpoint");
+    assert_eq!(frames(&mut client), vec![("add".to_string(), 3), ("main".to_string(), 5)]);
+
+    // The parameters and locals of the call apart from the globals
+    assert_eq!(scope_variables(&mut client, "Locals"), pairs(&[("a", "1"), ("b", "2"), ("sum", "3")]));
+    assert_eq!(scope_variables(&mut client, "Globals"), pairs(&[("add", "<fn lambda>")]));
+
+    // The caller's frame only has the globals
+    let scopes = client.request("scopes", json!({ "frameId": 1 }));
+    assert_eq!(scopes["scopes"].as_array().unwrap().len(), 1);
+    assert_eq!(scopes["scopes"][0]["name"], "Globals");
+
+    // Stepping out of the body goes back to the caller
+    client.request("next", json!({ "threadId": 1 }));
+    client.expect("event", "stopped");
+    assert_eq!(frames(&mut client), vec![("main".to_string(), 6)]);
+    assert!(scope_variables(&mut client, "Locals").is_empty());
+
+    client.request("continue", json!({ "threadId": 1 }));
+    client.expect("event", "terminated");
+
+    assert_eq!(client.finish(), "3\n");
+    fs::remove_file(program).unwrap();
+}
+
+#[test]
+fn next_steps_over_calls_and_step_in_enters_them() {
+    let program = write_program("step-over", ADD);
+    let mut client = Client::start();
+    launch(&mut client, &program, false, &[5]);
+
+    client.expect("event", "stopped");
+    assert_eq!(frames(&mut client), vec![("main".to_string(), 5)]);
+
+    client.request("next", json!({ "threadId": 1 }));
+    client.expect("event", "stopped");
+    assert_eq!(frames(&mut client), vec![("main".to_string(), 6)]);
+    assert_eq!(scope_variables(&mut client, "Globals"), pairs(&[("add", "<fn lambda>"), ("total", "3")]));
+
+    client.request("continue", json!({ "threadId": 1 }));
+    client.expect("event", "terminated");
+    assert_eq!(client.finish(), "3\n");
+
+    let mut client = Client::start();
+    launch(&mut client, &program, false, &[5]);
+
+    client.expect("event", "stopped");
+    client.request("stepIn", json!({ "threadId": 1 }));
+    client.expect("event", "stopped");
+    assert_eq!(frames(&mut client), vec![("add".to_string(), 2), ("main".to_string(), 5)]);
+
+    client.request("stepOut", json!({ "threadId": 1 }));
+    client.expect("event", "stopped");
+    assert_eq!(frames(&mut client), vec![("main".to_string(), 6)]);
+
+    client.request("continue", json!({ "threadId": 1 }));
+    client.expect("event", "terminated");
+    assert_eq!(client.finish(), "3\n");
+    fs::remove_file(program).unwrap();
+}
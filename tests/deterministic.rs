@@ -0,0 +1,69 @@
+// End-to-end tests for `--deterministic`, running the same script several times.
+
+mod common;
+
+const SCRIPT: &str = r#"
+print [random(), random(), random()];
+var start = clock();
+for (var i = 0; i < 10; i = i + 1) {}
+print clock() - start;
+var m = {"z": 1, "a": 2, "m": 3, "b": 4};
+for (var k in m) print k;
+print keys(m);
+var x = 1; var y = 2; var z = 3; var w = 4;
+"#;
+
+// Runs a script with the arguments, returning everything written to stdout and stderr
+fn run(name: &str, source: &str, args: &[&str]) -> String {
+    let output = common::run(name, source, args);
+    assert!(output.success(), "{}", output.stderr);
+    format!("{}{}", output.stdout, output.stderr)
+}
+
+// Lines printed by the script, with `--quiet` the run only echoes these
+fn printed(output: &str) -> Vec<String> {
+    output.lines().map(|line| line.to_string()).collect()
+}
+
+#[test]
+fn same_seed_same_output() {
+    let args = ["--deterministic", "--seed", "7", "--prefer-const"];
+    let first = run("same-1", SCRIPT, &args);
+    for i in 0..3 {
+        assert_eq!(first, run(&format!("same-{}", i + 2), SCRIPT, &args));
+    }
+}
+
+#[test]
+fn different_seed_different_random() {
+    let first = printed(&run("seed-1", SCRIPT, &["--deterministic", "--seed", "1"]));
+    let second = printed(&run("seed-2", SCRIPT, &["--deterministic", "--seed", "2"]));
+    assert_ne!(first[0], second[0]);
+    assert_eq!(first[1..], second[1..]);
+}
+
+#[test]
+fn virtual_clock() {
+    let source = "print clock(); var start = clock(); print clock() - start > 0;";
+    let output = printed(&run("clock", source, &["--deterministic"]));
+    assert!(output[0].parse::<f64>().unwrap() < 1.0, "{:?}", output);
+    assert_eq!(output[1], "true");
+}
+
+#[test]
+fn map_insertion_order() {
+    let output = printed(&run("maps", SCRIPT, &["--deterministic"]));
+    assert_eq!(output[2..6], ["z", "a", "m", "b"]);
+    assert_eq!(output[6], r#"["z", "a", "m", "b"]"#);
+}
+
+#[test]
+fn warnings_in_source_order() {
+    let output = run("warnings", SCRIPT, &["--deterministic", "--prefer-const"]);
+    let warned: Vec<&str> = output
+        .lines()
+        .filter(|line| line.contains("Warning"))
+        .filter_map(|line| line.split('\'').nth(1))
+        .collect();
+    assert_eq!(warned, ["start", "m", "w", "x", "y", "z"]);
+}
@@ -0,0 +1,117 @@
+// End-to-end tests for `==`, `!=` and the comparisons, running scripts through `rslox -i`.
+
+mod common;
+
+// One value of each type, declared in the prelude when they are not literals
+const PRELUDE: &str = "var l = [1]; var m = {\"a\": 1}; var f = fun() {};";
+const VALUES: [&str; 9] = ["1", "1.5", "\"a\"", "true", "nil", "l", "m", "f", "len"];
+
+// Prints every expression in a single script and compares with the expected values
+fn check(name: &str, cases: &[(String, &str)]) {
+    let mut source = format!("{}\n", PRELUDE);
+    for (expr, _) in cases {
+        source.push_str(&format!("print {};\n", expr));
+    }
+
+    let output = common::run(name, &source, &[]);
+    let printed = output.lines();
+    assert!(output.success(), "script failed: {:?}", printed);
+    assert_eq!(printed.len(), cases.len(), "{:?}", printed);
+
+    for ((expr, expected), got) in cases.iter().zip(printed.iter()) {
+        assert_eq!(got, expected, "{}", expr);
+    }
+}
+
+#[test]
+fn equality_matrix_of_all_types() {
+    let mut cases = Vec::new();
+
+    for (i, lhs) in VALUES.iter().enumerate() {
+        for (j, rhs) in VALUES.iter().enumerate() {
+            let same = if i == j { "true" } else { "false" };
+            let different = if i == j { "false" } else { "true" };
+            cases.push((format!("{} == {}", lhs, rhs), same));
+            cases.push((format!("{} != {}", lhs, rhs), different));
+        }
+    }
+
+    check("matrix", &cases);
+}
+
+#[test]
+fn equality_by_value() {
+    let cases = [
+        ("1 == 1.0".to_string(), "true"),
+        ("1.0 == 1".to_string(), "true"),
+        ("2 == 2".to_string(), "true"),
+        ("2 == 3".to_string(), "false"),
+        ("0.1 + 0.2 == 0.3".to_string(), "false"),
+        ("\"a\" == \"a\"".to_string(), "true"),
+        ("\"a\" == \"b\"".to_string(), "false"),
+        ("\"\" == \"\"".to_string(), "true"),
+        ("\"ab\" == \"a\" + \"b\"".to_string(), "true"),
+        ("true == true".to_string(), "true"),
+        ("true == false".to_string(), "false"),
+        ("false != true".to_string(), "true"),
+        ("nil == nil".to_string(), "true"),
+        ("nil != nil".to_string(), "false"),
+        ("nil == false".to_string(), "false"),
+        ("0 == false".to_string(), "false"),
+        ("\"1\" == 1".to_string(), "false"),
+        ("\"\" == nil".to_string(), "false"),
+    ];
+
+    check("value", &cases);
+}
+
+#[test]
+fn equality_by_identity() {
+    let cases = [
+        ("[1] == [1]".to_string(), "false"),
+        ("l == l".to_string(), "true"),
+        ("l == l[0:1]".to_string(), "false"),
+        ("{\"a\": 1} == m".to_string(), "false"),
+        ("m == m".to_string(), "true"),
+        ("f == fun() {}".to_string(), "false"),
+        ("f == f".to_string(), "true"),
+        ("len == len".to_string(), "true"),
+        ("len == push".to_string(), "false"),
+    ];
+
+    check("identity", &cases);
+}
+
+#[test]
+fn string_ordering() {
+    let cases = [
+        ("\"apple\" < \"banana\"".to_string(), "true"),
+        ("\"banana\" < \"apple\"".to_string(), "false"),
+        ("\"a\" <= \"a\"".to_string(), "true"),
+        ("\"a\" >= \"a\"".to_string(), "true"),
+        ("\"b\" > \"a\"".to_string(), "true"),
+        ("\"ab\" > \"a\"".to_string(), "true"),
+        ("\"\" < \"a\"".to_string(), "true"),
+        ("\"Z\" < \"a\"".to_string(), "true"),
+        ("\"é\" > \"z\"".to_string(), "true"),
+    ];
+
+    check("ordering", &cases);
+}
+
+#[test]
+fn ordering_mixed_types_is_an_error() {
+    // Through variables, so the type checker does not catch them before running
+    for (lhs, rhs) in [("\"a\"", "1"), ("1", "\"a\""), ("nil", "1"), ("true", "false"), ("l", "l")] {
+        let source = format!("{}\nvar x = {}; var y = {};\nprint x < y;\n", PRELUDE, lhs, rhs);
+        let output = common::run("mixed", &source, &[]);
+        let printed = output.lines();
+
+        assert!(!output.success(), "{} < {} should fail", lhs, rhs);
+        assert!(
+            printed.iter().any(|line| line.contains("Invalid operation on binary operand")),
+            "{:?}",
+            printed
+        );
+    }
+}
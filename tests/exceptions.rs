@@ -0,0 +1,118 @@
+// End-to-end tests for `throw` and `try`, running scripts through `rslox -i`.
+
+mod common;
+
+#[test]
+fn catch_receives_the_thrown_value() {
+    let source = "\
+try {
+    throw {\"code\": 7};
+} catch (e) {
+    print e[\"code\"];
+}
+try {
+    print 1 // 0;
+} catch (e) {
+    print e[\"message\"];
+    print e[\"line\"];
+}
+";
+    let output = common::run("catch", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["7", "Division by zero", "7"]);
+}
+
+#[test]
+fn finally_runs_on_return() {
+    let source = "\
+var log = \"\";
+var f = fun () {
+    try {
+        return \"returned\";
+    } finally {
+        log = \"finally\";
+    }
+};
+print f();
+print log;
+var g = fun () {
+    try {
+        return 1;
+    } finally {
+        return 2;
+    }
+};
+print g();
+";
+    let output = common::run("finally-return", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["returned", "finally", "2"]);
+}
+
+#[test]
+fn finally_runs_on_break_and_continue() {
+    let source = "\
+for (var i = 0; i < 3; i = i + 1) {
+    try {
+        if (i == 1) continue;
+        if (i == 2) break;
+        print i;
+    } catch (e) {
+        print \"never caught\";
+    } finally {
+        print \"finally \" + i;
+    }
+}
+";
+    let output = common::run("finally-loop", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["0", "finally 0", "finally 1", "finally 2"]);
+}
+
+#[test]
+fn finally_runs_on_rethrow() {
+    let source = "\
+try {
+    try {
+        throw \"inner\";
+    } catch (e) {
+        print \"caught \" + e;
+        throw e + \" again\";
+    } finally {
+        print \"finally\";
+    }
+} catch (e) {
+    print \"outer caught \" + e;
+}
+";
+    let output = common::run("finally-rethrow", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["caught inner", "finally", "outer caught inner again"]);
+}
+
+#[test]
+fn exception_in_finally_replaces_the_one_unwound() {
+    let source = "\
+try {
+    try {
+        throw \"first\";
+    } finally {
+        throw \"second\";
+    }
+} catch (e) {
+    print e;
+}
+";
+    let output = common::run("finally-throw", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["second"]);
+}
+
+#[test]
+fn uncaught_exception_stops_after_finally() {
+    let source = "try {\n    throw \"oops\";\n} finally {\n    print \"finally\";\n}\nprint \"after\";\n";
+    let output = common::run("uncaught", source, &[]);
+    assert_eq!(output.code, Some(65));
+    assert_eq!(output.lines()[0], "finally");
+    assert!(!output.lines().contains(&"after".to_string()), "{:?}", output.lines());
+}
@@ -0,0 +1,164 @@
+// End-to-end tests for `rslox lint`, checking each lint, the severities and the exit codes.
+
+mod common;
+
+use std::fs;
+
+const SOURCE: &str = "\
+var f = fun (a, b) {
+    var unused = 1;
+    var x = a;
+    {
+        var x = 2;
+        print x;
+    }
+    return x;
+    print \"never\";
+};
+print true ? 1 : 2;
+print 1 == \"1\";
+print f(1, 2);
+";
+
+// Lints the source, returning the output with the path of the script taken out
+fn lint(name: &str, source: &str, args: &[&str]) -> common::Output {
+    let path = common::temp_path(&format!("{}.lox", name));
+    fs::write(&path, source).unwrap();
+
+    let path = path.to_str().unwrap().to_string();
+    let mut output = common::rslox(&[&["lint", path.as_str()], args].concat());
+    output.stdout = output.stdout.replace(&format!("{}:", path), "");
+    output
+}
+
+#[test]
+fn default_lints() {
+    let output = lint("default", SOURCE, &[]);
+    assert_eq!(output.code, Some(0), "{}", output.stderr);
+
+    // Shadowing is allowed by default
+    assert_eq!(
+        output.lines(),
+        vec![
+            "1: warning: Parameter 'b' is never used [unused-parameter]",
+            "2: warning: Variable 'unused' is never used [unused-variable]",
+            "9: warning: Unreachable code after 'return' [unreachable-code]",
+            "11: warning: Condition of the ternary is always true [constant-condition]",
+            "12: warning: Comparing number with string [mixed-comparison]",
+        ]
+    );
+}
+
+#[test]
+fn every_jump_ends_the_reachable_code() {
+    let source = "\
+var f = fun (n) {
+    while (n > 0) {
+        n = n - 1;
+        if (n == 1) {
+            continue;
+            print \"continue\";
+        }
+        break;
+        print \"break\";
+    }
+    throw \"done\";
+    print \"throw\";
+};
+print nil ? 1 : 2;
+print nil != false;
+f(2);
+";
+    let output = lint("jumps", source, &[]);
+    assert_eq!(output.code, Some(0), "{}", output.stderr);
+    assert_eq!(
+        output.lines(),
+        vec![
+            "6: warning: Unreachable code after 'continue' [unreachable-code]",
+            "9: warning: Unreachable code after 'break' [unreachable-code]",
+            "12: warning: Unreachable code after 'throw' [unreachable-code]",
+            "14: warning: Condition of the ternary is always false [constant-condition]",
+            "15: warning: Comparing nil with bool [mixed-comparison]",
+        ]
+    );
+}
+
+#[test]
+fn warn_reports_an_allowed_lint() {
+    let output = lint("warn", SOURCE, &["--warn", "shadowing"]);
+    assert_eq!(output.code, Some(0), "{}", output.stderr);
+    assert!(
+        output.lines().contains(&"5: warning: 'x' shadows a variable of an outer scope [shadowing]".to_string()),
+        "{:?}",
+        output.lines()
+    );
+}
+
+#[test]
+fn allow_hides_a_lint() {
+    let output = lint("allow", SOURCE, &["--allow", "unused-parameter", "--allow", "unreachable-code"]);
+    assert_eq!(output.code, Some(0), "{}", output.stderr);
+    assert_eq!(
+        output.lines(),
+        vec![
+            "2: warning: Variable 'unused' is never used [unused-variable]",
+            "11: warning: Condition of the ternary is always true [constant-condition]",
+            "12: warning: Comparing number with string [mixed-comparison]",
+        ]
+    );
+}
+
+#[test]
+fn deny_fails_the_run() {
+    let output = lint("deny", SOURCE, &["--deny", "unused-variable"]);
+    assert_eq!(output.code, Some(65));
+    assert!(
+        output.lines().contains(&"2: error: Variable 'unused' is never used [unused-variable]".to_string()),
+        "{:?}",
+        output.lines()
+    );
+
+    // Denying a lint that is not found is fine
+    let output = lint("deny-clean", "print 1;\n", &["--deny", "unused-variable"]);
+    assert_eq!(output.code, Some(0));
+    assert!(output.stdout.is_empty(), "{}", output.stdout);
+}
+
+#[test]
+fn globals_are_not_unused() {
+    let output = lint("globals", "var x = 1;\nconst y = 2;\n", &[]);
+    assert_eq!(output.code, Some(0));
+    assert!(output.stdout.is_empty(), "{}", output.stdout);
+}
+
+#[test]
+fn json_report() {
+    let output = lint("json", SOURCE, &["--format", "json", "--deny", "mixed-comparison"]);
+    assert_eq!(output.code, Some(65), "{}", output.stderr);
+
+    let report: serde_json::Value = serde_json::from_str(&output.stdout).unwrap();
+    assert!(report["file"].as_str().unwrap().ends_with("json.lox"));
+
+    let diagnostics = report["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 5);
+    assert_eq!(
+        diagnostics[4],
+        serde_json::json!({
+            "line": 12,
+            "lint": "mixed-comparison",
+            "message": "Comparing number with string",
+            "severity": "error",
+        })
+    );
+}
+
+#[test]
+fn bad_arguments_are_usage_errors() {
+    let output = lint("unknown-lint", SOURCE, &["--deny", "nope"]);
+    assert_eq!(output.code, Some(64));
+    assert_eq!(output.stderr.trim_end(), "[LINT] Unknown lint 'nope'");
+
+    let output = lint("unknown-format", SOURCE, &["--format", "xml"]);
+    assert_eq!(output.code, Some(64));
+    assert_eq!(output.stderr.trim_end(), "[LINT] Unknown format 'xml', expected `text` or `json`");
+}
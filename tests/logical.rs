@@ -0,0 +1,54 @@
+// End-to-end tests for `and` and `or`, running scripts through `rslox -i`.
+
+mod common;
+
+const SOURCE: &str = "\
+print nil or \"default\";
+print 0 or 1;
+print false and 1;
+print 1 and 2;
+print true or false and false;
+print (true or false) and false;
+print 1 == 1 and 2 == 2;
+var calls = 0;
+var touch = fun (v) { calls = calls + 1; return v; };
+print touch(true) or touch(false);
+print touch(false) and touch(true);
+print calls;
+print false and 1 / 0;
+";
+
+// The right operand of the last one, a division by zero, never runs
+const PRINTED: [&str; 11] = ["default", "0", "false", "2", "true", "false", "true", "true", "false", "2", "false"];
+
+#[test]
+fn operands_decide_and_short_circuit() {
+    let output = common::run("logical", SOURCE, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+
+    assert_eq!(output.lines(), PRINTED);
+}
+
+#[test]
+fn optimizer_keeps_the_operand_that_decides() {
+    let output = common::run("logical-optimized", SOURCE, &["-O"]);
+    assert!(output.success(), "{:?}", output.lines());
+
+    assert_eq!(output.lines(), PRINTED);
+}
+
+#[test]
+fn strict_mode_needs_a_boolean_left_operand() {
+    // Reported before running when the type is known, at runtime otherwise
+    let output = common::run("logical-strict", "print nil or 1;\n", &["--strict"]);
+    assert_eq!(output.code, Some(65));
+    assert_eq!(output.lines(), vec!["1 at 'OR' Type error: Left operand of 'or' must be a boolean, found nil"]);
+
+    let output = common::run("logical-strict-runtime", "print [0][0] and 1;\n", &["--strict"]);
+    assert_eq!(output.code, Some(65));
+    assert_eq!(output.lines(), vec!["0 literal - Condition must be a boolean"]);
+
+    let output = common::run("logical-strict-ok", "print 1 > 0 and 2;\n", &["--strict"]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["2"]);
+}
@@ -0,0 +1,61 @@
+// End-to-end tests for loops, `break` and `continue`, running scripts through `rslox -i`.
+
+mod common;
+
+const SOURCE: &str = "\
+for (var i = 0; i < 5; i = i + 1) {
+    if (i % 2 == 0) continue;
+    print i;
+}
+var n = 0;
+while (n < 4) {
+    n = n + 1;
+    if (n == 2) continue;
+    if (n == 4) break;
+    print n;
+}
+for (var i = 0; i < 2; i = i + 1) {
+    for (var j = 0; j < 3; j = j + 1) {
+        if (j == 1) continue;
+        if (j == 2) break;
+        print i * 10 + j;
+    }
+}
+for (var x in [1, 2, 3, 4]) {
+    if (x == 2) continue;
+    if (x == 4) break;
+    print x;
+}
+";
+
+// The first loop would never end if `continue` skipped the increment
+const PRINTED: [&str; 8] = ["1", "3", "1", "3", "0", "10", "1", "3"];
+
+#[test]
+fn continue_in_for_runs_the_increment() {
+    let output = common::run("loops", SOURCE, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), PRINTED);
+}
+
+#[test]
+fn optimized_loops_keep_the_increment() {
+    let output = common::run("loops-optimized", SOURCE, &["-O"]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), PRINTED);
+}
+
+#[test]
+fn jumps_outside_of_a_loop_are_parse_errors() {
+    let cases = [
+        ("break;", "1 at 'BREAK' Can't be used outside of a loop."),
+        ("continue;", "1 at 'CONTINUE' Can't be used outside of a loop."),
+        ("while (true) { var f = fun () { break; }; }", "1 at 'BREAK' Can't be used outside of a loop."),
+    ];
+
+    for (source, expected) in cases {
+        let output = common::run("outside", source, &[]);
+        assert_eq!(output.code, Some(65), "{}", source);
+        assert_eq!(output.lines(), vec![expected], "{}", source);
+    }
+}
@@ -0,0 +1,35 @@
+// End-to-end tests for map keys, running scripts through `rslox -i`.
+
+mod common;
+
+#[test]
+fn numbers_and_strings_are_keys() {
+    let source = "var m = {\"a\": 1, 2: \"b\"};\nm[2.0] = \"c\";\nm[0.5] = true;\n\
+        print m[\"a\"];\nprint m[2];\nprint m[0.5];\nprint len(m);\n";
+    let output = common::run("keys", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["1", "c", "true", "3"]);
+}
+
+#[test]
+fn invalid_keys_are_errors() {
+    // The key comes from a list, so only the interpreter sees it is nil
+    let source = "var k = [nil][0];\nvar m = {\"a\": 1};\n\
+        try { print m[k]; } catch (e) { print e[\"message\"]; }\n\
+        try { m[k] = 1; } catch (e) { print e[\"message\"]; }\n\
+        try { var n = {k: 1}; } catch (e) { print e[\"message\"]; }\n\
+        try { print m[\"b\"]; } catch (e) { print e[\"message\"]; }\n\
+        print len(m);\n";
+    let output = common::run("invalid", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(
+        output.lines(),
+        vec![
+            "Map keys must be strings or numbers",
+            "Map keys must be strings or numbers",
+            "Map keys must be strings or numbers",
+            "Key not found in map",
+            "1",
+        ]
+    );
+}
@@ -0,0 +1,110 @@
+// End-to-end tests for `match` expressions, running scripts through `rslox -i`.
+
+mod common;
+
+#[test]
+fn malformed_match_is_a_parse_error() {
+    let cases = [
+        ("var x = match (1) { 1 -> 2 };", "1 at 'MINUS' Expect '=>' after match pattern"),
+        ("var x = match 1 { 1 => 2 };", "1 at 'INTEGER' Expect '(' after 'match'"),
+        ("var x = match (1) 1 => 2;", "1 at 'INTEGER' Expect '{' before match arms"),
+        ("var x = match (1) { + => 2 };", "1 at 'PLUS' Expect a literal, type name, '_' or name as pattern"),
+        ("var x = match (1) { 1 => 2 3 => 4 };", "1 at 'INTEGER' Expect '}' after match arms"),
+        ("var x = match (1) { 1 | y => 2 };", "1 at 'INTEGER' A binding can't be one of several alternatives."),
+    ];
+
+    for (source, expected) in cases {
+        let output = common::run("malformed", source, &[]);
+        assert_eq!(output.code, Some(65), "{}: {:?}", source, output.lines());
+        assert_eq!(output.lines(), vec![expected], "{}", source);
+    }
+}
+
+#[test]
+fn parsing_goes_on_after_a_malformed_match() {
+    // Each statement with an error is reported, the ones around it still parse
+    let source = "var x = match (1) { 1 -> 2 };\nvar y = 1;\nvar z = match (y) { _ 3 };\nprint y;\n";
+    let output = common::run("recover", source, &[]);
+    assert_eq!(output.code, Some(65));
+    assert_eq!(
+        output.lines(),
+        vec![
+            "1 at 'MINUS' Expect '=>' after match pattern",
+            "3 at 'INTEGER' Expect '=>' after match pattern",
+        ]
+    );
+}
+
+#[test]
+fn first_matching_arm_is_the_result() {
+    let source = "\
+var describe = fun (v) {
+    return match (v) {
+        0 => \"zero\",
+        1 | 2 => \"small\",
+        -1 => \"minus one\",
+        \"hi\" => \"greeting\",
+        true | false => \"bool\",
+        nil => \"nothing\",
+        n if n == 500 => \"five hundred\",
+        number => \"number\",
+        list => \"list\",
+        map => \"map\",
+        function => \"function\",
+        _ => \"other\",
+    };
+};
+print describe(0);
+print describe(2.0);
+print describe(-1);
+print describe(\"hi\");
+print describe(false);
+print describe(nil);
+print describe(500);
+print describe(3.5);
+print describe([1]);
+print describe({});
+print describe(describe);
+print describe(\"bye\");
+";
+    let output = common::run("arms", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(
+        output.lines(),
+        vec![
+            "zero", "small", "minus one", "greeting", "bool", "nothing", "five hundred", "number", "list", "map",
+            "function", "other",
+        ]
+    );
+}
+
+#[test]
+fn bindings_are_local_to_their_arm() {
+    let source = "var y = 5;\nprint match (y + 1) { y if y > 10 => 0, y => y * 2 };\nprint y;\n";
+    let output = common::run("binding", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["12", "5"]);
+}
+
+#[test]
+fn no_matching_arm_is_a_runtime_error() {
+    let source = "try {\n    print match (3) { 1 => \"one\" };\n} catch (e) {\n    print e[\"message\"];\n}\n";
+    let output = common::run("no-match-caught", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["No match arm for the value (line 2)"]);
+
+    let output = common::run("no-match", "print match (3) { 1 => \"one\" };\nprint \"after\";\n", &[]);
+    assert_eq!(output.code, Some(65));
+    assert!(!output.lines().contains(&"after".to_string()), "{:?}", output.lines());
+}
+
+#[test]
+fn unreachable_arms_are_warnings() {
+    let source = "var f = fun (v) { return match (v) { _ => 1, 2 => 2, number => 3 }; };\nprint f(2);\n";
+    let output = common::run("unreachable", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(
+        output.lines(),
+        vec!["1 at '2' Warning: Unreachable match arm", "1 at 'number' Warning: Unreachable match arm", "1"]
+    );
+}
@@ -0,0 +1,114 @@
+// End-to-end tests for number literals and arithmetic, running scripts through `rslox -i`.
+
+mod common;
+
+#[test]
+fn literals() {
+    let source = "print 0xFF;\nprint 0b1010;\nprint 1_000_000;\nprint 1e-9;\nprint .5;\nprint 9223372036854775807;\n";
+    let output = common::run("literals", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["255", "10", "1000000", "1e-09", "0.5", "9223372036854775807"]);
+}
+
+#[test]
+fn out_of_range_decimal_is_float() {
+    let output = common::run("float", "print 9223372036854775808;\nprint 9223372036854775808 / 2;\n", &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["9.223372036854776e+18", "4.611686018427388e+18"]);
+}
+
+#[test]
+fn malformed_literals_are_scan_errors() {
+    let source = "print 0x;\nprint 1e;  print 1_;\n  print 1__0;\nprint 12abc + 0x1_FFFFFFFFFFFFFFFF;\n";
+    let output = common::run("malformed", source, &[]);
+    assert_eq!(output.code, Some(65));
+
+    // Scanning goes on after each error, so all of them are reported with line and column
+    assert_eq!(
+        output.lines(),
+        vec![
+            "1:7 Scan error: Malformed number '0x': expect digits after '0x'",
+            "2:7 Scan error: Malformed number '1e': expect digits in exponent",
+            "2:18 Scan error: Malformed number '1_': '_' must be between digits",
+            "3:9 Scan error: Malformed number '1__0': '_' must be between digits",
+            "4:7 Scan error: Malformed number '12abc': unexpected 'a'",
+            "4:15 Scan error: Malformed number '0x1_FFFFFFFFFFFFFFFF': integer out of range",
+        ]
+    );
+}
+
+#[test]
+fn malformed_literal_stops_the_run() {
+    let output = common::run("stops", "print \"before\";\nprint 1__0;\n", &[]);
+    assert!(!output.success());
+    assert!(!output.lines().contains(&"before".to_string()), "{:?}", output.lines());
+}
+
+#[test]
+fn integer_division_and_modulo() {
+    let source = "print 7 // 2;\nprint 7 // -2;\nprint -7 // 2;\nprint 7.5 // 2;\nprint 7 / 2;\nprint -7 % 3;\nprint 7 % -3;\nprint 7.5 % 2;\n";
+    let output = common::run("division", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+
+    // `//` floors, `/` always gives a float, and `%` takes the sign of the divisor
+    assert_eq!(output.lines(), vec!["3", "-4", "-4", "3", "3.5", "2", "-2", "1.5"]);
+}
+
+#[test]
+fn bitwise_operators_and_shifts() {
+    let source = "print 6 & 3;\nprint 6 | 3;\nprint 6 ^ 3;\nprint 1 << 62;\nprint 256 >> 4;\nprint -16 >> 2;\nprint 1 | 2 == 3;\n";
+    let output = common::run("bitwise", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["2", "7", "5", "4611686018427387904", "16", "-4", "true"]);
+}
+
+#[test]
+fn integer_errors_stop_the_run() {
+    let cases = [
+        ("print 9223372036854775807 + 1;", "Integer overflow"),
+        ("print -9223372036854775807 - 2;", "Integer overflow"),
+        ("print 9223372036854775807 * 2;", "Integer overflow"),
+        ("print (-9223372036854775807 - 1) // -1;", "Integer overflow"),
+        ("print 1 << 63;", "Integer overflow"),
+        ("print 1 << 64;", "Shift amount must be between 0 and 63"),
+        ("print 1 >> -1;", "Shift amount must be between 0 and 63"),
+        ("print 7 // 0;", "Division by zero"),
+        ("print 7 % 0;", "Division by zero"),
+        ("print 7.0 // 0;", "Division by zero"),
+    ];
+
+    for (source, expected) in cases {
+        let output = common::run("integer-error", source, &[]);
+        assert_eq!(output.code, Some(65), "{}", source);
+        assert!(output.stdout.trim_end().ends_with(expected), "{}: {:?}", source, output.lines());
+    }
+}
+
+#[test]
+fn double_slash_after_a_value_is_division() {
+    let source = "var a = 9;\nprint (a + 1) // 2;\nprint [a][0] // 2;\nprint a // 2 == 4 and true;\nvar b = a // 2\n  + 1;\nprint b;\nprint 7 // 2; // two words\n";
+    let output = common::run("floor-division", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["5", "4", "true", "5", "3"]);
+}
+
+#[test]
+fn double_slash_comments() {
+    let source = "\
+// at the start of a line
+var double = fun (x) // doubles it
+{
+    return x * 2;
+};
+if (true) // the branch
+    print double(4);
+var x = 9;
+var y = x // the x
+  + 1;
+print y;
+print x; // a comment after a value
+";
+    let output = common::run("comments", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["8", "10", "9"]);
+}
@@ -0,0 +1,41 @@
+// End-to-end tests for `-O`, checking that the optimized scripts behave like the others.
+
+mod common;
+
+#[test]
+fn literals_are_folded() {
+    let source = "print 2 * 3 + 1;\nprint \"a\" + \"b\";\nprint -(4 // 3);\nprint true ? \"then\" : \"else\";\nprint \"${1 + 1} items\";\n";
+    let output = common::run("folded", source, &["-O"]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["7", "ab", "-1", "then", "2 items"]);
+}
+
+#[test]
+fn division_by_zero_is_not_folded() {
+    // The division fails when it runs, not while optimizing the lambda
+    let source = "var f = fun () { return 1 // 0; };\nprint \"before\";\nprint f();\n";
+    let plain = common::run("division", source, &[]);
+    let optimized = common::run("division-optimized", source, &["-O"]);
+
+    assert_eq!(optimized.code, Some(65));
+    assert_eq!(optimized.lines(), vec!["before", "1 0 literal - Division by zero"]);
+    assert_eq!(optimized.lines(), plain.lines());
+
+    let output = common::run("modulo-optimized", "print (true ? 1 : \"b\") % 0;\n", &["-O"]);
+    assert_eq!(output.code, Some(65));
+    assert_eq!(output.lines(), vec!["1 0 literal - Division by zero"]);
+}
+
+#[test]
+fn strict_plus_is_not_folded() {
+    // The ternary hides the types from the type checker, then folds into a number
+    let source = "print \"a\" + (true ? 1 : \"b\");\n";
+
+    let output = common::run("plus-optimized", source, &["-O"]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["a1"]);
+
+    let output = common::run("plus-strict-optimized", source, &["-O", "--strict"]);
+    assert_eq!(output.code, Some(65));
+    assert_eq!(output.lines(), vec!["a 1 literal - Operands of '+' must be two numbers or two strings"]);
+}
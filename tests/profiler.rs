@@ -0,0 +1,58 @@
+// End-to-end tests for `--profile`, checking the reports and the folded stacks.
+
+mod common;
+
+use std::fs;
+
+// Profiles a script, returning the report written to stderr and the folded stacks
+fn profile(name: &str, source: &str) -> (String, String) {
+    let path = common::temp_path(&format!("{}.lox", name));
+    let folded = path.with_extension("folded");
+    fs::write(&path, source).unwrap();
+
+    let output = common::run_file(&path, &["--profile", "--profile-output", &folded.to_string_lossy()]);
+    assert!(output.success());
+
+    let stacks = fs::read_to_string(&folded).unwrap_or_default();
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_file(&folded);
+
+    (output.stderr, stacks)
+}
+
+// Count of the row of the report with this line and label, in the table of that kind
+fn count(report: &str, kind: &str, line: usize, label: &str) -> Option<u64> {
+    let table = report.split("\n\n").find(|table| table.trim_start().starts_with("line") && table.contains(kind))?;
+    table.lines().find_map(|row| {
+        let columns: Vec<&str> = row.split_whitespace().collect();
+        match columns.as_slice() {
+            [l, name, count, ..] if *l == line.to_string() && *name == label => count.parse().ok(),
+            _ => None,
+        }
+    })
+}
+
+#[test]
+fn call_sites() {
+    let source = "\
+        var fib = fun (n) { return n < 2 ? n : fib(n - 1) + fib(n - 2); };\n\
+        print fib(5);\n\
+        var l = map([1, 2, 3], (x) => x * 2);\n";
+    let (report, stacks) = profile("calls", source);
+
+    assert_eq!(count(&report, "call", 2, "fib"), Some(1), "{}", report);
+    assert_eq!(count(&report, "call", 1, "fib"), Some(14), "{}", report);
+    assert_eq!(count(&report, "call", 3, "map"), Some(1), "{}", report);
+    assert_eq!(count(&report, "call", 3, "lambda"), Some(3), "{}", report);
+    assert_eq!(count(&report, "statement", 2, "print"), Some(1), "{}", report);
+
+    assert!(stacks.contains("main;line 2: print;line 2: call fib;line 1: return;line 1: call fib;"), "{}", stacks);
+    assert!(stacks.contains("main;line 3: var l;line 3: call map;line 3: call lambda;line 3: return "), "{}", stacks);
+}
+
+#[test]
+fn calls_of_natives() {
+    let (report, _) = profile("natives", "var l = [];\nfor (var i = 0; i < 4; i = i + 1) push(l, i);\nprint len(l);\n");
+    assert_eq!(count(&report, "call", 2, "push"), Some(4), "{}", report);
+    assert_eq!(count(&report, "call", 3, "len"), Some(1), "{}", report);
+}
@@ -0,0 +1,101 @@
+// End-to-end tests for the sandbox limits, running scripts through `rslox -i`.
+
+mod common;
+
+#[test]
+fn finally_cannot_escape_a_limit() {
+    let source = "\
+        var f = fun (n) { return f(n + 1); };\n\
+        var g = fun () { try { return f(0); } finally { return \"escaped\"; } };\n\
+        print g();\n";
+    let output = common::run("finally", source, &["--max-depth", "50"]);
+    assert!(!output.success());
+    assert!(output.stdout.contains("LIMIT - Stack overflow"), "{}", output.stdout);
+    assert!(!output.lines().iter().any(|line| line == "escaped"), "{}", output.stdout);
+
+    let source = "\
+        var i = 0;\n\
+        while (true) { try { while (true) { i = i + 1; } } finally { break; } }\n\
+        print \"escaped\";\n";
+    let output = common::run("finally-steps", source, &["--max-steps", "1000"]);
+    assert!(!output.success());
+    assert!(output.stdout.contains("LIMIT - Step limit of 1000 exceeded"), "{}", output.stdout);
+}
+
+#[test]
+fn max_steps() {
+    let source = "var i = 0;\nwhile (true) { i = i + 1; }\n";
+    let output = common::run("steps", source, &["--max-steps", "500"]);
+    assert!(!output.success());
+    assert!(output.stdout.contains("LIMIT - Step limit of 500 exceeded"), "{}", output.stdout);
+
+    let output = common::run("steps-ok", "var i = 0;\nwhile (i < 10) { i = i + 1; }\n", &["--max-steps", "500"]);
+    assert!(output.success(), "{}", output.stdout);
+}
+
+#[test]
+fn max_depth() {
+    let source = "var f = fun (n) { return n == 0 ? 0 : f(n - 1); };\nprint f(100);\n";
+    let output = common::run("depth", source, &["--max-depth", "50"]);
+    assert!(!output.success());
+    assert!(output.stdout.contains("LIMIT - Stack overflow"), "{}", output.stdout);
+
+    let output = common::run("depth-ok", source, &["--max-depth", "200"]);
+    assert!(output.success(), "{}", output.stdout);
+
+    // Always on, even without the flag
+    let source = "var f = fun (n) { return f(n + 1); };\nf(0);\n";
+    let output = common::run("depth-default", source, &[]);
+    assert!(!output.success());
+    assert!(output.stdout.contains("LIMIT - Stack overflow"), "{}", output.stdout);
+
+    // A depth larger than the stack can hold is bounded by the stack instead of aborting
+    let output = common::run("depth-huge", source, &["--max-depth", "1000000"]);
+    assert_eq!(output.code, Some(65), "{}", output.stderr);
+    assert!(output.stdout.contains("LIMIT - Stack overflow"), "{}", output.stdout);
+}
+
+#[test]
+fn max_values() {
+    let cases = [
+        ("literals", "var l = nil;\nwhile (true) { l = [1]; }\n"),
+        ("push", "var l = [];\nwhile (true) { push(l, 1); }\n"),
+        ("keys", "var m = {};\nvar i = 0;\nwhile (true) { m[i] = i; i = i + 1; }\n"),
+        ("map", "var l = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];\nmap(map(l, (x) => x), (x) => x);\n"),
+        ("filter", "var l = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];\nfilter(l, (x) => true);\nfilter(l, (x) => true);\n"),
+    ];
+
+    for (name, source) in cases {
+        let output = common::run(name, source, &["--max-values", "15"]);
+        assert!(!output.success(), "{}", name);
+        assert!(output.stdout.contains("LIMIT - Value limit of 15 exceeded"), "{}: {}", name, output.stdout);
+    }
+
+    let output = common::run("values-ok", "var l = [];\npush(l, 1);\nmap(l, (x) => x);\n", &["--max-values", "15"]);
+    assert!(output.success(), "{}", output.stdout);
+}
+
+#[test]
+fn max_string_bytes() {
+    let source = "var s = \"ab\";\nwhile (true) { s = s + s; }\n";
+    let output = common::run("strings", source, &["--max-string-bytes", "1000"]);
+    assert!(!output.success());
+    assert!(output.stdout.contains("LIMIT - String limit of 1000 bytes exceeded"), "{}", output.stdout);
+}
+
+#[test]
+fn limits_are_not_caught() {
+    let source = "try { while (true) {} } catch (e) { print \"caught\"; }\n";
+    let output = common::run("catch", source, &["--max-steps", "100"]);
+    assert!(!output.success());
+    assert!(!output.lines().iter().any(|line| line == "caught"), "{}", output.stdout);
+}
+
+#[test]
+fn timeout() {
+    let start = std::time::Instant::now();
+    let output = common::run("timeout", "while (true) {}\n", &["--timeout", "200"]);
+    assert!(!output.success());
+    assert!(output.stdout.contains("LIMIT - Execution cancelled"), "{}", output.stdout);
+    assert!(start.elapsed() < std::time::Duration::from_secs(30));
+}
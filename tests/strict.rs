@@ -0,0 +1,59 @@
+// End-to-end tests for `--strict` and `--prefer-const`, running scripts through `rslox -i`.
+
+mod common;
+
+#[test]
+fn strict_conditions_must_be_booleans() {
+    let output = common::run("strict-condition", "var c = [0][0];\nif (c) print 1;\n", &["--strict"]);
+    assert_eq!(output.code, Some(65));
+    assert_eq!(output.lines(), vec!["0 literal - Condition must be a boolean"]);
+
+    let output = common::run("strict-bang", "print !nil;\n", &["--strict"]);
+    assert_eq!(output.code, Some(65));
+    assert_eq!(output.lines(), vec!["1 at 'BANG' Type error: Operand of '!' must be a boolean, found nil"]);
+
+    let output = common::run("strict-ok", "if (1 > 0) print !false;\n", &["--strict"]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["true"]);
+}
+
+#[test]
+fn strict_plus_does_not_convert_numbers() {
+    // Reported before running when the types are known, at runtime otherwise
+    let output = common::run("strict-plus", "print \"a\" + 1;\n", &["--strict"]);
+    assert_eq!(output.code, Some(65));
+    assert_eq!(output.lines(), vec!["1 at 'PLUS' Type error: Invalid operands for '+': string and number"]);
+
+    let output = common::run("strict-plus-runtime", "var n = [1][0];\nprint \"a\" + n;\n", &["--strict"]);
+    assert_eq!(output.code, Some(65));
+    assert!(
+        output.lines().iter().any(|line| line.ends_with("Operands of '+' must be two numbers or two strings")),
+        "{:?}",
+        output.lines()
+    );
+}
+
+#[test]
+fn loose_without_strict() {
+    let output = common::run("loose", "if (0) print \"a\" + 1;\nprint !nil;\n", &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["a1", "true"]);
+}
+
+#[test]
+fn strict_does_not_suggest_constants() {
+    let output = common::run("strict-no-const", "var x = 1;\nprint x;\n", &["--strict"]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(output.lines(), vec!["1"]);
+}
+
+#[test]
+fn prefer_const_warns_without_strict_rules() {
+    let source = "var x = 1;\nvar y = 2;\ny = 3;\nprint \"a\" + x + y;\n";
+    let output = common::run("prefer-const", source, &["--prefer-const"]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(
+        output.lines(),
+        vec!["1 at 'x' Warning: Variable 'x' is never reassigned, it could be a const", "a13"]
+    );
+}
@@ -0,0 +1,74 @@
+// End-to-end tests for string literals: escapes, interpolation and the source encoding.
+
+mod common;
+
+#[test]
+fn escapes_write_their_characters() {
+    let source = r#"print "a\tb";
+print "say \"hi\"";
+print "back\\slash";
+print "\${not} interpolated";
+print "\u{41}\u{e9}\u{1F600}";
+print "two\nlines";
+"#;
+    let output = common::run("escapes", source, &[]);
+    assert!(output.success(), "{:?}", output.lines());
+    assert_eq!(
+        output.lines(),
+        vec!["a\tb", "say \"hi\"", "back\\slash", "${not} interpolated", "Aé😀", "two", "lines"]
+    );
+}
+
+#[test]
+fn bad_escapes_are_scan_errors() {
+    let source = r#"print "\q";
+print "\u{}";
+print "\u{D800}";
+print "\u{110000}";
+print "\u41";
+print "ok \q" + "\u{1234567}";
+"#;
+    let output = common::run("bad-escapes", source, &[]);
+    assert_eq!(output.code, Some(65));
+
+    // Each one is reported at its backslash, and the scanning goes on
+    assert_eq!(
+        output.lines(),
+        vec![
+            "1:8 Scan error: Unknown escape sequence '\\q'",
+            "2:8 Scan error: Invalid unicode escape",
+            "3:8 Scan error: Invalid unicode code point 'D800'",
+            "4:8 Scan error: Invalid unicode code point '110000'",
+            "5:8 Scan error: Expect '{' after '\\u'",
+            "6:11 Scan error: Unknown escape sequence '\\q'",
+            "6:18 Scan error: Invalid unicode escape",
+        ]
+    );
+}
+
+#[test]
+fn unterminated_strings_are_scan_errors() {
+    let cases = [
+        ("print \"abc", "1:11 Scan error: Unterminated string"),
+        ("print \"abc\\", "1:12 Scan error: Unterminated string"),
+        ("print \"a\nb", "2:2 Scan error: Unterminated string"),
+        ("print \"a ${1 + 2", "1:17 Scan error: Unterminated string interpolation"),
+    ];
+
+    for (source, expected) in cases {
+        let output = common::run("unterminated", source, &[]);
+        assert_eq!(output.code, Some(65), "{}", source);
+        assert_eq!(output.lines().first().map(String::as_str), Some(expected), "{}", source);
+    }
+}
+
+#[test]
+fn invalid_utf8_is_rejected() {
+    let path = common::temp_path("invalid-utf8.lox");
+    std::fs::write(&path, b"print \"caf\xe9\";\n").unwrap();
+
+    let output = common::run_file(&path, &[]);
+    assert_eq!(output.code, Some(65));
+    assert!(output.stdout.is_empty(), "{}", output.stdout);
+    assert!(output.stderr.contains("invalid-utf8.lox"), "{}", output.stderr);
+}
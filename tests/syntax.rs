@@ -0,0 +1,54 @@
+// End-to-end tests for malformed expressions, reported as parse errors instead of aborting.
+
+mod common;
+
+#[test]
+fn malformed_expressions_are_parse_errors() {
+    let cases = [
+        ("print 1 ? 2 3;", "1 at 'INTEGER' Expect ':' after THEN of conditional expression"),
+        ("print (1;", "1 at 'SEMICOLON' Expect ')' after expression"),
+        ("print clock(1;", "1 at 'SEMICOLON' Expect ')' after arguments"),
+        ("var a = [1]; print a[0;", "1 at 'SEMICOLON' Expect ']' after index"),
+        ("var a = [1]; print a[0:1;", "1 at 'SEMICOLON' Expect ']' after index"),
+        ("print [1, 2;", "1 at 'SEMICOLON' Expect ']' after list elements"),
+        ("print {1 2};", "1 at 'INTEGER' Expect ':' after map key"),
+        ("print {1: 2;", "1 at 'SEMICOLON' Expect '}' after map entries"),
+        ("var m = {}; print m.;", "1 at 'SEMICOLON' Expect member name after '.'"),
+    ];
+
+    for (source, expected) in cases {
+        let output = common::run("malformed", source, &[]);
+        assert_eq!(output.code, Some(65), "{}: {:?}", source, output.lines());
+        assert_eq!(output.lines(), vec![expected], "{}", source);
+    }
+}
+
+#[test]
+fn parsing_goes_on_after_a_malformed_expression() {
+    let source = "print [1, 2;\nvar x = 1;\nprint {x: 1;\nprint x;\n";
+    let output = common::run("recover", source, &[]);
+    assert_eq!(output.code, Some(65));
+    assert_eq!(
+        output.lines(),
+        vec![
+            "1 at 'SEMICOLON' Expect ']' after list elements",
+            "3 at 'SEMICOLON' Expect '}' after map entries",
+        ]
+    );
+}
+
+#[test]
+fn lint_reports_a_syntax_diagnostic() {
+    let path = common::temp_path("bad.lox");
+    std::fs::write(&path, "print [1, 2;\n").unwrap();
+
+    let output = common::rslox(&["lint", path.to_str().unwrap(), "--format", "json"]);
+    assert_eq!(output.code, Some(65), "{}", output.stderr);
+
+    let report: serde_json::Value = serde_json::from_str(&output.stdout).unwrap();
+    let diagnostics = report["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["lint"], "syntax");
+    assert_eq!(diagnostics[0]["severity"], "error");
+    assert_eq!(diagnostics[0]["line"], 1);
+}
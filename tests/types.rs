@@ -0,0 +1,86 @@
+// End-to-end tests for the type annotations, running scripts through `rslox -i`.
+
+mod common;
+
+// Runs a script, returning the type errors it reported and whether it succeeded
+fn run(name: &str, source: &str) -> (Vec<String>, bool) {
+    let output = common::run(name, source, &[]);
+    let errors = output.lines().into_iter().filter(|line| line.contains("Type error")).collect();
+    (errors, output.success())
+}
+
+// Each script must report a single type error, containing the expected message
+fn check_errors(cases: &[(&str, &str)]) {
+    for (i, (source, expected)) in cases.iter().enumerate() {
+        let (errors, success) = run(&format!("error-{}", i), source);
+        assert!(!success, "{}", source);
+        assert_eq!(errors.len(), 1, "{}: {:?}", source, errors);
+        assert!(errors[0].contains(expected), "{}: {:?}", source, errors);
+    }
+}
+
+#[test]
+fn variables() {
+    check_errors(&[
+        ("var x: number = \"a\";", "Cannot initialize variable of type number with string"),
+        ("var x: string;", "Variable of type string must be initialized"),
+        ("var x: number = 1;\nx = true;", "Cannot assign bool to variable of type number"),
+        ("var x: widget = 1;", "Unknown type 'widget'"),
+    ]);
+}
+
+#[test]
+fn parameters() {
+    let add = "const add = fun (a: number, b: number): number { return a + b; };\n";
+    check_errors(&[
+        (&format!("{}add(\"a\", 2);", add), "Argument 1 must be number, found string"),
+        (&format!("{}add(1);", add), "Expected 2 arguments but got 1"),
+        (&format!("{}var s: string = add(1, 2);", add), "Cannot initialize variable of type string with number"),
+        (&format!("{}var other = add;\nother(1, true);", add), "Argument 2 must be number, found bool"),
+        ("((s: string) => s)(1);", "Argument 1 must be string, found number"),
+        ("var f = fun (a: number) { return a - \"x\"; };", "Operands must be numbers, found number and string"),
+    ]);
+}
+
+#[test]
+fn returns() {
+    check_errors(&[
+        ("var f = fun (n: number): string { return n; };", "Cannot return number from a function returning string"),
+        ("var f = fun (): number { return; };", "Cannot return nil from a function returning number"),
+        ("var f = (n: number): bool => n + 1;", "Cannot return number from a function returning bool"),
+    ]);
+}
+
+#[test]
+fn gradual() {
+    let source = "\
+        var untyped = fun (a, b) { return a; };\n\
+        untyped(1, \"x\");\n\
+        var f = fun (a: number): number { return a; };\n\
+        f = fun (s) { return s; };\n\
+        f(true);\n\
+        const g = (x: number): number => x * 2;\n\
+        print g(3);\n";
+    let (errors, success) = run("gradual", source);
+    assert!(success, "{:?}", errors);
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn reassigned_signatures() {
+    // The assignments may never run, so the calls are no longer checked
+    let sources = [
+        "var f = (x: number) => x;\nif (false) f = (s: string) => s;\nprint f(1);",
+        "var f = (x: number) => x;\nvar never = fun () { f = (s: string) => s; };\nprint f(1);",
+        "var f = (x: number) => x;\nwhile (false) f = 1;\nprint f(1);",
+    ];
+    for (i, source) in sources.iter().enumerate() {
+        let (errors, success) = run(&format!("reassigned-{}", i), source);
+        assert!(success, "{}: {:?}", source, errors);
+    }
+
+    // Reassigned with the same signature, it is still checked
+    check_errors(&[
+        ("var f = (x: number) => x;\nif (false) f = (y: number) => y + 1;\nf(\"a\");", "Argument 1 must be number, found string"),
+    ]);
+}